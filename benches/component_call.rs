@@ -0,0 +1,369 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use wasmtime::component::{
+    CallBuffer, Component, ComponentType, Lift, Linker, Lower, TypedFunc, WasmList, WasmStr,
+};
+use wasmtime::{Config, Engine, Store};
+
+criterion_main!(benches);
+criterion_group!(
+    benches,
+    measure_execution_time,
+    measure_utf16_decoding,
+    measure_record_list_lifting,
+    measure_pod_list_lifting
+);
+
+type Params = (u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32);
+
+fn measure_execution_time(c: &mut Criterion) {
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    let engine = Engine::new(&config).unwrap();
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (core module $m
+                (func (export "nop"))
+                (func (export "sum")
+                    (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+                    (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add
+                    local.get 2
+                    i32.add
+                    local.get 3
+                    i32.add
+                    local.get 4
+                    i32.add
+                    local.get 5
+                    i32.add
+                    local.get 6
+                    i32.add
+                    local.get 7
+                    i32.add
+                    local.get 8
+                    i32.add
+                    local.get 9
+                    i32.add
+                    local.get 10
+                    i32.add
+                    local.get 11
+                    i32.add)
+            )
+            (core instance $i (instantiate $m))
+            (func (export "nop")
+                (canon lift (core func $i "nop"))
+            )
+            (func (export "sum")
+                (param u32) (param u32) (param u32) (param u32)
+                (param u32) (param u32) (param u32) (param u32)
+                (param u32) (param u32) (param u32) (param u32)
+                (result u32)
+                (canon lift (core func $i "sum"))
+            )
+        )"#,
+    )
+    .unwrap();
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine)
+        .instantiate(&mut store, &component)
+        .unwrap();
+    let sum: TypedFunc<Params, u32> = instance.get_typed_func(&mut store, "sum").unwrap();
+    let params = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12);
+
+    // Both ways of calling must produce identical results, including when the
+    // buffer is reused with different arguments.
+    let mut buffer = CallBuffer::new();
+    for i in 0..10 {
+        let params = (i, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, i * 2);
+        let expected = sum.call(&mut store, params).unwrap();
+        sum.post_return(&mut store).unwrap();
+        let actual = sum
+            .call_with_space(&mut store, params, &mut buffer)
+            .unwrap();
+        sum.post_return(&mut store).unwrap();
+        assert_eq!(expected, actual);
+        assert_eq!(actual, 65 + i * 3);
+    }
+
+    let nop: TypedFunc<(), ()> = instance.get_typed_func(&mut store, "nop").unwrap();
+    let mut group = c.benchmark_group("component-nop");
+    group.bench_function("call", |b| {
+        b.iter(|| {
+            nop.call(&mut store, ()).unwrap();
+            nop.post_return(&mut store).unwrap();
+        })
+    });
+    group.bench_function("call-with-space", |b| {
+        b.iter(|| {
+            nop.call_with_space(&mut store, (), &mut buffer).unwrap();
+            nop.post_return(&mut store).unwrap();
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("component-12-params");
+    group.bench_function("call", |b| {
+        b.iter(|| {
+            sum.call(&mut store, params).unwrap();
+            sum.post_return(&mut store).unwrap();
+        })
+    });
+    group.bench_function("call-with-space", |b| {
+        b.iter(|| {
+            sum.call_with_space(&mut store, params, &mut buffer)
+                .unwrap();
+            sum.post_return(&mut store).unwrap();
+        })
+    });
+    group.finish();
+}
+
+fn measure_utf16_decoding(c: &mut Criterion) {
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    let engine = Engine::new(&config).unwrap();
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (core module $m
+                (memory (export "memory") 128)
+                (global $last (mut i32) (i32.const 8))
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    (local $ret i32)
+                    ;; shrink in place
+                    (if (local.get 0) (then (return (local.get 0))))
+                    (local.set $ret (global.get $last))
+                    (global.set $last (i32.add (global.get $last) (local.get 3)))
+                    (local.get $ret))
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+            )
+            (core instance $i (instantiate $m))
+            (func (export "echo") (param string) (result string)
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                    string-encoding=utf16
+                )
+            )
+        )"#,
+    )
+    .unwrap();
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine)
+        .instantiate(&mut store, &component)
+        .unwrap();
+    let echo: TypedFunc<(&str,), WasmStr> = instance.get_typed_func(&mut store, "echo").unwrap();
+
+    let mut group = c.benchmark_group("component-utf16-to-str");
+    for (name, piece) in [
+        ("ascii", "The quick brown fox jumps over the lazy dog. "),
+        ("mixed", "hello, мир, 世界, 🌍! "),
+    ] {
+        // About 1 MB of utf-8.
+        let input = piece.repeat((1 << 20) / piece.len());
+        let s = echo.call(&mut store, (input.as_str(),)).unwrap();
+        assert_eq!(s.to_str(&store).unwrap(), input);
+        group.bench_function(name, |b| {
+            b.iter(|| s.to_str(&store).unwrap());
+        });
+        echo.post_return(&mut store).unwrap();
+    }
+    group.finish();
+}
+
+#[derive(ComponentType, Lift, Lower, Clone, Copy, PartialEq, Debug)]
+#[component(record)]
+struct Wide {
+    a: u8,
+    b: u32,
+    c: u64,
+    d: f32,
+    e: u16,
+    f: bool,
+}
+
+fn measure_record_list_lifting(c: &mut Criterion) {
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    let engine = Engine::new(&config).unwrap();
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (core module $m
+                (memory (export "memory") 128)
+                (global $last (mut i32) (i32.const 8))
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    (local $ret i32)
+                    ;; shrink in place
+                    (if (local.get 0) (then (return (local.get 0))))
+                    (local.set $ret (global.get $last))
+                    (global.set $last (i32.add (global.get $last) (local.get 3)))
+                    (local.get $ret))
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+            )
+            (core instance $i (instantiate $m))
+            (type $wide (record
+                (field "a" u8)
+                (field "b" u32)
+                (field "c" u64)
+                (field "d" float32)
+                (field "e" u16)
+                (field "f" bool)
+            ))
+            (func (export "echo") (param (list $wide)) (result (list $wide))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#,
+    )
+    .unwrap();
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine)
+        .instantiate(&mut store, &component)
+        .unwrap();
+    let echo: TypedFunc<(&[Wide],), WasmList<Wide>> =
+        instance.get_typed_func(&mut store, "echo").unwrap();
+
+    let input = (0..100_000u32)
+        .map(|i| Wide {
+            a: i as u8,
+            b: i,
+            c: (u64::from(i) << 32) | 7,
+            d: i as f32,
+            e: i as u16,
+            f: i % 3 == 0,
+        })
+        .collect::<Vec<_>>();
+    let list = echo.call(&mut store, (&input,)).unwrap();
+    let lift = |list: &WasmList<Wide>, store: &Store<()>| {
+        list.iter(store)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap()
+    };
+    assert_eq!(lift(&list, &store), input);
+
+    let mut group = c.benchmark_group("component-lift-records");
+    group.bench_function("100k-6-fields", |b| {
+        b.iter(|| lift(&list, &store));
+    });
+    group.finish();
+    echo.post_return(&mut store).unwrap();
+}
+
+#[derive(ComponentType, Lift, Lower, Clone, Copy, PartialEq, Debug)]
+#[component(record)]
+#[repr(C)]
+struct Vec4 {
+    x: f32,
+    y: f32,
+    z: f32,
+    w: f32,
+}
+
+#[derive(ComponentType, Lift, Lower, Clone, Copy, PartialEq, Debug)]
+#[component(record)]
+#[component(pod)]
+#[repr(C)]
+struct PodVec4 {
+    x: f32,
+    y: f32,
+    z: f32,
+    w: f32,
+}
+
+fn measure_pod_list_lifting(c: &mut Criterion) {
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    let engine = Engine::new(&config).unwrap();
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (core module $m
+                (memory (export "memory") 128)
+                (global $last (mut i32) (i32.const 8))
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    (local $ret i32)
+                    ;; shrink in place
+                    (if (local.get 0) (then (return (local.get 0))))
+                    (local.set $ret (global.get $last))
+                    (global.set $last (i32.add (global.get $last) (local.get 3)))
+                    (local.get $ret))
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+            )
+            (core instance $i (instantiate $m))
+            (type $vec4 (record
+                (field "x" float32)
+                (field "y" float32)
+                (field "z" float32)
+                (field "w" float32)
+            ))
+            (func (export "echo") (param (list $vec4)) (result (list $vec4))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#,
+    )
+    .unwrap();
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine)
+        .instantiate(&mut store, &component)
+        .unwrap();
+    let echo: TypedFunc<(&[PodVec4],), WasmList<PodVec4>> =
+        instance.get_typed_func(&mut store, "echo").unwrap();
+
+    let input = (0..100_000u32)
+        .map(|i| PodVec4 {
+            x: i as f32,
+            y: i as f32 * 0.5,
+            z: -(i as f32),
+            w: 1.0,
+        })
+        .collect::<Vec<_>>();
+    let pods = echo.call(&mut store, (&input,)).unwrap();
+    assert_eq!(pods.to_vec(&store).unwrap(), input);
+
+    // The same list lifted as a record which isn't `pod`, so its elements are
+    // loaded one at a time.
+    let func = echo.func();
+    let slow = func.typed::<(&[Vec4],), WasmList<Vec4>, _>(&store).unwrap();
+    echo.post_return(&mut store).unwrap();
+    let slow_input = input
+        .iter()
+        .map(|v| Vec4 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+            w: v.w,
+        })
+        .collect::<Vec<_>>();
+    let vecs = slow.call(&mut store, (&slow_input,)).unwrap();
+    assert_eq!(vecs.to_vec(&store).unwrap(), slow_input);
+
+    let mut group = c.benchmark_group("component-lift-pod-records");
+    group.bench_function("100k-vec4", |b| {
+        b.iter(|| vecs.to_vec(&store).unwrap());
+    });
+    group.bench_function("100k-pod-vec4", |b| {
+        b.iter(|| pods.to_vec(&store).unwrap());
+    });
+    group.finish();
+    slow.post_return(&mut store).unwrap();
+}
@@ -2,6 +2,7 @@ use proc_macro2::{Literal, TokenStream, TokenTree};
 use quote::{format_ident, quote};
 use std::collections::HashSet;
 use std::fmt;
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::{braced, parse_macro_input, parse_quote, Data, DeriveInput, Error, Result, Token};
@@ -28,6 +29,7 @@ impl fmt::Display for VariantStyle {
 enum Style {
     Record,
     Variant(VariantStyle),
+    Transparent,
 }
 
 fn find_style(input: &DeriveInput) -> Result<Style> {
@@ -54,10 +56,19 @@ fn find_style(input: &DeriveInput) -> Result<Style> {
         let style_string = if let [TokenTree::Group(group)] =
             &attribute.tokens.clone().into_iter().collect::<Vec<_>>()[..]
         {
-            if let [TokenTree::Ident(style)] = &group.stream().into_iter().collect::<Vec<_>>()[..] {
-                style.to_string()
-            } else {
-                return syntax_error();
+            match &group.stream().into_iter().collect::<Vec<_>>()[..] {
+                // `pod` isn't a style and is handled by
+                // `find_container_attributes`
+                [TokenTree::Ident(flag)] if "pod" == &flag.to_string() => continue,
+                [TokenTree::Ident(style)] => style.to_string(),
+                // `key = "value"` attributes are handled separately by
+                // `find_container_attributes`
+                [TokenTree::Ident(_), TokenTree::Punct(op), TokenTree::Literal(_)]
+                    if '=' == op.as_char() =>
+                {
+                    continue
+                }
+                _ => return syntax_error(),
             }
         } else {
             return syntax_error();
@@ -72,6 +83,7 @@ fn find_style(input: &DeriveInput) -> Result<Style> {
             "variant" => Style::Variant(VariantStyle::Variant),
             "enum" => Style::Variant(VariantStyle::Enum),
             "union" => Style::Variant(VariantStyle::Union),
+            "transparent" => Style::Transparent,
             "flags" => {
                 return Err(Error::new_spanned(
                     &attribute.tokens,
@@ -83,7 +95,7 @@ fn find_style(input: &DeriveInput) -> Result<Style> {
                 return Err(Error::new_spanned(
                     &attribute.tokens,
                     "unrecognized component type keyword \
-                     (expected `record`, `variant`, `enum`, or `union`)",
+                     (expected `record`, `variant`, `enum`, `union`, or `transparent`)",
                 ))
             }
         });
@@ -92,6 +104,163 @@ fn find_style(input: &DeriveInput) -> Result<Style> {
     style.ok_or_else(|| Error::new_spanned(input, "missing `component` attribute"))
 }
 
+/// Container-level renaming rules which can be specified with
+/// `#[component(rename_all = "...")]`.
+#[derive(Debug, Copy, Clone)]
+enum RenameAll {
+    /// Converts `snake_case` field names and `CamelCase` case names to
+    /// `kebab-case`, which is what the component model conventionally uses.
+    KebabCase,
+}
+
+impl RenameAll {
+    fn apply(&self, name: &str) -> String {
+        match self {
+            Self::KebabCase => {
+                let chars = name.chars().collect::<Vec<_>>();
+                let mut result = String::new();
+                for (index, &c) in chars.iter().enumerate() {
+                    if c == '_' {
+                        if !result.is_empty() && !result.ends_with('-') {
+                            result.push('-');
+                        }
+                        continue;
+                    }
+                    if c.is_uppercase() && index > 0 && !result.ends_with('-') {
+                        let prev = chars[index - 1];
+                        let next = chars.get(index + 1);
+                        if prev.is_lowercase()
+                            || prev.is_ascii_digit()
+                            || (prev.is_uppercase() && next.map_or(false, |n| n.is_lowercase()))
+                        {
+                            result.push('-');
+                        }
+                    }
+                    result.extend(c.to_lowercase());
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Options specified with `#[component(key = "value")]` attributes on the
+/// `struct` or `enum` being derived for.
+#[derive(Default)]
+struct ContainerAttributes {
+    /// A rule for renaming all fields or cases, if specified.
+    rename_all: Option<RenameAll>,
+
+    /// The names of a record's fields in the order they're declared in the
+    /// component type, if specified with `field_order = "a, b, c"`.
+    ///
+    /// When present fields are typechecked and lifted/lowered in this order
+    /// rather than the Rust declaration order.
+    field_order: Option<Vec<String>>,
+
+    /// Whether the record was marked with `#[component(pod)]`, meaning that
+    /// its Rust representation is identical to its representation in linear
+    /// memory so lists of it can be lifted with a single copy.
+    pod: bool,
+}
+
+fn find_container_attributes(attributes: &[syn::Attribute]) -> Result<ContainerAttributes> {
+    let mut result = ContainerAttributes::default();
+
+    for attribute in attributes {
+        if attribute.path.leading_colon.is_some() || attribute.path.segments.len() != 1 {
+            continue;
+        }
+
+        let ident = &attribute.path.segments[0].ident;
+
+        if "component" != &ident.to_string() {
+            continue;
+        }
+
+        let (key, literal) = if let [TokenTree::Group(group)] =
+            &attribute.tokens.clone().into_iter().collect::<Vec<_>>()[..]
+        {
+            match &group.stream().into_iter().collect::<Vec<_>>()[..] {
+                [TokenTree::Ident(key), TokenTree::Punct(op), TokenTree::Literal(literal)]
+                    if '=' == op.as_char() =>
+                {
+                    (key.clone(), literal.clone())
+                }
+                [TokenTree::Ident(flag)] if "pod" == &flag.to_string() => {
+                    if result.pod {
+                        return Err(Error::new(flag.span(), "duplicate `pod` attribute"));
+                    }
+                    result.pod = true;
+                    continue;
+                }
+                // Other `component` attributes are handled by `find_style`
+                _ => continue,
+            }
+        } else {
+            continue;
+        };
+
+        let value = syn::parse2::<syn::LitStr>(quote!(#literal))?.value();
+
+        match key.to_string().as_ref() {
+            "rename_all" => {
+                if result.rename_all.is_some() {
+                    return Err(Error::new(key.span(), "duplicate `rename_all` attribute"));
+                }
+
+                result.rename_all = Some(match value.as_ref() {
+                    "kebab-case" => RenameAll::KebabCase,
+                    _ => {
+                        return Err(Error::new(
+                            literal.span(),
+                            "unrecognized `rename_all` rule (expected \"kebab-case\")",
+                        ))
+                    }
+                });
+            }
+            "field_order" => {
+                if result.field_order.is_some() {
+                    return Err(Error::new(key.span(), "duplicate `field_order` attribute"));
+                }
+
+                result.field_order = Some(
+                    value
+                        .split(',')
+                        .map(|name| name.trim().to_owned())
+                        .filter(|name| !name.is_empty())
+                        .collect(),
+                );
+            }
+            _ => {
+                return Err(Error::new(
+                    key.span(),
+                    "unrecognized component attribute (expected `rename_all` or `field_order`)",
+                ))
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Determines the name used in the component type for the field or case named
+/// `ident`, taking into account both `#[component(name = "...")]` on the item
+/// itself and any container-level `rename_all` rule.
+fn component_name(
+    attributes: &[syn::Attribute],
+    ident: &syn::Ident,
+    rename_all: Option<RenameAll>,
+) -> Result<Literal> {
+    Ok(match find_rename(attributes)? {
+        Some(name) => name,
+        None => match rename_all {
+            Some(rule) => Literal::string(&rule.apply(&ident.unraw().to_string())),
+            None => Literal::string(&ident.to_string()),
+        },
+    })
+}
+
 fn find_rename(attributes: &[syn::Attribute]) -> Result<Option<Literal>> {
     let mut name = None;
 
@@ -138,16 +307,97 @@ fn find_rename(attributes: &[syn::Attribute]) -> Result<Option<Literal>> {
     Ok(name)
 }
 
-fn add_trait_bounds(generics: &syn::Generics, bound: syn::TypeParamBound) -> syn::Generics {
+/// Adds `bound` to each type parameter in `generics` which is mentioned by at
+/// least one of `types`.
+///
+/// Type parameters which only appear in `PhantomData` fields are left
+/// unconstrained since values of those types are never lifted or lowered.
+fn add_trait_bounds(
+    generics: &syn::Generics,
+    bound: syn::TypeParamBound,
+    types: &[&syn::Type],
+) -> syn::Generics {
     let mut generics = generics.clone();
     for param in &mut generics.params {
         if let syn::GenericParam::Type(ref mut type_param) = *param {
-            type_param.bounds.push(bound.clone());
+            if types
+                .iter()
+                .any(|ty| mentions_ident(quote!(#ty), &type_param.ident))
+            {
+                type_param.bounds.push(bound.clone());
+            }
         }
     }
     generics
 }
 
+fn mentions_ident(tokens: TokenStream, ident: &syn::Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(other) => other == *ident,
+        TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// Returns whether `ty` looks like `PhantomData<...>`, in which case the field
+/// is skipped entirely when deriving and is not part of the component type.
+fn is_phantom_data(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "PhantomData")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Returns the types of all `fields` which participate in the canonical ABI,
+/// namely everything except `PhantomData` fields.
+fn field_types<'a>(fields: &[&'a syn::Field]) -> Vec<&'a syn::Type> {
+    fields
+        .iter()
+        .map(|field| &field.ty)
+        .filter(|ty| !is_phantom_data(ty))
+        .collect()
+}
+
+/// Generates an inherent associated constant named `offsets` on `name` which
+/// holds the offset of each of `fields` within the record, in order, so the
+/// offsets are computed at compile time rather than for every value.
+///
+/// The derived `Lift` and `Lower` impls each generate their own constant so
+/// that either can be derived for a type with a hand-written `ComponentType`
+/// impl. Nothing is generated if there are no fields since the constant would
+/// be unused.
+fn expand_field_offsets(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+    fields: &[&syn::Field],
+    offsets: &syn::Ident,
+) -> TokenStream {
+    let internal = quote!(wasmtime::component::__internal);
+    let types = field_types(fields);
+    if types.is_empty() {
+        return TokenStream::new();
+    }
+    let count = types.len();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc(hidden)]
+            const #offsets: [usize; #count] = #internal::field_offsets([#((
+                <#types as wasmtime::component::ComponentType>::SIZE32,
+                <#types as wasmtime::component::ComponentType>::ALIGN32,
+            )),*]);
+        }
+    }
+}
+
+fn case_types<'a>(cases: &[VariantCase<'a>]) -> Vec<&'a syn::Type> {
+    cases.iter().filter_map(|case| case.ty).collect()
+}
+
 struct VariantCase<'a> {
     attrs: &'a [syn::Attribute],
     ident: &'a syn::Ident,
@@ -160,6 +410,7 @@ trait Expander {
         name: &syn::Ident,
         generics: &syn::Generics,
         fields: &[&syn::Field],
+        attributes: &ContainerAttributes,
     ) -> Result<TokenStream>;
 
     fn expand_variant(
@@ -169,13 +420,39 @@ trait Expander {
         discriminant_size: DiscriminantSize,
         cases: &[VariantCase],
         style: VariantStyle,
+        attributes: &ContainerAttributes,
+    ) -> Result<TokenStream>;
+
+    fn expand_transparent(
+        &self,
+        name: &syn::Ident,
+        generics: &syn::Generics,
+        member: &syn::Member,
+        ty: &syn::Type,
     ) -> Result<TokenStream>;
 }
 
 fn expand(expander: &dyn Expander, input: &DeriveInput) -> Result<TokenStream> {
-    match find_style(input)? {
+    let style = find_style(input)?;
+    if !matches!(style, Style::Record) {
+        let attributes = find_container_attributes(&input.attrs)?;
+        if attributes.pod {
+            return Err(Error::new(
+                input.ident.span(),
+                "`pod` is only supported for `record` component types",
+            ));
+        }
+        if attributes.field_order.is_some() {
+            return Err(Error::new(
+                input.ident.span(),
+                "`field_order` is only supported for `record` component types",
+            ));
+        }
+    }
+    match style {
         Style::Record => expand_record(expander, input),
         Style::Variant(style) => expand_variant(expander, input, style),
+        Style::Transparent => expand_transparent(expander, input),
     }
 }
 
@@ -192,11 +469,17 @@ fn expand_record(expander: &dyn Expander, input: &DeriveInput) -> Result<TokenSt
     };
 
     match &body.fields {
-        syn::Fields::Named(fields) => expander.expand_record(
-            &input.ident,
-            &input.generics,
-            &fields.named.iter().collect::<Vec<_>>(),
-        ),
+        syn::Fields::Named(fields) => {
+            let attributes = find_container_attributes(&input.attrs)?;
+            let mut fields = fields.named.iter().collect::<Vec<_>>();
+            if attributes.pod {
+                validate_pod(input, &fields, &attributes)?;
+            }
+            if let Some(order) = &attributes.field_order {
+                fields = order_fields(name, fields, order, attributes.rename_all)?;
+            }
+            expander.expand_record(&input.ident, &input.generics, &fields, &attributes)
+        }
 
         syn::Fields::Unnamed(_) | syn::Fields::Unit => Err(Error::new(
             name.span(),
@@ -205,6 +488,143 @@ fn expand_record(expander: &dyn Expander, input: &DeriveInput) -> Result<TokenSt
     }
 }
 
+/// Returns the size, which is also the alignment, of `ty` in the canonical ABI
+/// if it's a primitive type which may be a field of a `#[component(pod)]`
+/// record, namely an integer or a float.
+fn pod_field_size(ty: &syn::Type) -> Option<usize> {
+    let ident = match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path.get_ident()?,
+        _ => return None,
+    };
+    match ident.to_string().as_ref() {
+        "u8" | "i8" => Some(1),
+        "u16" | "i16" => Some(2),
+        "u32" | "i32" | "f32" => Some(4),
+        "u64" | "i64" | "f64" => Some(8),
+        _ => None,
+    }
+}
+
+fn is_float(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            path.is_ident("f32") || path.is_ident("f64")
+        }
+        _ => false,
+    }
+}
+
+/// Checks that the record `input` with `fields` can be marked with
+/// `#[component(pod)]`.
+///
+/// The Rust representation of such a record must be identical to its
+/// representation in linear memory on little-endian hosts. That's the case for
+/// `#[repr(C)]` structs whose fields are all integers and floats in the same
+/// order as the component type, as long as the canonical ABI doesn't insert
+/// any padding. Note that `bool`s and `char`s are rejected since not all byte
+/// patterns are valid values of them.
+fn validate_pod(
+    input: &DeriveInput,
+    fields: &[&syn::Field],
+    attributes: &ContainerAttributes,
+) -> Result<()> {
+    let name = &input.ident;
+
+    let repr_c = input.attrs.iter().any(|attribute| {
+        attribute.path.is_ident("repr")
+            && mentions_ident(attribute.tokens.clone(), &format_ident!("C"))
+    });
+    if !repr_c {
+        return Err(Error::new(
+            name.span(),
+            "`pod` records must be `#[repr(C)]`",
+        ));
+    }
+
+    if attributes.field_order.is_some() {
+        return Err(Error::new(
+            name.span(),
+            "`pod` records can't be combined with `field_order`",
+        ));
+    }
+
+    let mut size = 0;
+    let mut align = 1;
+    for field in fields {
+        let ty = &field.ty;
+        if is_phantom_data(ty) {
+            continue;
+        }
+        let field_size = match pod_field_size(ty) {
+            Some(field_size) => field_size,
+            None => {
+                let message = match quote!(#ty).to_string().as_ref() {
+                    "bool" => {
+                        "`pod` records can't have `bool` fields since not every byte is \
+                         a valid `bool`"
+                    }
+                    "char" => {
+                        "`pod` records can't have `char` fields since not every 32-bit \
+                         value is a valid `char`"
+                    }
+                    _ => "fields of `pod` records must be integers or floats",
+                };
+                return Err(Error::new_spanned(ty, message));
+            }
+        };
+        if size % field_size != 0 {
+            return Err(Error::new_spanned(
+                field,
+                format!(
+                    "`pod` records can't have padding, but there's padding before field `{}`",
+                    field.ident.as_ref().unwrap()
+                ),
+            ));
+        }
+        size += field_size;
+        align = align.max(field_size);
+    }
+    if size % align != 0 {
+        return Err(Error::new(
+            name.span(),
+            "`pod` records can't have padding, but there's padding after the last field",
+        ));
+    }
+
+    Ok(())
+}
+
+fn expand_transparent(expander: &dyn Expander, input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+
+    let body = if let Data::Struct(body) = &input.data {
+        body
+    } else {
+        return Err(Error::new(
+            name.span(),
+            "`transparent` component types can only be derived for Rust `struct`s",
+        ));
+    };
+
+    let mut fields = body.fields.iter();
+    let field = match (fields.next(), fields.next()) {
+        (Some(field), None) => field,
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "`transparent` component types can only be derived for `struct`s with exactly one field",
+            ))
+        }
+    };
+
+    let member = match &field.ident {
+        Some(ident) => syn::Member::Named(ident.clone()),
+        None => syn::Member::Unnamed(syn::Index::from(0)),
+    };
+
+    expander.expand_transparent(name, &input.generics, &member, &field.ty)
+}
+
 fn expand_variant(
     expander: &dyn Expander,
     input: &DeriveInput,
@@ -283,9 +703,75 @@ fn expand_variant(
         discriminant_size,
         &cases,
         style,
+        &find_container_attributes(&input.attrs)?,
     )
 }
 
+/// Reorders `fields` to follow `order`, the names of the fields as declared in
+/// the component type, so that the generated lift/lower/store/load code
+/// follows the component's order rather than the Rust declaration order.
+///
+/// This builds the permutation from the position of each field in the
+/// component type to its index in `fields`. `PhantomData` fields aren't part
+/// of the component type so they're placed at the end, and every other field
+/// must be named exactly once in `order`.
+fn order_fields<'a>(
+    name: &syn::Ident,
+    fields: Vec<&'a syn::Field>,
+    order: &[String],
+    rename_all: Option<RenameAll>,
+) -> Result<Vec<&'a syn::Field>> {
+    let component_names = fields
+        .iter()
+        .map(|field| {
+            if is_phantom_data(&field.ty) {
+                return Ok(None);
+            }
+            let component_name =
+                component_name(&field.attrs, field.ident.as_ref().unwrap(), rename_all)?;
+            Ok(Some(
+                syn::parse2::<syn::LitStr>(quote!(#component_name))?.value(),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut permutation = Vec::with_capacity(fields.len());
+    for expected in order {
+        let index = component_names
+            .iter()
+            .position(|component_name| component_name.as_ref() == Some(expected))
+            .ok_or_else(|| {
+                Error::new(
+                    name.span(),
+                    format!(
+                        "`field_order` names `{expected}` which is not a field of this `struct`"
+                    ),
+                )
+            })?;
+        if permutation.contains(&index) {
+            return Err(Error::new(
+                name.span(),
+                format!("`field_order` names `{expected}` more than once"),
+            ));
+        }
+        permutation.push(index);
+    }
+    for (index, component_name) in component_names.iter().enumerate() {
+        match component_name {
+            Some(missing) if !permutation.contains(&index) => {
+                return Err(Error::new(
+                    name.span(),
+                    format!("`field_order` is missing field `{missing}`"),
+                ));
+            }
+            Some(_) => {}
+            None => permutation.push(index),
+        }
+    }
+
+    Ok(permutation.into_iter().map(|index| fields[index]).collect())
+}
+
 fn expand_record_for_component_type(
     name: &syn::Ident,
     generics: &syn::Generics,
@@ -301,7 +787,11 @@ fn expand_record_for_component_type(
     let mut sizes = TokenStream::new();
     let mut unique_types = HashSet::new();
 
-    for (index, syn::Field { ident, ty, .. }) in fields.iter().enumerate() {
+    for (index, syn::Field { ident, ty, .. }) in fields
+        .iter()
+        .filter(|field| !is_phantom_data(&field.ty))
+        .enumerate()
+    {
         let generic = format_ident!("T{}", index);
 
         lower_generic_params.extend(quote!(#generic: Copy,));
@@ -327,7 +817,11 @@ fn expand_record_for_component_type(
         })
         .collect::<TokenStream>();
 
-    let generics = add_trait_bounds(generics, parse_quote!(wasmtime::component::ComponentType));
+    let generics = add_trait_bounds(
+        generics,
+        parse_quote!(wasmtime::component::ComponentType),
+        &field_types(fields),
+    );
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let lower = format_ident!("Lower{}", name);
 
@@ -414,13 +908,22 @@ impl Expander for LiftExpander {
         name: &syn::Ident,
         generics: &syn::Generics,
         fields: &[&syn::Field],
+        attributes: &ContainerAttributes,
     ) -> Result<TokenStream> {
         let internal = quote!(wasmtime::component::__internal);
 
         let mut lifts = TokenStream::new();
         let mut loads = TokenStream::new();
+        let offsets = format_ident!("__WASMTIME_LIFT_FIELD_OFFSETS");
+        let mut index = 0usize;
 
         for syn::Field { ident, ty, .. } in fields {
+            if is_phantom_data(ty) {
+                lifts.extend(quote!(#ident: std::marker::PhantomData,));
+                loads.extend(quote!(#ident: std::marker::PhantomData,));
+                continue;
+            }
+
             lifts.extend(quote!(#ident: <#ty as wasmtime::component::Lift>::lift(
                 store, options, &src.#ident
             )?,));
@@ -428,15 +931,31 @@ impl Expander for LiftExpander {
             loads.extend(quote!(#ident: <#ty as wasmtime::component::Lift>::load(
                 memory,
                 &bytes
-                    [#internal::next_field::<#ty>(&mut offset)..]
+                    [Self::#offsets[#index]..]
                     [..<#ty as wasmtime::component::ComponentType>::SIZE32]
             )?,));
+
+            index += 1;
         }
 
-        let generics = add_trait_bounds(generics, parse_quote!(wasmtime::component::Lift));
+        let generics = add_trait_bounds(
+            generics,
+            parse_quote!(wasmtime::component::Lift),
+            &field_types(fields),
+        );
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let (pod_layout, load_list) = if attributes.pod {
+            expand_pod_load_list(name, &generics, fields)
+        } else {
+            (TokenStream::new(), TokenStream::new())
+        };
+        let field_offsets = expand_field_offsets(name, &generics, fields, &offsets);
 
         let expanded = quote! {
+            #pod_layout
+
+            #field_offsets
+
             unsafe impl #impl_generics wasmtime::component::Lift for #name #ty_generics #where_clause {
                 #[inline]
                 fn lift(
@@ -456,11 +975,12 @@ impl Expander for LiftExpander {
                             % (<Self as wasmtime::component::ComponentType>::ALIGN32 as usize)
                             == 0
                     );
-                    let mut offset = 0;
                     Ok(Self {
                         #loads
                     })
                 }
+
+                #load_list
             }
         };
 
@@ -473,9 +993,11 @@ impl Expander for LiftExpander {
         generics: &syn::Generics,
         discriminant_size: DiscriminantSize,
         cases: &[VariantCase],
-        _style: VariantStyle,
+        style: VariantStyle,
+        _attributes: &ContainerAttributes,
     ) -> Result<TokenStream> {
         let internal = quote!(wasmtime::component::__internal);
+        let style = style.to_string();
 
         let mut lifts = TokenStream::new();
         let mut loads = TokenStream::new();
@@ -492,19 +1014,27 @@ impl Expander for LiftExpander {
                     )?),),
                 );
 
-                loads.extend(
-                    quote!(#index_quoted => Self::#ident(<#ty as wasmtime::component::Lift>::load(
-                        memory, &payload[..<#ty as wasmtime::component::ComponentType>::SIZE32]
-                    )?),),
-                );
+                loads.extend(quote!(#index_quoted => {
+                    let size = <#ty as wasmtime::component::ComponentType>::SIZE32;
+                    (
+                        Self::#ident(<#ty as wasmtime::component::Lift>::load(
+                            memory, &payload[..size]
+                        )?),
+                        size,
+                    )
+                }));
             } else {
                 lifts.extend(quote!(#index_u32 => Self::#ident,));
 
-                loads.extend(quote!(#index_quoted => Self::#ident,));
+                loads.extend(quote!(#index_quoted => (Self::#ident, 0),));
             }
         }
 
-        let generics = add_trait_bounds(generics, parse_quote!(wasmtime::component::Lift));
+        let generics = add_trait_bounds(
+            generics,
+            parse_quote!(wasmtime::component::Lift),
+            &case_types(cases),
+        );
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
         let from_bytes = match discriminant_size {
@@ -513,7 +1043,7 @@ impl Expander for LiftExpander {
             DiscriminantSize::Size4 => quote!(u32::from_le_bytes(bytes[0..4].try_into()?)),
         };
 
-        let payload_offset = usize::from(discriminant_size);
+        let discriminant_size = usize::from(discriminant_size);
 
         let expanded = quote! {
             unsafe impl #impl_generics wasmtime::component::Lift for #name #ty_generics #where_clause {
@@ -525,7 +1055,10 @@ impl Expander for LiftExpander {
                 ) -> #internal::anyhow::Result<Self> {
                     Ok(match src.tag.get_u32() {
                         #lifts
-                        discrim => #internal::anyhow::bail!("unexpected discriminant: {}", discrim),
+                        discrim => return Err(#internal::LiftError::InvalidDiscriminant {
+                            ty: #style,
+                            value: discrim,
+                        }.into()),
                     })
                 }
 
@@ -534,10 +1067,59 @@ impl Expander for LiftExpander {
                     let align = <Self as wasmtime::component::ComponentType>::ALIGN32;
                     debug_assert!((bytes.as_ptr() as usize) % (align as usize) == 0);
                     let discrim = #from_bytes;
-                    let payload = &bytes[#internal::align_to(#payload_offset, align)..];
-                    Ok(match discrim {
+                    let payload_offset = #internal::align_to(#discriminant_size, align);
+                    let payload = &bytes[payload_offset..];
+                    let (value, payload_size) = match discrim {
                         #loads
-                        discrim => #internal::anyhow::bail!("unexpected discriminant: {}", discrim),
+                        discrim => return Err(#internal::LiftError::InvalidDiscriminant {
+                            ty: #style,
+                            value: discrim.into(),
+                        }.into()),
+                    };
+                    #internal::validate_variant_padding(
+                        memory,
+                        &bytes[..<Self as wasmtime::component::ComponentType>::SIZE32],
+                        #discriminant_size,
+                        payload_offset,
+                        payload_size,
+                    )?;
+                    Ok(value)
+                }
+            }
+        };
+
+        Ok(expanded)
+    }
+
+    fn expand_transparent(
+        &self,
+        name: &syn::Ident,
+        generics: &syn::Generics,
+        member: &syn::Member,
+        ty: &syn::Type,
+    ) -> Result<TokenStream> {
+        let internal = quote!(wasmtime::component::__internal);
+
+        let generics = add_trait_bounds(generics, parse_quote!(wasmtime::component::Lift), &[ty]);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let expanded = quote! {
+            unsafe impl #impl_generics wasmtime::component::Lift for #name #ty_generics #where_clause {
+                #[inline]
+                fn lift(
+                    store: &#internal::StoreOpaque,
+                    options: &#internal::Options,
+                    src: &Self::Lower,
+                ) -> #internal::anyhow::Result<Self> {
+                    Ok(Self {
+                        #member: <#ty as wasmtime::component::Lift>::lift(store, options, src)?,
+                    })
+                }
+
+                #[inline]
+                fn load(memory: &#internal::Memory, bytes: &[u8]) -> #internal::anyhow::Result<Self> {
+                    Ok(Self {
+                        #member: <#ty as wasmtime::component::Lift>::load(memory, bytes)?,
                     })
                 }
             }
@@ -547,6 +1129,124 @@ impl Expander for LiftExpander {
     }
 }
 
+/// Generates the override of `Lift::load_list` for a `#[component(pod)]`
+/// record along with an inherent associated constant asserting that its layout
+/// matches the canonical ABI, which `validate_pod` already checked as far as
+/// possible by looking at the names of the field types.
+///
+/// The type of each field is additionally required to implement `PodField`,
+/// so that aliases such as `type u8 = bool;` are rejected, and the offset of
+/// each field in the `#[repr(C)]` layout is asserted to be its offset in the
+/// canonical ABI.
+///
+/// Lists are copied out of linear memory all at once on little-endian hosts,
+/// after which only the NaNs of float fields need to be canonicalized.
+fn expand_pod_load_list(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+    fields: &[&syn::Field],
+) -> (TokenStream, TokenStream) {
+    let internal = quote!(wasmtime::component::__internal);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let pod_fields = fields
+        .iter()
+        .filter(|field| !is_phantom_data(&field.ty))
+        .collect::<Vec<_>>();
+    let types = pod_fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let count = types.len();
+    let field_checks = pod_fields.iter().enumerate().map(|(index, field)| {
+        let ty = &field.ty;
+        let message = format!(
+            "the layout of field `{}` of a `pod` record doesn't match the canonical ABI",
+            field.ident.as_ref().unwrap()
+        );
+        quote!(assert!(
+            rust[#index] == canonical[#index]
+                && std::mem::size_of::<#ty>()
+                    == <#ty as wasmtime::component::ComponentType>::SIZE32,
+            #message
+        );)
+    });
+
+    // The offsets of `#[repr(C)]` fields in Rust are computed with the same
+    // algorithm as the canonical ABI but with the host's sizes and alignments.
+    let offsets = if count == 0 {
+        TokenStream::new()
+    } else {
+        quote! {
+            let rust: [usize; #count] = #internal::field_offsets([#((
+                std::mem::size_of::<#types>(),
+                std::mem::align_of::<#types>() as u32,
+            )),*]);
+            let canonical: [usize; #count] = #internal::field_offsets([#((
+                <#types as wasmtime::component::ComponentType>::SIZE32,
+                <#types as wasmtime::component::ComponentType>::ALIGN32,
+            )),*]);
+            #(#field_checks)*
+        }
+    };
+
+    let layout = quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc(hidden)]
+            const __WASMTIME_POD_LAYOUT: () = {
+                assert!(
+                    std::mem::size_of::<Self>()
+                        == <Self as wasmtime::component::ComponentType>::SIZE32
+                        && std::mem::align_of::<Self>()
+                            <= <Self as wasmtime::component::ComponentType>::ALIGN32 as usize,
+                    "the layout of a `pod` record doesn't match the canonical ABI"
+                );
+                #offsets
+            };
+        }
+    };
+
+    // Canonicalize NaNs just as the `Lift` implementations of floats do.
+    let floats = fields
+        .iter()
+        .filter(|field| is_float(&field.ty))
+        .map(|syn::Field { ident, ty, .. }| {
+            quote!(if value.#ident.is_nan() {
+                value.#ident = #ty::NAN;
+            })
+        })
+        .collect::<Vec<_>>();
+    let copy = quote!(unsafe { #internal::load_pod_list(bytes, len, dst) });
+    let copy = if floats.is_empty() {
+        quote!(#copy;)
+    } else {
+        quote!(for value in #copy {
+            #(#floats)*
+        })
+    };
+
+    let load_list = quote! {
+        #[inline]
+        fn load_list(
+            memory: &#internal::Memory,
+            bytes: &[u8],
+            len: usize,
+            dst: &mut Vec<Self>,
+        ) -> #internal::anyhow::Result<()> {
+            if cfg!(target_endian = "little") {
+                let () = Self::__WASMTIME_POD_LAYOUT;
+                #(#internal::assert_pod_field::<#types>();)*
+                // Safety: `validate_pod`, the assertions above, and the
+                // `PodField` bounds check that this is a `#[repr(C)]` struct
+                // of integers and floats laid out as in the canonical ABI.
+                #copy
+                Ok(())
+            } else {
+                #internal::load_list(memory, bytes, len, dst)
+            }
+        }
+    };
+
+    (layout, load_list)
+}
+
 #[proc_macro_derive(Lower, attributes(component))]
 pub fn lower(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     expand(&LowerExpander, &parse_macro_input!(input as DeriveInput))
@@ -562,26 +1262,42 @@ impl Expander for LowerExpander {
         name: &syn::Ident,
         generics: &syn::Generics,
         fields: &[&syn::Field],
+        _attributes: &ContainerAttributes,
     ) -> Result<TokenStream> {
         let internal = quote!(wasmtime::component::__internal);
 
         let mut lowers = TokenStream::new();
         let mut stores = TokenStream::new();
+        let offsets = format_ident!("__WASMTIME_LOWER_FIELD_OFFSETS");
+        let mut index = 0usize;
 
         for syn::Field { ident, ty, .. } in fields {
+            if is_phantom_data(ty) {
+                continue;
+            }
+
             lowers.extend(quote!(wasmtime::component::Lower::lower(
                 &self.#ident, store, options, #internal::map_maybe_uninit!(dst.#ident)
             )?;));
 
             stores.extend(quote!(wasmtime::component::Lower::store(
-                &self.#ident, memory, #internal::next_field::<#ty>(&mut offset)
+                &self.#ident, memory, offset + Self::#offsets[#index]
             )?;));
+
+            index += 1;
         }
 
-        let generics = add_trait_bounds(generics, parse_quote!(wasmtime::component::Lower));
+        let generics = add_trait_bounds(
+            generics,
+            parse_quote!(wasmtime::component::Lower),
+            &field_types(fields),
+        );
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let field_offsets = expand_field_offsets(name, &generics, fields, &offsets);
 
         let expanded = quote! {
+            #field_offsets
+
             unsafe impl #impl_generics wasmtime::component::Lower for #name #ty_generics #where_clause {
                 #[inline]
                 fn lower<T>(
@@ -598,7 +1314,7 @@ impl Expander for LowerExpander {
                 fn store<T>(
                     &self,
                     memory: &mut #internal::MemoryMut<'_, T>,
-                    mut offset: usize
+                    offset: usize
                 ) -> #internal::anyhow::Result<()> {
                     debug_assert!(offset % (<Self as wasmtime::component::ComponentType>::ALIGN32 as usize) == 0);
                     #stores
@@ -617,6 +1333,7 @@ impl Expander for LowerExpander {
         discriminant_size: DiscriminantSize,
         cases: &[VariantCase],
         _style: VariantStyle,
+        _attributes: &ContainerAttributes,
     ) -> Result<TokenStream> {
         let internal = quote!(wasmtime::component::__internal);
 
@@ -661,7 +1378,11 @@ impl Expander for LowerExpander {
             }));
         }
 
-        let generics = add_trait_bounds(generics, parse_quote!(wasmtime::component::Lower));
+        let generics = add_trait_bounds(
+            generics,
+            parse_quote!(wasmtime::component::Lower),
+            &case_types(cases),
+        );
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
         let expanded = quote! {
@@ -701,6 +1422,44 @@ impl Expander for LowerExpander {
 
         Ok(expanded)
     }
+
+    fn expand_transparent(
+        &self,
+        name: &syn::Ident,
+        generics: &syn::Generics,
+        member: &syn::Member,
+        ty: &syn::Type,
+    ) -> Result<TokenStream> {
+        let internal = quote!(wasmtime::component::__internal);
+
+        let generics = add_trait_bounds(generics, parse_quote!(wasmtime::component::Lower), &[ty]);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let expanded = quote! {
+            unsafe impl #impl_generics wasmtime::component::Lower for #name #ty_generics #where_clause {
+                #[inline]
+                fn lower<T>(
+                    &self,
+                    store: &mut wasmtime::StoreContextMut<T>,
+                    options: &#internal::Options,
+                    dst: &mut std::mem::MaybeUninit<Self::Lower>,
+                ) -> #internal::anyhow::Result<()> {
+                    <#ty as wasmtime::component::Lower>::lower(&self.#member, store, options, dst)
+                }
+
+                #[inline]
+                fn store<T>(
+                    &self,
+                    memory: &mut #internal::MemoryMut<'_, T>,
+                    offset: usize
+                ) -> #internal::anyhow::Result<()> {
+                    <#ty as wasmtime::component::Lower>::store(&self.#member, memory, offset)
+                }
+            }
+        };
+
+        Ok(expanded)
+    }
 }
 
 #[proc_macro_derive(ComponentType, attributes(component))]
@@ -721,21 +1480,26 @@ impl Expander for ComponentTypeExpander {
         name: &syn::Ident,
         generics: &syn::Generics,
         fields: &[&syn::Field],
+        attributes: &ContainerAttributes,
     ) -> Result<TokenStream> {
         expand_record_for_component_type(
             name,
             generics,
             fields,
-            quote!(typecheck_record),
+            if attributes.field_order.is_some() {
+                quote!(typecheck_record_with_field_order)
+            } else {
+                quote!(typecheck_record)
+            },
             fields
                 .iter()
+                .filter(|field| !is_phantom_data(&field.ty))
                 .map(
                     |syn::Field {
                          attrs, ident, ty, ..
                      }| {
-                        let name = find_rename(attrs)?.unwrap_or_else(|| {
-                            Literal::string(&ident.as_ref().unwrap().to_string())
-                        });
+                        let name =
+                            component_name(attrs, ident.as_ref().unwrap(), attributes.rename_all)?;
 
                         Ok(quote!((#name, <#ty as wasmtime::component::ComponentType>::typecheck),))
                     },
@@ -751,6 +1515,7 @@ impl Expander for ComponentTypeExpander {
         discriminant_size: DiscriminantSize,
         cases: &[VariantCase],
         style: VariantStyle,
+        attributes: &ContainerAttributes,
     ) -> Result<TokenStream> {
         let internal = quote!(wasmtime::component::__internal);
 
@@ -772,7 +1537,10 @@ impl Expander for ComponentTypeExpander {
                 ));
             }
 
-            let name = rename.unwrap_or_else(|| Literal::string(&ident.to_string()));
+            let name = match rename {
+                Some(name) => name,
+                None => component_name(&[], ident, attributes.rename_all)?,
+            };
 
             if let Some(ty) = ty {
                 sizes.extend({
@@ -839,7 +1607,11 @@ impl Expander for ComponentTypeExpander {
             VariantStyle::Enum => quote!(typecheck_enum),
         };
 
-        let generics = add_trait_bounds(generics, parse_quote!(wasmtime::component::ComponentType));
+        let generics = add_trait_bounds(
+            generics,
+            parse_quote!(wasmtime::component::ComponentType),
+            &case_types(cases),
+        );
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         let lower = format_ident!("Lower{}", name);
         let lower_payload = format_ident!("LowerPayload{}", name);
@@ -897,6 +1669,46 @@ impl Expander for ComponentTypeExpander {
 
         Ok(quote!(const _: () = { #expanded };))
     }
+
+    fn expand_transparent(
+        &self,
+        name: &syn::Ident,
+        generics: &syn::Generics,
+        _member: &syn::Member,
+        ty: &syn::Type,
+    ) -> Result<TokenStream> {
+        let internal = quote!(wasmtime::component::__internal);
+
+        let generics = add_trait_bounds(
+            generics,
+            parse_quote!(wasmtime::component::ComponentType),
+            &[ty],
+        );
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let expanded = quote! {
+            unsafe impl #impl_generics wasmtime::component::ComponentType for #name #ty_generics #where_clause {
+                type Lower = <#ty as wasmtime::component::ComponentType>::Lower;
+
+                const SIZE32: usize = <#ty as wasmtime::component::ComponentType>::SIZE32;
+                const ALIGN32: u32 = <#ty as wasmtime::component::ComponentType>::ALIGN32;
+
+                fn describe() -> String {
+                    <#ty as wasmtime::component::ComponentType>::describe()
+                }
+
+                #[inline]
+                fn typecheck(
+                    ty: &#internal::InterfaceType,
+                    types: &#internal::ComponentTypes,
+                ) -> #internal::anyhow::Result<()> {
+                    <#ty as wasmtime::component::ComponentType>::typecheck(ty, types)
+                }
+            }
+        };
+
+        Ok(expanded)
+    }
 }
 
 #[derive(Debug)]
@@ -949,6 +1761,17 @@ impl Parse for Flags {
     }
 }
 
+/// Defines a `flags` type, a set of named bits.
+///
+/// Besides the per-flag constants and the bitwise operators the generated
+/// type has `to_bits`, `from_bits`, `from_bits_truncate`, `all`, `is_empty`,
+/// `contains`, and `iter_names` methods.  The bits are a `u8`, `u16`, or
+/// `u32` for up to 8, 16, or 32 flags respectively and a `[u32; N]` for more
+/// than that, with the `i`th declared flag in bit `i % 32` of word `i / 32`.
+///
+/// Bits which don't correspond to a declared flag are never reported by
+/// `to_bits`, make `from_bits` return `None`, and are discarded by
+/// `from_bits_truncate`.
 #[proc_macro]
 pub fn flags(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     expand_flags(&parse_macro_input!(input as Flags))
@@ -958,6 +1781,7 @@ pub fn flags(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
 fn expand_flags(flags: &Flags) -> Result<TokenStream> {
     let size = FlagsSize::from_count(flags.flags.len());
+    let flag_count = flags.flags.len();
 
     let ty;
     let eq;
@@ -1072,6 +1896,81 @@ fn expand_flags(flags: &Flags) -> Result<TokenStream> {
         }
     };
 
+    // The "bits" representation is the backing integer itself when a single
+    // word suffices and `[u32; N]` otherwise, masked to the declared flags.
+    let bits_ty;
+    let to_bits;
+    let from_bits_valid;
+    let from_bits_truncate;
+
+    match size {
+        FlagsSize::Size1 => {
+            let mask = if flag_count == 8 {
+                u8::MAX
+            } else {
+                !(u8::MAX << flag_count)
+            };
+            bits_ty = quote!(u8);
+            to_bits = quote!(self.__inner0 & #mask);
+            from_bits_valid = quote!(bits & !#mask == 0);
+            from_bits_truncate = quote!(Self {
+                __inner0: bits & #mask
+            });
+        }
+        FlagsSize::Size2 => {
+            let mask = if flag_count == 16 {
+                u16::MAX
+            } else {
+                !(u16::MAX << flag_count)
+            };
+            bits_ty = quote!(u16);
+            to_bits = quote!(self.__inner0 & #mask);
+            from_bits_valid = quote!(bits & !#mask == 0);
+            from_bits_truncate = quote!(Self {
+                __inner0: bits & #mask
+            });
+        }
+        FlagsSize::Size4Plus(n) => {
+            let masks = (0..n)
+                .map(|index| {
+                    let remaining = flag_count - index * 32;
+                    if remaining >= 32 {
+                        u32::MAX
+                    } else {
+                        !(u32::MAX << remaining)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if n == 1 {
+                let mask = masks[0];
+                bits_ty = quote!(u32);
+                to_bits = quote!(self.__inner0 & #mask);
+                from_bits_valid = quote!(bits & !#mask == 0);
+                from_bits_truncate = quote!(Self {
+                    __inner0: bits & #mask
+                });
+            } else {
+                let mut words = TokenStream::new();
+                let mut valid = TokenStream::new();
+                let mut truncate = TokenStream::new();
+
+                for (index, mask) in masks.iter().enumerate() {
+                    let field = format_ident!("__inner{}", index);
+
+                    words.extend(quote!(self.#field & #mask,));
+                    valid.extend(quote!(bits[#index] & !#mask == 0 &&));
+                    truncate.extend(quote!(#field: bits[#index] & #mask,));
+                }
+
+                bits_ty = quote!([u32; #n]);
+                to_bits = quote!([#words]);
+                from_bits_valid = quote!(#valid true);
+                from_bits_truncate = quote!(Self { #truncate });
+            }
+        }
+    }
+
     let name = format_ident!("{}", flags.name);
 
     let mut constants = TokenStream::new();
@@ -1144,9 +2043,11 @@ fn expand_flags(flags: &Flags) -> Result<TokenStream> {
         component_names,
     )?;
 
-    let lower_impl = LowerExpander.expand_record(&name, &generics, &fields)?;
+    let attributes = ContainerAttributes::default();
+
+    let lower_impl = LowerExpander.expand_record(&name, &generics, &fields, &attributes)?;
 
-    let lift_impl = LiftExpander.expand_record(&name, &generics, &fields)?;
+    let lift_impl = LiftExpander.expand_record(&name, &generics, &fields, &attributes)?;
 
     let internal = quote!(wasmtime::component::__internal);
 
@@ -1167,6 +2068,52 @@ fn expand_flags(flags: &Flags) -> Result<TokenStream> {
             }
         }
 
+        #[allow(dead_code)]
+        impl #name {
+            /// Returns the set of all declared flags.
+            fn all() -> Self {
+                !Self::default()
+            }
+
+            /// Returns the bits of the flags which are set, with bit `i`
+            /// corresponding to the `i`th declared flag.
+            fn to_bits(&self) -> #bits_ty {
+                #to_bits
+            }
+
+            /// Creates a set of flags from `bits`, returning `None` if any bit
+            /// beyond the declared flags is set.
+            fn from_bits(bits: #bits_ty) -> Option<Self> {
+                if #from_bits_valid {
+                    Some(Self::from_bits_truncate(bits))
+                } else {
+                    None
+                }
+            }
+
+            /// Creates a set of flags from `bits`, discarding any bit beyond
+            /// the declared flags.
+            fn from_bits_truncate(bits: #bits_ty) -> Self {
+                #from_bits_truncate
+            }
+
+            /// Returns whether no flags are set.
+            fn is_empty(&self) -> bool {
+                *self == Self::default()
+            }
+
+            /// Returns whether every flag set in `other` is also set in `self`.
+            fn contains(&self, other: Self) -> bool {
+                (*self & other) == other
+            }
+
+            /// Returns an iterator over the names of the flags which are set,
+            /// in declaration order.
+            fn iter_names(&self) -> impl Iterator<Item = &'static str> {
+                #internal::flag_names(self.as_array(), &[#rust_names])
+            }
+        }
+
         impl std::cmp::PartialEq for #name {
             fn eq(&self, rhs: &#name) -> bool {
                 #eq
@@ -1177,7 +2124,7 @@ fn expand_flags(flags: &Flags) -> Result<TokenStream> {
 
         impl std::fmt::Debug for #name {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                #internal::format_flags(&self.as_array(), &[#rust_names], f)
+                #internal::format_flags(&self.as_array(), #count, &[#rust_names], f)
             }
         }
 
@@ -4,9 +4,12 @@ use crate::component::values::Val;
 use crate::store::{StoreOpaque, Stored};
 use crate::{AsContext, AsContextMut, StoreContextMut, ValRaw};
 use anyhow::{bail, Context, Result};
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::marker::PhantomData;
 use std::mem::{self, MaybeUninit};
 use std::ptr::NonNull;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use wasmtime_environ::component::{
     CanonicalOptions, ComponentTypes, CoreDef, RuntimeComponentInstanceIndex, TypeFuncIndex,
     MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
@@ -65,9 +68,11 @@ impl<T> MaybeUninitExt<T> for MaybeUninit<T> {
     }
 }
 
+mod error;
 mod host;
 mod options;
 mod typed;
+pub use self::error::*;
 pub use self::host::*;
 pub use self::options::*;
 pub use self::typed::*;
@@ -86,6 +91,7 @@ pub struct Func(Stored<FuncData>);
 
 #[doc(hidden)]
 pub struct FuncData {
+    name: Box<str>,
     trampoline: VMTrampoline,
     export: ExportFunction,
     ty: TypeFuncIndex,
@@ -94,11 +100,74 @@ pub struct FuncData {
     instance: Instance,
     component_instance: RuntimeComponentInstanceIndex,
     post_return: Option<(ExportFunction, VMTrampoline)>,
-    post_return_arg: Option<ValRaw>,
+    post_return_state: PostReturnState,
+    typecheck_cache: Arc<TypecheckCache>,
+}
+
+/// Where the most recent call of a [`Func`] is with respect to its
+/// `post-return`.
+#[derive(Copy, Clone)]
+enum PostReturnState {
+    /// The function hasn't been called yet.
+    Idle,
+    /// The function was called successfully and `post-return` must be invoked
+    /// with the contained argument.
+    Pending(ValRaw),
+    /// `post-return` was invoked for the most recent call.
+    Completed,
+    /// The most recent call failed, so there's no `post-return` to invoke.
+    CallFailed,
+}
+
+/// A cache of the successful typechecks performed by [`Func::typed`], shared
+/// amongst all functions exported from one component instance.
+///
+/// Entries are keyed by the function's type and the `TypeId` of the `Params`
+/// and `Return` in question, see `type_id_ignoring_lifetimes`.
+pub(crate) type TypecheckCache = Mutex<HashSet<(TypeFuncIndex, TypeId)>>;
+
+/// Returns the `TypeId` of `T` as if all of its lifetimes were `'static`.
+///
+/// `TypeId::of` requires `T: 'static`, but parameters such as `(&str,)`
+/// borrow from the caller. Lifetimes don't affect typechecking, though, and
+/// they're erased before code generation anyway, so `T` and its `'static`
+/// counterpart have the same `TypeId`.
+fn type_id_ignoring_lifetimes<T: ?Sized>() -> TypeId {
+    trait NonStaticAny {
+        fn get_type_id(&self) -> TypeId
+        where
+            Self: 'static;
+    }
+
+    impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+        fn get_type_id(&self) -> TypeId
+        where
+            Self: 'static,
+        {
+            TypeId::of::<T>()
+        }
+    }
+
+    // Extending the lifetime of the trait object is what makes `get_type_id`
+    // callable. Nothing is borrowed through it, and `PhantomData` has no
+    // data, so this can't be used to reach anything that's dangling.
+    let phantom = PhantomData::<T>;
+    NonStaticAny::get_type_id(unsafe {
+        mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&phantom)
+    })
+}
+
+fn typecheck_func<Params, Return>(ty: TypeFuncIndex, types: &ComponentTypes) -> Result<()>
+where
+    Params: ComponentParams + Lower,
+    Return: Lift,
+{
+    typecheck_signature::<Params, Return>(&types[ty], types)
 }
 
 impl Func {
     pub(crate) fn from_lifted_func(
+        name: &str,
         store: &mut StoreOpaque,
         instance: &Instance,
         data: &InstanceData,
@@ -121,8 +190,11 @@ impl Func {
             (ExportFunction { anyfunc }, trampoline)
         });
         let component_instance = options.instance;
-        let options = unsafe { Options::new(store.id(), memory, realloc, options.string_encoding) };
+        let shared_memory = options.memory.map_or(false, |i| data.is_shared_memory(i));
+        let options = unsafe { Options::new(store.id(), memory, realloc, options.string_encoding) }
+            .with_shared_memory(shared_memory);
         Func(store.store_data_mut().insert(FuncData {
+            name: name.into(),
             trampoline,
             export,
             options,
@@ -131,7 +203,8 @@ impl Func {
             instance: *instance,
             component_instance,
             post_return,
-            post_return_arg: None,
+            post_return_state: PostReturnState::Idle,
+            typecheck_cache: data.typecheck_cache().clone(),
         }))
     }
 
@@ -239,12 +312,15 @@ impl Func {
         Return: Lift,
     {
         let data = &store[self.0];
-        let ty = &data.types[data.ty];
-
-        Params::typecheck_params(&ty.params, &data.types)
-            .context("type mismatch with parameters")?;
-        Return::typecheck(&ty.result, &data.types).context("type mismatch with result")?;
+        let key = (data.ty, type_id_ignoring_lifetimes::<(Params, Return)>());
+        if data.typecheck_cache.lock().unwrap().contains(&key) {
+            return Ok(());
+        }
 
+        // Note that only successful typechecks are cached, so a mismatch is
+        // diagnosed afresh each time.
+        typecheck_func::<Params, Return>(data.ty, &data.types)?;
+        data.typecheck_cache.lock().unwrap().insert(key);
         Ok(())
     }
 
@@ -258,6 +334,29 @@ impl Func {
             .collect()
     }
 
+    /// Get the parameters of this function along with their names, if the
+    /// component gave them names.
+    pub fn named_params(&self, store: impl AsContext) -> Box<[(Option<String>, Type)]> {
+        let data = &store.as_context()[self.0];
+        data.types[data.ty]
+            .params
+            .iter()
+            .map(|(name, ty)| (name.clone(), Type::from(ty, &data.types)))
+            .collect()
+    }
+
+    /// Get the result type for this function.
+    pub fn result(&self, store: impl AsContext) -> Type {
+        let data = &store.as_context()[self.0];
+        Type::from(&data.types[data.ty].result, &data.types)
+    }
+
+    /// Returns the canonical ABI options this function was lifted with, such
+    /// as its string encoding and whether it has a memory and `realloc`.
+    pub fn options(&self, store: impl AsContext) -> Options {
+        store.as_context()[self.0].options
+    }
+
     /// Invokes this function with the `params` given and returns the result.
     ///
     /// The `params` here must match the type signature of this `Func`, or this will return an error. If a trap
@@ -265,6 +364,10 @@ impl Func {
     // TODO: say more -- most of the docs for `TypedFunc::call` apply here, too
     pub fn call(&self, mut store: impl AsContextMut, args: &[Val]) -> Result<Val> {
         let store = &mut store.as_context_mut();
+        assert!(
+            !store.0.async_support(),
+            "must use `call_async` when async support is enabled on the config"
+        );
 
         let params;
         let result;
@@ -345,6 +448,31 @@ impl Func {
         ) -> Result<()>,
         lift: impl FnOnce(&StoreOpaque, &Options, &LowerReturn) -> Result<Return>,
     ) -> Result<Return>
+    where
+        LowerParams: Copy,
+        LowerReturn: Copy,
+    {
+        let space = &mut MaybeUninit::<ParamsAndResults<LowerParams, LowerReturn>>::uninit();
+        self.call_raw_in(store, params, space, lower, lift)
+    }
+
+    /// Same as `call_raw` except that `space` is used for the lowered
+    /// parameters and results instead of space on the stack.
+    ///
+    /// No assumptions are made about the contents of `space`.
+    fn call_raw_in<T, Params: ?Sized, Return, LowerParams, LowerReturn>(
+        &self,
+        store: &mut StoreContextMut<'_, T>,
+        params: &Params,
+        space: &mut MaybeUninit<ParamsAndResults<LowerParams, LowerReturn>>,
+        lower: impl FnOnce(
+            &mut StoreContextMut<'_, T>,
+            &Options,
+            &Params,
+            &mut MaybeUninit<LowerParams>,
+        ) -> Result<()>,
+        lift: impl FnOnce(&StoreOpaque, &Options, &LowerReturn) -> Result<Return>,
+    ) -> Result<Return>
     where
         LowerParams: Copy,
         LowerReturn: Copy,
@@ -358,8 +486,6 @@ impl Func {
             ..
         } = store.0[self.0];
 
-        let space = &mut MaybeUninit::<ParamsAndResults<LowerParams, LowerReturn>>::uninit();
-
         // Double-check the size/alignemnt of `space`, just in case.
         //
         // Note that this alone is not enough to guarantee the validity of the
@@ -375,6 +501,35 @@ impl Func {
         assert!(mem::align_of_val(map_maybe_uninit!(space.params)) == val_align);
         assert!(mem::align_of_val(map_maybe_uninit!(space.ret)) == val_align);
 
+        // Reject shared memories before anything else happens so the failure
+        // doesn't depend on the arguments or the state of the instance.
+        options.check_shared_memory()?;
+
+        // With deferred post-return the previous call into this instance may
+        // not have had its post-return run yet, so do that now before the
+        // instance is entered again.
+        if store
+            .0
+            .engine()
+            .config()
+            .component_model_deferred_post_return
+        {
+            // If the post-return traps it poisons the instance, in which case
+            // the error reporting that below is returned instead of the trap.
+            if let Err(e) = self.run_deferred_post_return(store) {
+                if self.poisoned(store.0).is_none() {
+                    return Err(e);
+                }
+            }
+        }
+
+        // Calls into an instance poisoned by a trap in post-return would fail
+        // below anyway since it can't be entered, but report the trap instead
+        // of a confusing error about reentrance.
+        if let Some(poisoned) = self.poisoned(store.0) {
+            return Err(poisoned.into());
+        }
+
         let instance = store.0[instance.0].as_ref().unwrap().instance();
         let mut flags = instance.instance_flags(component_instance);
 
@@ -391,11 +546,45 @@ impl Func {
             }
             flags.set_may_enter(false);
 
-            debug_assert!(flags.may_leave());
-            flags.set_may_leave(false);
-            let result = lower(store, &options, params, map_maybe_uninit!(space.params));
-            flags.set_may_leave(true);
-            result?;
+            // Until the call completes successfully consider it failed, so a
+            // subsequent `post_return` can report that.
+            store.0[self.0].post_return_state = PostReturnState::CallFailed;
+
+            // Functions without any parameters, such as those taking `()`,
+            // have nothing to lower so skip that entirely. Note that this
+            // condition is known at compile time.
+            let result = if mem::size_of::<LowerParams>() == 0 {
+                Ok(())
+            } else {
+                debug_assert!(flags.may_leave());
+                flags.set_may_leave(false);
+                let result = lower(store, &options, params, map_maybe_uninit!(space.params));
+                flags.set_may_leave(true);
+                result
+            };
+            if let Err(e) = result {
+                // If lowering failed due to a missing `realloc` then no guest
+                // code has run yet, so the instance can be left enterable.
+                if let Some(LowerError::MissingRealloc { size }) = e.downcast_ref::<LowerError>() {
+                    let size = *size;
+                    flags.set_may_enter(true);
+                    let data = &store.0[self.0];
+                    let params = data.types[data.ty]
+                        .params
+                        .iter()
+                        .map(|(_, ty)| Type::from(ty, &data.types).to_string())
+                        .collect::<Vec<_>>();
+                    let msg = format!(
+                        "function `{}` has no `realloc` canonical option, but lowering its \
+                         parameters `({})` requires allocating {} bytes",
+                        data.name,
+                        params.join(", "),
+                        size,
+                    );
+                    return Err(e.context(msg));
+                }
+                return Err(e);
+            }
 
             // This is unsafe as we are providing the guarantee that all the
             // inputs are valid. The various pointers passed in for the function
@@ -432,11 +621,23 @@ impl Func {
             let val = lift(store.0, &options, ret)?;
             let ret_slice = cast_storage(ret);
             let data = &mut store.0[self.0];
-            assert!(data.post_return_arg.is_none());
-            match ret_slice.len() {
-                0 => data.post_return_arg = Some(ValRaw::i32(0)),
-                1 => data.post_return_arg = Some(ret_slice[0]),
+            data.post_return_state = match ret_slice.len() {
+                0 => PostReturnState::Pending(ValRaw::i32(0)),
+                1 => PostReturnState::Pending(ret_slice[0]),
                 _ => unreachable!(),
+            };
+            if store
+                .0
+                .engine()
+                .config()
+                .component_model_deferred_post_return
+            {
+                store
+                    .0
+                    .store_data_mut()
+                    .components
+                    .pending_post_returns
+                    .push(*self);
             }
             return Ok(val);
         }
@@ -465,73 +666,140 @@ impl Func {
     ///
     /// This function will return an error in the case of a WebAssembly trap
     /// happening during the execution of the `post-return` function, if
-    /// specified.
+    /// specified. Such a trap poisons the component instance: all further
+    /// calls of its functions fail with an [`InstancePoisoned`] error which
+    /// records the trap.
     ///
-    /// # Panics
+    /// This can only be called once after each invocation of [`Func::call`]
+    /// which completes successfully, and only for the same [`Func`] that was
+    /// `call`'d. Otherwise an error is returned without running anything and
+    /// without affecting the state of the component instance:
     ///
-    /// This function will panic if it's not called under the correct
-    /// conditions. This can only be called after a previous invocation of
-    /// [`Func::call`] completes successfully, and this function can only
-    /// be called for the same [`Func`] that was `call`'d.
+    /// * If `post_return` was already called for the most recent call.
+    /// * If this function hasn't been called yet.
+    /// * If the most recent call of this function failed. Failed calls have
+    ///   no `post-return`, and after most failures the instance can't be
+    ///   entered again anyway.
+    /// * If a different [`Func`] for the same component instance was invoked,
+    ///   because the `post-return` needs to happen for the other function.
     ///
-    /// If this function is called when [`Func::call`] was not previously
-    /// called, then it will panic. If a different [`Func`] for the same
-    /// component instance was invoked then this function will also panic
-    /// because the `post-return` needs to happen for the other function.
+    /// # Panics
+    ///
+    /// Panics if this is called on a store with async support enabled, in
+    /// which case [`Func::post_return_async`] must be used instead.
     pub fn post_return(&self, mut store: impl AsContextMut) -> Result<()> {
         let mut store = store.as_context_mut();
+        assert!(
+            !store.0.async_support(),
+            "must use `post_return_async` when async support is enabled on the config"
+        );
+        self.post_return_impl(store)
+    }
+
+    /// Same as [`Func::post_return`], except for use with stores that have
+    /// async support enabled.
+    ///
+    /// The `post-return` function, if any, is executed on a fiber so it's
+    /// able to yield back to the caller, for example when it runs out of
+    /// fuel.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Func::post_return`], and
+    /// additionally if this is called on a store without async support
+    /// enabled.
+    #[cfg(feature = "async")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "async")))]
+    pub async fn post_return_async<T: Send>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+    ) -> Result<()> {
+        let mut store = store.as_context_mut();
+        assert!(
+            store.0.async_support(),
+            "cannot use `post_return_async` without enabling async support in the config"
+        );
+        store.on_fiber(|store| self.post_return_impl(store)).await?
+    }
+
+    fn post_return_impl(&self, mut store: impl AsContextMut) -> Result<()> {
+        let mut store = store.as_context_mut();
+        store
+            .0
+            .store_data_mut()
+            .components
+            .pending_post_returns
+            .retain(|f| f.0 != self.0);
         let data = &mut store.0[self.0];
         let instance = data.instance;
         let post_return = data.post_return;
         let component_instance = data.component_instance;
-        let post_return_arg = data.post_return_arg.take();
+        let state = data.post_return_state;
         let instance = store.0[instance.0].as_ref().unwrap().instance();
         let mut flags = instance.instance_flags(component_instance);
 
         unsafe {
-            // First assert that the instance is in a "needs post return" state.
-            // This will ensure that the previous action on the instance was a
-            // function call above. This flag is only set after a component
-            // function returns so this also can't be called (as expected)
-            // during a host import for example.
-            //
-            // Note, though, that this assert is not sufficient because it just
-            // means some function on this instance needs its post-return
-            // called. We need a precise post-return for a particular function
-            // which is the second assert here (the `.expect`). That will assert
-            // that this function itself needs to have its post-return called.
-            //
-            // The theory at least is that these two asserts ensure component
-            // model semantics are upheld where the host properly calls
-            // `post_return` on the right function despite the call being a
-            // separate step in the API.
-            assert!(
-                flags.needs_post_return(),
-                "post_return can only be called after a function has previously been called",
-            );
-            let post_return_arg = post_return_arg.expect("calling post_return on wrong function");
-
-            // This is a sanity-check assert which shouldn't ever trip.
+            // Validate that this function is the one whose post-return is
+            // expected next. The "needs post return" flag is per-instance
+            // while the state here is per-function, so together they can tell
+            // apart all the ways this can be misused.
+            let post_return_arg = match state {
+                PostReturnState::Pending(arg) => arg,
+                PostReturnState::CallFailed => bail!(
+                    "post_return called without a preceding successful call: \
+                     the previous call failed and has no post-return"
+                ),
+                _ if flags.needs_post_return() => bail!(
+                    "post_return called on the wrong function: a different \
+                     function of this component instance needs its post_return \
+                     called first"
+                ),
+                PostReturnState::Completed => {
+                    bail!("post_return already completed for the previous call")
+                }
+                PostReturnState::Idle => {
+                    bail!("post_return called without a preceding successful call")
+                }
+            };
+
+            // These are sanity-check asserts which shouldn't ever trip: a
+            // pending post-return for this function means the instance was
+            // left waiting for it.
+            assert!(flags.needs_post_return());
             assert!(!flags.may_enter());
 
-            // Unset the "needs post return" flag now that post-return is being
-            // processed. This will cause future invocations of this method to
-            // panic, even if the function call below traps.
+            // Mark the post-return as processed now that it's being run. This
+            // will cause future invocations of this method to return an error,
+            // even if the function call below traps.
             flags.set_needs_post_return(false);
+            store.0[self.0].post_return_state = PostReturnState::Completed;
 
             // If the function actually had a `post-return` configured in its
             // canonical options that's executed here.
             //
             // Note that if this traps (returns an error) this function
             // intentionally leaves the instance in a "poisoned" state where it
-            // can no longer be entered because `may_enter` is `false`.
+            // can no longer be entered because `may_enter` is `false`. The
+            // trap is recorded so further calls can report why.
             if let Some((func, trampoline)) = post_return {
-                crate::Func::call_unchecked_raw(
+                if let Err(trap) = crate::Func::call_unchecked_raw(
                     &mut store,
                     func.anyfunc,
                     trampoline,
                     &post_return_arg as *const ValRaw as *mut ValRaw,
-                )?;
+                ) {
+                    let poisoned = InstancePoisoned {
+                        func: store.0[self.0].name.clone(),
+                        trap: trap.clone(),
+                    };
+                    store
+                        .0
+                        .store_data_mut()
+                        .components
+                        .poisoned
+                        .push((*self, poisoned));
+                    return Err(trap.into());
+                }
             }
 
             // And finally if everything completed successfully then the "may
@@ -542,6 +810,62 @@ impl Func {
         Ok(())
     }
 
+    /// Returns whether `self` and `other` belong to the same component
+    /// instance, which share the flags guarding entry and post-return.
+    fn same_instance(&self, store: &StoreOpaque, other: &Func) -> bool {
+        let (a, b) = (&store[self.0], &store[other.0]);
+        a.instance.0 == b.instance.0 && a.component_instance == b.component_instance
+    }
+
+    /// Returns the error describing the trap in post-return which poisoned
+    /// the component instance of `self`, if any.
+    fn poisoned(&self, store: &StoreOpaque) -> Option<InstancePoisoned> {
+        store
+            .store_data()
+            .components
+            .poisoned
+            .iter()
+            .find(|(f, _)| self.same_instance(store, f))
+            .map(|(_, poisoned)| poisoned.clone())
+    }
+
+    /// Runs the deferred post-return of the function previously called on the
+    /// same component instance as `self`, if there is one.
+    fn run_deferred_post_return<T>(&self, store: &mut StoreContextMut<'_, T>) -> Result<()> {
+        let pending = store
+            .0
+            .store_data()
+            .components
+            .pending_post_returns
+            .iter()
+            .find(|f| self.same_instance(store.0, f))
+            .copied();
+        match pending {
+            Some(func) => func.post_return_impl(store.as_context_mut()),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs all deferred post-returns in `store`, see
+    /// `Store::flush_post_returns`.
+    pub(crate) fn flush_post_returns<T>(mut store: StoreContextMut<'_, T>) -> Result<()> {
+        assert!(
+            !store.0.async_support(),
+            "cannot flush post-returns when async support is enabled on the config"
+        );
+        while let Some(func) = store
+            .0
+            .store_data()
+            .components
+            .pending_post_returns
+            .first()
+            .copied()
+        {
+            func.post_return_impl(store.as_context_mut())?;
+        }
+        Ok(())
+    }
+
     fn store_args<T>(
         &self,
         store: &mut StoreContextMut<'_, T>,
@@ -559,10 +883,13 @@ impl Func {
 
         let mut memory = MemoryMut::new(store.as_context_mut(), options);
         let ptr = memory.realloc(0, 0, alignment, size)?;
-        let mut offset = ptr;
-        for (ty, arg) in params.iter().zip(args) {
-            arg.store(&mut memory, ty.next_field(&mut offset))?;
-        }
+        memory.free_on_error(ptr, size, alignment, |memory| {
+            let mut offset = ptr;
+            for (ty, arg) in params.iter().zip(args) {
+                arg.store(memory, ty.next_field(&mut offset))?;
+            }
+            Ok(())
+        })?;
 
         map_maybe_uninit!(dst[0]).write(ValRaw::i64(ptr as i64));
 
@@ -578,7 +905,12 @@ impl Func {
         // FIXME: needs to read an i64 for memory64
         let ptr = usize::try_from(src.next().unwrap().get_u32())?;
         if ptr % usize::try_from(alignment)? != 0 {
-            bail!("return pointer not aligned");
+            return Err(LiftError::Misaligned {
+                what: "return",
+                ptr,
+                align: alignment,
+            }
+            .into());
         }
 
         let bytes = mem
@@ -0,0 +1,320 @@
+use crate::Trap;
+use std::fmt;
+use std::str::Utf8Error;
+
+/// An error which happened while lifting a value out of a component, for
+/// example because a component returned an invalid value.
+///
+/// Errors returned from [`TypedFunc::call`](crate::component::TypedFunc::call)
+/// and from reading [`WasmStr`](crate::component::WasmStr) and
+/// [`WasmList`](crate::component::WasmList) values can be downcast to this
+/// type with [`anyhow::Error::downcast_ref`] to determine what went wrong
+/// without matching on error messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LiftError {
+    /// A pointer and length describe a range which is out of bounds of linear
+    /// memory.
+    OutOfBounds {
+        /// What the pointer refers to, for example `"string"`.
+        what: &'static str,
+        /// The pointer to the start of the range.
+        ptr: usize,
+        /// The number of elements in the range.
+        len: usize,
+        /// The size of linear memory, in bytes.
+        memory_size: usize,
+    },
+    /// A pointer isn't aligned as required by the canonical ABI.
+    Misaligned {
+        /// What the pointer refers to, for example `"list"`.
+        what: &'static str,
+        /// The misaligned pointer.
+        ptr: usize,
+        /// The required alignment, in bytes.
+        align: u32,
+    },
+    /// The discriminant of a variant-like type is out of range.
+    InvalidDiscriminant {
+        /// The kind of type, for example `"option"`.
+        ty: &'static str,
+        /// The invalid discriminant.
+        value: u32,
+    },
+    /// A `bool` was neither 0 nor 1 with strict validation enabled.
+    InvalidBool {
+        /// The invalid value.
+        value: u32,
+        /// Where the value came from, for example `"loaded from linear memory"`.
+        source: &'static str,
+    },
+    /// A `char` wasn't a valid Unicode scalar value.
+    InvalidChar {
+        /// The invalid value.
+        value: u32,
+        /// Where the value came from, for example `"lifted from the stack"`.
+        source: &'static str,
+    },
+    /// A string encoded as utf-8 wasn't valid utf-8.
+    InvalidUtf8(Utf8Error),
+    /// A string encoded as utf-16 wasn't valid utf-16.
+    InvalidUtf16 {
+        /// The unpaired surrogate.
+        unpaired_surrogate: u16,
+        /// The index of the unpaired surrogate, in code units.
+        position: usize,
+        /// The length of the string, in code units.
+        len: usize,
+    },
+    /// Padding or unused payload bytes of a variant-like type were nonzero
+    /// with strict validation enabled.
+    NonzeroPadding {
+        /// Whether this was `"padding after discriminant"` or
+        /// `"unused payload"`.
+        what: &'static str,
+        /// The nonzero byte.
+        value: u8,
+        /// The offset of the byte within the value.
+        offset: usize,
+        /// The size of the value, in bytes.
+        size: usize,
+    },
+    /// A string or list exceeded a limit configured in
+    /// [`Config`](crate::Config).
+    LimitExceeded {
+        /// Either `"string"` or `"list"`.
+        what: &'static str,
+        /// The size of the string, in bytes, or the number of elements of the
+        /// list.
+        len: usize,
+        /// The configured limit.
+        limit: usize,
+    },
+    /// A `list<tuple<K, V>>` lifted as a map, such as a `HashMap`, contained
+    /// the same key more than once with strict validation enabled.
+    DuplicateMapKey {
+        /// The index of the entry whose key was already present.
+        index: usize,
+        /// The number of entries of the list.
+        len: usize,
+    },
+}
+
+impl fmt::Display for LiftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiftError::OutOfBounds {
+                what,
+                ptr,
+                len,
+                memory_size,
+            } => write!(
+                f,
+                "{} pointer/length out of bounds of memory (ptr {:#x}, len {}, memory size {})",
+                what, ptr, len, memory_size
+            ),
+            LiftError::Misaligned { what, ptr, align } => write!(
+                f,
+                "{} pointer is not aligned (ptr {:#x}, alignment {})",
+                what, ptr, align
+            ),
+            LiftError::InvalidDiscriminant { ty, value } => {
+                write!(f, "invalid {} discriminant {}", ty, value)
+            }
+            LiftError::InvalidBool { value, source } => {
+                write!(f, "invalid bool value {} {}", value, source)
+            }
+            LiftError::InvalidChar { value, source } => {
+                write!(f, "invalid char value {:#x} {}: ", value, source)?;
+                if (0xd800..=0xdfff).contains(value) {
+                    f.write_str("surrogate code points are not valid chars")
+                } else {
+                    f.write_str("value is larger than 0x10ffff")
+                }
+            }
+            LiftError::InvalidUtf8(e) => e.fmt(f),
+            LiftError::InvalidUtf16 {
+                unpaired_surrogate,
+                position,
+                len,
+            } => write!(
+                f,
+                "invalid utf-16 string: unpaired surrogate {:#06x} at code unit {} of {}",
+                unpaired_surrogate, position, len
+            ),
+            LiftError::NonzeroPadding {
+                what,
+                value,
+                offset,
+                size,
+            } => write!(
+                f,
+                "nonzero {} byte {:#04x} at offset {} of a {}-byte value",
+                what, value, offset, size
+            ),
+            LiftError::LimitExceeded { what, len, limit } => {
+                let (unit, option) = match *what {
+                    "string" => ("bytes", "component_model_max_lifted_string_bytes"),
+                    _ => ("elements", "component_model_max_lifted_list_elements"),
+                };
+                write!(
+                    f,
+                    "lifted {} of {} {} exceeds the limit of {} {} configured \
+                     by `Config::{}`",
+                    what, len, unit, limit, unit, option
+                )
+            }
+            LiftError::DuplicateMapKey { index, len } => write!(
+                f,
+                "duplicate map key at entry {} of a list of {} entries",
+                index, len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LiftError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LiftError::InvalidUtf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// An error which happened while lowering a value into a component.
+///
+/// Like [`LiftError`] errors returned from
+/// [`TypedFunc::call`](crate::component::TypedFunc::call) can be downcast to
+/// this type with [`anyhow::Error::downcast_ref`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LowerError {
+    /// A pointer and length describe a range which is out of bounds of linear
+    /// memory.
+    OutOfBounds {
+        /// What the pointer refers to, for example `"write"`.
+        what: &'static str,
+        /// The pointer to the start of the range.
+        ptr: usize,
+        /// The number of elements in the range.
+        len: usize,
+        /// The size of linear memory, in bytes.
+        memory_size: usize,
+    },
+    /// Memory needed to be allocated but the component has no `realloc`
+    /// canonical option.
+    MissingRealloc {
+        /// The number of bytes which needed to be allocated.
+        size: usize,
+    },
+    /// The component's `realloc` returned a pointer which isn't aligned as
+    /// requested.
+    ReallocMisaligned {
+        /// The pointer returned by `realloc`.
+        ptr: u32,
+        /// The requested alignment.
+        align: u32,
+    },
+    /// The component's `realloc` returned a pointer such that the allocation
+    /// is out of bounds of linear memory.
+    ReallocOutOfBounds {
+        /// The pointer returned by `realloc`.
+        ptr: usize,
+        /// The requested size of the allocation.
+        size: usize,
+    },
+    /// A string or list is too large to be represented in the linear memory
+    /// it's lowered into.
+    TooLarge {
+        /// The type being lowered, for example `list<u8>`.
+        ty: String,
+        /// The length of the string or list.
+        len: usize,
+        /// The index width of linear memory, which is currently always 32
+        /// since lowering only supports 32-bit memories.
+        bits: u32,
+    },
+}
+
+impl fmt::Display for LowerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LowerError::OutOfBounds {
+                what,
+                ptr,
+                len,
+                memory_size,
+            } => write!(
+                f,
+                "{} pointer/length out of bounds of memory (ptr {:#x}, len {}, memory size {})",
+                what, ptr, len, memory_size
+            ),
+            LowerError::MissingRealloc { size } => write!(
+                f,
+                "cannot allocate {} bytes in linear memory without a `realloc` canonical option",
+                size
+            ),
+            LowerError::ReallocMisaligned { ptr, align } => write!(
+                f,
+                "realloc return: result not aligned (ptr {:#x}, alignment {})",
+                ptr, align
+            ),
+            LowerError::ReallocOutOfBounds { ptr, size } => write!(
+                f,
+                "realloc return: beyond end of memory (ptr {:#x}, size {})",
+                ptr, size
+            ),
+            LowerError::TooLarge { ty, len, bits } => write!(
+                f,
+                "`{}` of length {} is too large to lower into a {}-bit linear memory",
+                ty, len, bits
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LowerError {}
+
+/// An error returned when calling a function of a component instance which
+/// was poisoned because a `post-return` function trapped.
+///
+/// The trap itself is returned from
+/// [`Func::post_return`](crate::component::Func::post_return), after which no
+/// function of the same component instance can be called again. Instead all
+/// calls return this error, which records the trap and the name of the
+/// function whose `post-return` trapped.
+#[derive(Debug, Clone)]
+pub struct InstancePoisoned {
+    pub(crate) func: Box<str>,
+    pub(crate) trap: Trap,
+}
+
+impl InstancePoisoned {
+    /// Returns the name of the function whose `post-return` trapped.
+    pub fn func_name(&self) -> &str {
+        &self.func
+    }
+
+    /// Returns the trap raised by `post-return`, including its backtrace.
+    pub fn trap(&self) -> &Trap {
+        &self.trap
+    }
+}
+
+impl fmt::Display for InstancePoisoned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "instance poisoned by trap in post-return of `{}`: {}",
+            self.func,
+            self.trap.display_reason()
+        )
+    }
+}
+
+impl std::error::Error for InstancePoisoned {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.trap)
+    }
+}
@@ -1,7 +1,7 @@
-use crate::component::func::{Memory, MemoryMut, Options};
+use crate::component::func::{typecheck_signature, Memory, MemoryMut, Options};
 use crate::component::{ComponentParams, ComponentType, Lift, Lower};
 use crate::{AsContextMut, StoreContextMut, ValRaw};
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, Result};
 use std::any::Any;
 use std::mem::MaybeUninit;
 use std::panic::{self, AssertUnwindSafe};
@@ -61,14 +61,55 @@ impl HostFunc {
         })
     }
 
+    /// Creates a host function from a closure which receives its parameters
+    /// as a single `P` tuple.
+    pub(crate) fn from_closure<T, F, P, R>(func: F) -> Arc<HostFunc>
+    where
+        F: Fn(StoreContextMut<'_, T>, P) -> Result<R> + Send + Sync + 'static,
+        P: ComponentParams + Lift + 'static,
+        R: Lower + 'static,
+    {
+        HostFunc::new::<_, P, R>(func, closure_entrypoint::<T, F, P, R>)
+    }
+
+    /// Creates a host function whose result is produced by a future.
+    ///
+    /// The future is driven to completion on the fiber that wasm is executing
+    /// on, suspending back to the embedder's executor whenever it's pending.
+    #[cfg(feature = "async")]
+    pub(crate) fn new_async<T, F, P, R>(func: F) -> Arc<HostFunc>
+    where
+        F: for<'a> Fn(
+                StoreContextMut<'a, T>,
+                P,
+            ) -> Box<dyn std::future::Future<Output = Result<R>> + Send + 'a>
+            + Send
+            + Sync
+            + 'static,
+        P: ComponentParams + Lift + 'static,
+        R: Lower + 'static,
+    {
+        HostFunc::new::<_, P, R>(func, async_entrypoint::<T, F, P, R>)
+    }
+
     pub fn typecheck(&self, ty: TypeFuncIndex, types: &ComponentTypes) -> Result<()> {
         (self.typecheck)(ty, types)
     }
 
-    pub fn lowering(&self) -> VMLowering {
+    /// Returns the lowering of this function for canonical ABI options which
+    /// use a shared memory if `shared_memory` is set, or otherwise any other
+    /// options.
+    ///
+    /// Lifting and lowering don't support shared memories, so in that case the
+    /// lowering fails every call without invoking this function.
+    pub fn lowering(&self, shared_memory: bool) -> VMLowering {
         let data = &*self.func as *const (dyn Any + Send + Sync) as *mut u8;
         VMLowering {
-            callee: self.entrypoint,
+            callee: if shared_memory {
+                shared_memory_entrypoint
+            } else {
+                self.entrypoint
+            },
             data,
         }
     }
@@ -79,10 +120,7 @@ where
     P: ComponentParams + Lift,
     R: Lower,
 {
-    let ty = &types[ty];
-    P::typecheck_params(&ty.params, types).context("type mismatch with parameters")?;
-    R::typecheck(&ty.result, types).context("type mismatch with result")?;
-    Ok(())
+    typecheck_signature::<P, R>(&types[ty], types)
 }
 
 /// The "meat" of calling a host function from wasm.
@@ -249,6 +287,101 @@ unsafe fn handle_result(func: impl FnOnce() -> Result<()>) {
     }
 }
 
+/// Host entrypoint of lowerings whose canonical options use a shared memory,
+/// see `HostFunc::lowering`.
+extern "C" fn shared_memory_entrypoint(
+    _cx: *mut VMOpaqueContext,
+    _data: *mut u8,
+    _flags: InstanceFlags,
+    _memory: *mut VMMemoryDefinition,
+    _realloc: *mut VMCallerCheckedAnyfunc,
+    _string_encoding: StringEncoding,
+    _storage: *mut ValRaw,
+    _storage_len: usize,
+) {
+    unsafe { handle_result(|| Err(Options::shared_memory_error())) }
+}
+
+/// Host entrypoint for functions created with `HostFunc::from_closure`.
+///
+/// This is the same as the `entrypoint` generated for `IntoComponentFunc`
+/// except that the parameters are passed to the host closure as one tuple.
+extern "C" fn closure_entrypoint<T, F, P, R>(
+    cx: *mut VMOpaqueContext,
+    data: *mut u8,
+    flags: InstanceFlags,
+    memory: *mut VMMemoryDefinition,
+    realloc: *mut VMCallerCheckedAnyfunc,
+    string_encoding: StringEncoding,
+    storage: *mut ValRaw,
+    storage_len: usize,
+) where
+    F: Fn(StoreContextMut<'_, T>, P) -> Result<R>,
+    P: Lift,
+    R: Lower,
+{
+    let data = data as *const F;
+    unsafe {
+        handle_result(|| {
+            call_host::<T, _, _, _>(
+                cx,
+                flags,
+                memory,
+                realloc,
+                string_encoding,
+                std::slice::from_raw_parts_mut(storage, storage_len),
+                |store, params| (*data)(store, params),
+            )
+        })
+    }
+}
+
+/// Host entrypoint for functions created with `HostFunc::new_async`.
+///
+/// This is the same as the `entrypoint` generated for `IntoComponentFunc`
+/// except that the host closure returns a future which is blocked on here.
+#[cfg(feature = "async")]
+extern "C" fn async_entrypoint<T, F, P, R>(
+    cx: *mut VMOpaqueContext,
+    data: *mut u8,
+    flags: InstanceFlags,
+    memory: *mut VMMemoryDefinition,
+    realloc: *mut VMCallerCheckedAnyfunc,
+    string_encoding: StringEncoding,
+    storage: *mut ValRaw,
+    storage_len: usize,
+) where
+    F: for<'a> Fn(
+        StoreContextMut<'a, T>,
+        P,
+    ) -> Box<dyn std::future::Future<Output = Result<R>> + Send + 'a>,
+    P: Lift,
+    R: Lower,
+{
+    let data = data as *const F;
+    unsafe {
+        handle_result(|| {
+            call_host::<T, _, _, _>(
+                cx,
+                flags,
+                memory,
+                realloc,
+                string_encoding,
+                std::slice::from_raw_parts_mut(storage, storage_len),
+                |mut store, params| {
+                    let async_cx = store
+                        .as_context_mut()
+                        .0
+                        .async_cx()
+                        .expect("Attempt to start async function on dying fiber");
+                    let mut future = std::pin::Pin::from((*data)(store, params));
+                    async_cx.block_on(future.as_mut())?
+                },
+            )
+        })
+    }
+}
+
 macro_rules! impl_into_component_func {
     ($num:tt $($args:ident)*) => {
         // Implement for functions without a leading `StoreContextMut` parameter
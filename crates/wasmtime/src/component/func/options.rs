@@ -1,6 +1,9 @@
+use crate::component::func::typed::{bounds_check, checked_range};
+use crate::component::func::{LiftError, LowerError};
 use crate::store::{StoreId, StoreOpaque};
-use crate::StoreContextMut;
-use anyhow::{bail, Result};
+use crate::{StoreContext, StoreContextMut, Trap};
+use anyhow::{anyhow, bail, Error, Result};
+use std::fmt;
 use std::ptr::NonNull;
 use wasmtime_environ::component::StringEncoding;
 use wasmtime_runtime::{VMCallerCheckedAnyfunc, VMMemoryDefinition};
@@ -13,6 +16,10 @@ use wasmtime_runtime::{VMCallerCheckedAnyfunc, VMMemoryDefinition};
 /// out of thin air for a host function import, for example. The `store_id`
 /// field, however, is what is used to pair this set of options with a store
 /// reference to actually use the pointers.
+///
+/// Embedders get these options from [`Func::options`](crate::component::Func::options)
+/// and can only inspect them, for example with [`Options::string_encoding`],
+/// [`Options::has_memory`], and [`Options::has_realloc`].
 #[derive(Copy, Clone)]
 pub struct Options {
     /// The store from which this options originated from.
@@ -36,6 +43,26 @@ pub struct Options {
     ///
     /// This defaults to utf-8 but can be changed if necessary.
     string_encoding: StringEncoding,
+
+    /// Whether `memory` is a 64-bit memory.
+    memory64: bool,
+
+    /// Whether `memory` is a shared memory, which lifting and lowering don't
+    /// support, see `Options::check_shared_memory`.
+    shared_memory: bool,
+}
+
+/// Shows the configuration of these options rather than the raw pointers
+/// they hold, for example to describe a function in diagnostics.
+impl fmt::Debug for Options {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Options")
+            .field("string_encoding", &self.string_encoding)
+            .field("memory", &self.has_memory())
+            .field("realloc", &self.has_realloc())
+            .field("memory64", &self.memory64)
+            .finish()
+    }
 }
 
 // The `Options` structure stores raw pointers but they're never used unless a
@@ -54,7 +81,7 @@ impl Options {
     /// This is unsafety as there is no way to statically verify the validity of
     /// the arguments. For example pointers must be valid pointers, the
     /// `StoreId` must be valid for the pointers, etc.
-    pub unsafe fn new(
+    pub(crate) unsafe fn new(
         store_id: StoreId,
         memory: Option<NonNull<VMMemoryDefinition>>,
         realloc: Option<NonNull<VMCallerCheckedAnyfunc>>,
@@ -65,9 +92,79 @@ impl Options {
             memory,
             realloc,
             string_encoding,
+            memory64: false,
+            shared_memory: false,
         }
     }
 
+    /// Returns a copy of these options which uses `string_encoding` for
+    /// strings instead of the encoding declared by the component.
+    ///
+    /// This is an advanced method intended for testing and adapters, for
+    /// example to exercise both the utf-8 and utf-16 string paths against a
+    /// single guest memory when constructing [`Memory`] or [`MemoryMut`] views
+    /// manually. Calls made through [`Func`](crate::component::Func) and
+    /// [`TypedFunc`](crate::component::TypedFunc) always use the encoding that
+    /// the component declared. Any strings lowered with the returned options
+    /// must be interpreted by the guest with the same encoding.
+    pub fn with_string_encoding(mut self, string_encoding: StringEncoding) -> Options {
+        self.string_encoding = string_encoding;
+        self
+    }
+
+    /// Returns a copy of these options which records whether the memory is a
+    /// 64-bit memory.
+    ///
+    /// Like [`Options::with_string_encoding`] this is an advanced method which
+    /// doesn't affect calls made through `Func` or `TypedFunc`. Note that
+    /// lifting and lowering currently only support 32-bit memories, so this is
+    /// only informational for code inspecting [`Options::memory64`].
+    pub fn with_memory64(mut self, memory64: bool) -> Options {
+        self.memory64 = memory64;
+        self
+    }
+
+    /// Returns a copy of these options which records whether the memory is a
+    /// shared memory.
+    pub(crate) fn with_shared_memory(mut self, shared_memory: bool) -> Options {
+        self.shared_memory = shared_memory;
+        self
+    }
+
+    /// Returns an error if the memory of these options is a shared memory.
+    ///
+    /// Lifting and lowering view linear memory as plain Rust slices, which is
+    /// only sound if nothing else accesses memory at the same time. That can't
+    /// be guaranteed for shared memories since other threads may be running
+    /// wasm which accesses them, so calls with such options are rejected
+    /// before anything is lifted or lowered, regardless of the types involved.
+    pub(crate) fn check_shared_memory(&self) -> Result<()> {
+        if self.shared_memory {
+            return Err(Options::shared_memory_error());
+        }
+        Ok(())
+    }
+
+    /// Returns an error unless these options belong to `store` and specify a
+    /// linear memory which can be viewed as a slice.
+    fn check_memory(&self, store: &StoreOpaque) -> Result<()> {
+        if self.store_id != store.id() {
+            bail!("options belong to a different store");
+        }
+        if self.memory.is_none() {
+            bail!("options don't specify a linear memory");
+        }
+        self.check_shared_memory()
+    }
+
+    /// The error returned when canonical ABI options use a shared memory.
+    pub(crate) fn shared_memory_error() -> Error {
+        anyhow!(
+            "shared memories are not supported by the component canonical ABI \
+             host implementation"
+        )
+    }
+
     fn realloc<'a, T>(
         &self,
         store: &'a mut StoreContextMut<'_, T>,
@@ -95,8 +192,19 @@ impl Options {
             )?
         };
 
+        // A new size of zero frees the allocation, and the pointer returned in
+        // that case isn't meaningful, for example it may be null or one past
+        // the end of memory, so it's not validated.
+        if new_size == 0 {
+            return Ok((&mut [], 0));
+        }
+
         if result % old_align != 0 {
-            bail!("realloc return: result not aligned");
+            return Err(LowerError::ReallocMisaligned {
+                ptr: result,
+                align: old_align,
+            }
+            .into());
         }
         let result = usize::try_from(result)?;
 
@@ -104,25 +212,62 @@ impl Options {
 
         let result_slice = match memory.get_mut(result..).and_then(|s| s.get_mut(..new_size)) {
             Some(end) => end,
-            None => bail!("realloc return: beyond end of memory"),
+            None => {
+                return Err(LowerError::ReallocOutOfBounds {
+                    ptr: result,
+                    size: new_size,
+                }
+                .into())
+            }
         };
 
         Ok((result_slice, result))
     }
 
+    /// Returns whether these options specify a linear memory.
+    ///
+    /// Functions which only pass scalar values don't need a memory, in which
+    /// case strings and lists can't be passed to or from them.
+    pub fn has_memory(&self) -> bool {
+        self.memory.is_some()
+    }
+
+    /// Returns whether these options specify a `realloc` function.
+    ///
+    /// Without a `realloc` function the host can't allocate memory in the
+    /// guest, so non-empty strings and lists can't be lowered into it.
+    pub fn has_realloc(&self) -> bool {
+        self.realloc.is_some()
+    }
+
+    /// Returns the identifier of the store that these options belong to.
+    pub(crate) fn store_id(&self) -> StoreId {
+        self.store_id
+    }
+
     /// Asserts that this function has an associated memory attached to it and
     /// then returns the slice of memory tied to the lifetime of the provided
     /// store.
-    pub fn memory<'a>(&self, store: &'a StoreOpaque) -> &'a [u8] {
+    ///
+    /// # Panics
+    ///
+    /// Panics if the memory is a shared memory, which calls reject before
+    /// memory is accessed.
+    pub(crate) fn memory<'a>(&self, store: &'a StoreOpaque) -> &'a [u8] {
         self.store_id.assert_belongs_to(store.id());
+        assert!(
+            !self.shared_memory,
+            "cannot view a shared memory as a slice"
+        );
 
-        // The unsafety here is intended to be encapsulated by the two
-        // preceding assertions. Namely we assert that the `store` is the same
-        // as the original store of this `Options`, meaning that we safely have
-        // either a shared reference or a mutable reference (as below) which
-        // means it's safe to view the memory (aka it's not a different store
-        // where our original store is on some other thread or something like
-        // that).
+        // The unsafety here is intended to be encapsulated by the preceding
+        // assertions. Namely we assert that the `store` is the same as the
+        // original store of this `Options`, meaning that we safely have either
+        // a shared reference or a mutable reference (as below) which means
+        // it's safe to view the memory (aka it's not a different store where
+        // our original store is on some other thread or something like that).
+        // Shared memories are excluded since other threads may access them
+        // regardless of the store.
         //
         // Additionally the memory itself is asserted to be present as memory
         // is an optional configuration in canonical ABI options.
@@ -133,8 +278,12 @@ impl Options {
     }
 
     /// Same as above, just `_mut`
-    pub fn memory_mut<'a>(&self, store: &'a mut StoreOpaque) -> &'a mut [u8] {
+    pub(crate) fn memory_mut<'a>(&self, store: &'a mut StoreOpaque) -> &'a mut [u8] {
         self.store_id.assert_belongs_to(store.id());
+        assert!(
+            !self.shared_memory,
+            "cannot view a shared memory as a slice"
+        );
 
         // See comments in `memory` about the unsafety
         unsafe {
@@ -148,11 +297,21 @@ impl Options {
     pub fn string_encoding(&self) -> StringEncoding {
         self.string_encoding
     }
+
+    /// Returns whether the memory used by this lifting/lowering is a 64-bit
+    /// memory.
+    pub fn memory64(&self) -> bool {
+        self.memory64
+    }
 }
 
 /// A helper structure to package up proof-of-memory. This holds a store pointer
 /// and a `Func` pointer where the function has the pointers to memory.
 ///
+/// Embedders can create one with [`MemoryMut::from_options`] to write to the
+/// linear memory of a component function with [`MemoryMut::write`] and
+/// [`MemoryMut::write_u32`].
+///
 /// Note that one of the purposes of this type is to make `lower_list`
 /// vectorizable by "caching" the last view of memory. CUrrently it doesn't do
 /// that, though, because I couldn't get `lower_list::<u8>` to vectorize. I've
@@ -163,20 +322,56 @@ pub struct MemoryMut<'a, T> {
     options: &'a Options,
 }
 
-#[doc(hidden)]
 impl<'a, T> MemoryMut<'a, T> {
+    /// Creates a view of the linear memory that `options` specify within
+    /// `store`, for example the options of a function returned by
+    /// [`Func::options`](crate::component::Func::options).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `options` belong to a different store, don't
+    /// specify a memory, or specify a shared memory.
+    pub fn from_options(
+        store: StoreContextMut<'a, T>,
+        options: &'a Options,
+    ) -> Result<MemoryMut<'a, T>> {
+        options.check_memory(store.0)?;
+        Ok(MemoryMut::new(store, options))
+    }
+
+    #[doc(hidden)]
     pub fn new(store: StoreContextMut<'a, T>, options: &'a Options) -> MemoryMut<'a, T> {
         MemoryMut { options, store }
     }
 
+    #[doc(hidden)]
     pub fn string_encoding(&self) -> StringEncoding {
         self.options.string_encoding()
     }
 
+    #[doc(hidden)]
+    pub fn memory64(&self) -> bool {
+        self.options.memory64()
+    }
+
+    #[doc(hidden)]
     pub fn as_slice_mut(&mut self) -> &mut [u8] {
         self.options.memory_mut(self.store.0)
     }
 
+    /// Invokes the guest's `realloc` function, returning the new pointer.
+    ///
+    /// Fresh allocations of zero bytes don't invoke the guest and instead
+    /// return 0, which is suitably aligned and in-bounds for a zero-length
+    /// range. This means that empty strings and lists can be passed to
+    /// components which don't have a `realloc` option. Any other allocation
+    /// without a `realloc` option returns a `LowerError::MissingRealloc` error.
+    ///
+    /// Reallocating a non-empty allocation to zero bytes frees it, as
+    /// permitted by the canonical ABI. The guest's return value isn't used in
+    /// that case and 0 is returned, just as for fresh allocations of zero
+    /// bytes.
+    #[doc(hidden)]
     pub fn realloc(
         &mut self,
         old: usize,
@@ -184,16 +379,53 @@ impl<'a, T> MemoryMut<'a, T> {
         old_align: u32,
         new_size: usize,
     ) -> Result<usize> {
+        if old_size == 0 && new_size == 0 {
+            return Ok(0);
+        }
+        if !self.options.has_realloc() {
+            return Err(LowerError::MissingRealloc { size: new_size }.into());
+        }
         self.options
             .realloc(&mut self.store, old, old_size, old_align, new_size)
             .map(|(_, ptr)| ptr)
     }
 
+    /// Invokes `f` to store values into the allocation of `size` bytes at
+    /// `ptr`, which was just returned by `realloc`, and frees the allocation
+    /// if `f` fails so that partially lowered values don't leak guest memory.
+    ///
+    /// Note that allocations made by `f` itself which it doesn't free are
+    /// still leaked, for example those of the elements of a list which were
+    /// successfully stored before a later element failed.
+    ///
+    /// The allocation is also leaked if `f` fails with a trap, which includes
+    /// the guest's `realloc` trapping as well as epoch and fuel interruption,
+    /// since the guest can't be entered again to free it after that.
+    pub(crate) fn free_on_error<R>(
+        &mut self,
+        ptr: usize,
+        size: usize,
+        align: u32,
+        f: impl FnOnce(&mut Self) -> Result<R>,
+    ) -> Result<R> {
+        let result = f(self);
+        if let Err(e) = &result {
+            // The original error is more relevant than any failure to free,
+            // in which case the allocation is leaked as it would have been
+            // anyway.
+            if !e.is::<Trap>() {
+                let _ = self.free(ptr, size, align);
+            }
+        }
+        result
+    }
+
     /// Returns a fixed mutable slice of memory `N` bytes large starting at
     /// offset `N`, panicking on out-of-bounds.
     ///
     /// It should be previously verified that `offset` is in-bounds via
     /// bounds-checks.
+    #[doc(hidden)]
     pub fn get<const N: usize>(&mut self, offset: usize) -> &mut [u8; N] {
         // FIXME: this bounds check shouldn't actually be necessary, all
         // callers of `ComponentType::store` have already performed a bounds
@@ -209,29 +441,309 @@ impl<'a, T> MemoryMut<'a, T> {
             .try_into()
             .unwrap()
     }
+
+    /// Frees an allocation of `size` bytes aligned to `align` at `ptr` which
+    /// was previously returned by the guest's `realloc` function.
+    ///
+    /// This invokes `realloc` with a new size of zero, which the canonical ABI
+    /// permits as a way to free memory. Allocations of zero bytes don't occupy
+    /// any memory so freeing them doesn't invoke the guest.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a non-empty allocation is freed but the component
+    /// doesn't have a `realloc` option, or if the guest's `realloc` traps.
+    pub fn free(&mut self, ptr: usize, size: usize, align: u32) -> Result<()> {
+        self.realloc(ptr, size, align, 0)?;
+        Ok(())
+    }
+
+    /// Copies `bytes` into linear memory starting at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without modifying memory, if any part of the
+    /// destination is out of bounds of linear memory.
+    pub fn write(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+        let memory = self.as_slice_mut();
+        let range = checked_range(memory.len(), offset, bytes.len(), 1).ok_or_else(|| {
+            LowerError::OutOfBounds {
+                what: "write",
+                ptr: offset,
+                len: bytes.len(),
+                memory_size: memory.len(),
+            }
+        })?;
+        memory[range].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Writes `value` to linear memory at `offset` in the little-endian
+    /// format used by the canonical ABI.
+    ///
+    /// Note that `offset` isn't required to be aligned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `offset..offset + 4` is out of bounds of linear
+    /// memory.
+    pub fn write_u32(&mut self, offset: usize, value: u32) -> Result<()> {
+        self.write(offset, &value.to_le_bytes())
+    }
+
+    /// Returns how many elements of `size` bytes to store between calls to
+    /// `check_interrupt`, see
+    /// [`Config::component_model_copy_check_interval`](crate::Config::component_model_copy_check_interval).
+    pub(crate) fn elements_between_checks(&self, size: usize) -> usize {
+        let tunables = &self.store.0.engine().config().tunables;
+        let enabled = tunables.epoch_interruption || tunables.consume_fuel;
+        elements_between_checks(&self.store.0, enabled, size)
+    }
+
+    /// Checks whether a large copy into linear memory should be interrupted
+    /// because the epoch deadline was reached or fuel ran out.
+    pub(crate) fn check_interrupt(&mut self) -> Result<()> {
+        self.store.0.check_interrupt()
+    }
 }
 
 /// Like `MemoryMut` but for a read-only version that's used during lifting.
+///
+/// Embedders can create one with [`Memory::from_options`] to read the linear
+/// memory of a component function with [`Memory::read`] and
+/// [`Memory::read_u32`].
 pub struct Memory<'a> {
     pub(crate) store: &'a StoreOpaque,
     options: &'a Options,
 }
 
-#[doc(hidden)]
 impl<'a> Memory<'a> {
+    /// Creates a view of the linear memory that `options` specify within
+    /// `store`, for example the options of a function returned by
+    /// [`Func::options`](crate::component::Func::options).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `options` belong to a different store, don't
+    /// specify a memory, or specify a shared memory.
+    pub fn from_options<T: 'a>(
+        store: impl Into<StoreContext<'a, T>>,
+        options: &'a Options,
+    ) -> Result<Memory<'a>> {
+        let store = store.into().0;
+        options.check_memory(store)?;
+        Ok(Memory::new(store, options))
+    }
+
+    #[doc(hidden)]
     pub fn new(store: &'a StoreOpaque, options: &'a Options) -> Memory<'a> {
         Memory { store, options }
     }
 
+    #[doc(hidden)]
     pub fn as_slice(&self) -> &'a [u8] {
         self.options.memory(self.store)
     }
 
+    #[doc(hidden)]
     pub fn string_encoding(&self) -> StringEncoding {
         self.options.string_encoding()
     }
 
+    #[doc(hidden)]
     pub fn options(&self) -> &Options {
         self.options
     }
+
+    /// Returns whether strict canonical ABI validation is enabled, see
+    /// [`Config::component_model_strict_validation`](crate::Config::component_model_strict_validation).
+    #[doc(hidden)]
+    pub fn strict_validation(&self) -> bool {
+        self.store
+            .engine()
+            .config()
+            .component_model_strict_validation
+    }
+
+    /// Returns whether invalid `char`s are lifted as U+FFFD, see
+    /// [`Config::component_model_lossy_chars`](crate::Config::component_model_lossy_chars).
+    #[doc(hidden)]
+    pub fn lossy_chars(&self) -> bool {
+        self.store.engine().config().component_model_lossy_chars
+    }
+
+    /// Returns an error if a string of `bytes` bytes exceeds
+    /// [`Config::component_model_max_lifted_string_bytes`](crate::Config::component_model_max_lifted_string_bytes).
+    pub(crate) fn check_string_limit(&self, bytes: usize) -> Result<()> {
+        let max = self
+            .store
+            .engine()
+            .config()
+            .component_model_max_lifted_string_bytes;
+        if bytes > max {
+            return Err(LiftError::LimitExceeded {
+                what: "string",
+                len: bytes,
+                limit: max,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Returns an error if a list of `len` elements exceeds
+    /// [`Config::component_model_max_lifted_list_elements`](crate::Config::component_model_max_lifted_list_elements).
+    pub(crate) fn check_list_limit(&self, len: usize) -> Result<()> {
+        let max = self
+            .store
+            .engine()
+            .config()
+            .component_model_max_lifted_list_elements;
+        if len > max {
+            return Err(LiftError::LimitExceeded {
+                what: "list",
+                len,
+                limit: max,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Returns how many elements of `size` bytes to load between calls to
+    /// `check_interrupt`, see
+    /// [`Config::component_model_copy_check_interval`](crate::Config::component_model_copy_check_interval).
+    pub(crate) fn elements_between_checks(&self, size: usize) -> usize {
+        let tunables = &self.store.engine().config().tunables;
+        let enabled = tunables.epoch_interruption || tunables.consume_fuel;
+        elements_between_checks(self.store, enabled, size)
+    }
+
+    /// Checks whether a large copy out of linear memory should be interrupted
+    /// because the epoch deadline was reached or fuel ran out.
+    pub(crate) fn check_interrupt(&self) -> Result<()> {
+        self.store.check_interrupt_shared()
+    }
+
+    /// Returns the `len` bytes of linear memory starting at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any part of the range is out of bounds of linear
+    /// memory.
+    pub fn read(&self, offset: usize, len: usize) -> Result<&'a [u8]> {
+        let memory = self.as_slice();
+        let range = bounds_check("read", memory.len(), offset, len, 1)?;
+        Ok(&memory[range])
+    }
+
+    /// Reads a `u32` stored at `offset` in the little-endian format used by
+    /// the canonical ABI.
+    ///
+    /// Note that `offset` isn't required to be aligned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `offset..offset + 4` is out of bounds of linear
+    /// memory.
+    pub fn read_u32(&self, offset: usize) -> Result<u32> {
+        let bytes = self.read(offset, 4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+/// Returns how many elements of `size` bytes lifting or lowering copies
+/// between checks for interruption, which is unbounded unless `enabled`.
+fn elements_between_checks(store: &StoreOpaque, enabled: bool, size: usize) -> usize {
+    if !enabled {
+        return usize::MAX;
+    }
+    let interval = store.engine().config().component_model_copy_check_interval;
+    (interval / size.max(1)).max(1)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::component::{Component, Linker};
+    use crate::{AsContext, AsContextMut, Config, Engine, Store};
+
+    /// Instantiates a component whose export has a `memory` and `realloc`,
+    /// returning the store along with that export's canonical options.
+    pub(crate) fn store_and_options() -> Result<(Store<()>, Options)> {
+        let mut config = Config::new();
+        config.wasm_component_model(true);
+        let engine = Engine::new(&config)?;
+        let component = Component::new(
+            &engine,
+            r#"(component
+                (core module $m
+                    (memory (export "memory") 1)
+                    (global $last (mut i32) (i32.const 8))
+                    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                        (local $ret i32)
+                        ;; shrink in place
+                        (if (local.get 0) (then (return (local.get 0))))
+                        (local.set $ret (global.get $last))
+                        (global.set $last (i32.add (global.get $last) (i32.const 64)))
+                        (local.get $ret))
+                    (func (export "f") (param i32 i32))
+                )
+                (core instance $i (instantiate $m))
+                (func (export "f") (param string)
+                    (canon lift (core func $i "f")
+                        (memory $i "memory")
+                        (realloc (func $i "realloc"))
+                    )
+                )
+            )"#,
+        )?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let func = instance.get_func(&mut store, "f").unwrap();
+        let options = store.as_context()[func.0].options;
+        Ok((store, options))
+    }
+
+    #[test]
+    fn memory_read_write() -> Result<()> {
+        let (mut store, options) = store_and_options()?;
+        let size = options.memory(store.as_context().0).len();
+
+        let mut memory = MemoryMut::new(store.as_context_mut(), &options);
+        memory.write(0, b"abc")?;
+        memory.write_u32(size - 4, 0x1234_5678)?;
+        memory.write(size, &[])?;
+        let err = memory.write(size - 3, &[0; 4]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "write pointer/length out of bounds of memory \
+             (ptr 0xfffd, len 4, memory size 65536)"
+        );
+        assert!(memory.write_u32(size - 3, 0).is_err());
+        assert!(memory.write(size + 1, &[]).is_err());
+        assert!(memory.write(usize::MAX, &[0]).is_err());
+
+        let memory = Memory::new(store.as_context().0, &options);
+        assert_eq!(memory.read(0, 3)?, b"abc");
+        assert_eq!(memory.read_u32(size - 4)?, 0x1234_5678);
+        assert_eq!(memory.read(size - 4, 4)?, [0x78, 0x56, 0x34, 0x12]);
+        // The failed write above didn't modify memory.
+        assert_eq!(memory.read(size - 3, 3)?, [0x56, 0x34, 0x12]);
+        // Unaligned reads are allowed.
+        assert_eq!(memory.read_u32(1)?, u32::from_le_bytes(*b"bc\0\0"));
+        assert_eq!(memory.read(size, 0)?, b"");
+
+        let err = memory.read(size - 3, 4).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "read pointer/length out of bounds of memory \
+             (ptr 0xfffd, len 4, memory size 65536)"
+        );
+        assert!(memory.read_u32(size - 3).is_err());
+        assert!(memory.read(size + 1, 0).is_err());
+        assert!(memory.read(usize::MAX, 2).is_err());
+
+        Ok(())
+    }
 }
@@ -1,14 +1,20 @@
-use crate::component::func::{Func, Memory, MemoryMut, Options};
+use crate::component::func::{
+    Func, LiftError, LowerError, Memory, MemoryMut, Options, ParamsAndResults,
+};
+use crate::component::types::DisplayInterfaceType;
 use crate::store::StoreOpaque;
 use crate::{AsContext, AsContextMut, StoreContext, StoreContextMut, ValRaw};
 use anyhow::{bail, Context, Result};
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::hash::{BuildHasher, Hash};
 use std::marker;
 use std::mem::{self, MaybeUninit};
+use std::ops::Range;
 use std::str;
 use wasmtime_environ::component::{
-    ComponentTypes, InterfaceType, StringEncoding, MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
+    ComponentTypes, InterfaceType, StringEncoding, TypeFunc, MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
 };
 
 /// A statically-typed version of [`Func`] which takes `Params` as input and
@@ -23,6 +29,12 @@ use wasmtime_environ::component::{
 /// and usage will panic if used with the wrong store.
 ///
 /// This type is primarily created with the [`Func::typed`] API.
+///
+/// Component functions have a single result in the version of the component
+/// model implemented here, so functions which produce several values do so by
+/// returning a `tuple`. Such a result is bound with a Rust tuple as `Return`,
+/// and is lifted from the stack if it flattens to at most one core wasm value
+/// or from linear memory otherwise.
 pub struct TypedFunc<Params, Return> {
     func: Func,
 
@@ -114,7 +126,7 @@ where
     /// This means that embedders must unconditionally call
     /// [`TypedFunc::post_return`] when a function returns. If this function
     /// call returns an error, however, then [`TypedFunc::post_return`] is not
-    /// required.
+    /// required, and calling it anyway returns an error.
     ///
     /// # Errors
     ///
@@ -129,6 +141,13 @@ where
     ///   instance is currently calling a host function.
     /// * If a previous function call occurred and the corresponding
     ///   `post_return` hasn't been invoked yet.
+    /// * If this function's canonical options specify a shared memory. Shared
+    ///   memories may be accessed by other threads at any time so they're not
+    ///   supported by the host implementation of the canonical ABI, and such
+    ///   calls always fail before any arguments are lowered.
+    /// * If the epoch deadline is reached or fuel runs out while copying a
+    ///   large list or string in or out of memory, see
+    ///   [`Config::component_model_copy_check_interval`](crate::Config::component_model_copy_check_interval).
     ///
     /// In general there are many ways that things could go wrong when copying
     /// types in and out of a wasm module with the canonical ABI, and certain
@@ -143,9 +162,90 @@ where
     ///
     /// # Panics
     ///
-    /// This function will panic if `store` does not own this function.
+    /// This function will panic if `store` does not own this function. It
+    /// will also panic if `store` has async support enabled, in which case
+    /// [`TypedFunc::call_async`] must be used instead.
     pub fn call(&self, mut store: impl AsContextMut, params: Params) -> Result<Return> {
-        let store = &mut store.as_context_mut();
+        let mut store = store.as_context_mut();
+        assert!(
+            !store.0.async_support(),
+            "must use `call_async` when async support is enabled on the config"
+        );
+        self.call_impl(&mut store, params, None)
+    }
+
+    /// Same as [`TypedFunc::call`], except that `buffer` is used as scratch
+    /// space for the lowered parameters and results.
+    ///
+    /// This is intended for callers which invoke functions at a high frequency
+    /// and would like to reuse one [`CallBuffer`] across many calls, possibly
+    /// of different functions. The contents of `buffer` don't affect the
+    /// result of the call, and are unspecified afterwards.
+    ///
+    /// As with [`TypedFunc::call`], a successful call must be followed by a
+    /// call to [`TypedFunc::post_return`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`TypedFunc::call`].
+    pub fn call_with_space(
+        &self,
+        mut store: impl AsContextMut,
+        params: Params,
+        buffer: &mut CallBuffer,
+    ) -> Result<Return> {
+        let mut store = store.as_context_mut();
+        assert!(
+            !store.0.async_support(),
+            "must use `call_async` when async support is enabled on the config"
+        );
+        self.call_impl(&mut store, params, Some(buffer))
+    }
+
+    /// Exactly like [`TypedFunc::call`], except for use with stores that have
+    /// async support enabled.
+    ///
+    /// The lowering of `params`, the call into WebAssembly, and the lifting of
+    /// the result all happen on a fiber. This means that async host imports
+    /// invoked by the component, as well as fuel or epoch based yields, will
+    /// suspend back to the caller of this function rather than blocking the
+    /// current thread.
+    ///
+    /// As with [`TypedFunc::call`], a successful call must be followed by a
+    /// call to [`TypedFunc::post_return_async`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`TypedFunc::call`], except that
+    /// here `store` must have async support enabled.
+    #[cfg(feature = "async")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "async")))]
+    pub async fn call_async<T>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        params: Params,
+    ) -> Result<Return>
+    where
+        T: Send,
+        Params: Send + Sync,
+        Return: Send + Sync,
+    {
+        let mut store = store.as_context_mut();
+        assert!(
+            store.0.async_support(),
+            "cannot use `call_async` without enabling async support in the config"
+        );
+        store
+            .on_fiber(|store| self.call_impl(store, params, None))
+            .await?
+    }
+
+    fn call_impl<T>(
+        &self,
+        store: &mut StoreContextMut<'_, T>,
+        params: Params,
+        buffer: Option<&mut CallBuffer>,
+    ) -> Result<Return> {
         // Note that this is in theory simpler than it might read at this time.
         // Here we're doing a runtime dispatch on the `flatten_count` for the
         // params/results to see whether they're inbounds. This creates 4 cases
@@ -158,32 +258,36 @@ where
         // count)
         if Params::flatten_count() <= MAX_FLAT_PARAMS {
             if Return::flatten_count() <= MAX_FLAT_RESULTS {
-                self.func.call_raw(
+                self.call_in(
                     store,
                     &params,
+                    buffer,
                     Self::lower_stack_args,
                     Self::lift_stack_result,
                 )
             } else {
-                self.func.call_raw(
+                self.call_in(
                     store,
                     &params,
+                    buffer,
                     Self::lower_stack_args,
                     Self::lift_heap_result,
                 )
             }
         } else {
             if Return::flatten_count() <= MAX_FLAT_RESULTS {
-                self.func.call_raw(
+                self.call_in(
                     store,
                     &params,
+                    buffer,
                     Self::lower_heap_args,
                     Self::lift_stack_result,
                 )
             } else {
-                self.func.call_raw(
+                self.call_in(
                     store,
                     &params,
+                    buffer,
                     Self::lower_heap_args,
                     Self::lift_heap_result,
                 )
@@ -191,6 +295,32 @@ where
         }
     }
 
+    /// Invokes the underlying function using `buffer` for the lowered
+    /// parameters and results, if provided, and otherwise the stack.
+    ///
+    /// Stack space is only reserved when there's no `buffer`, so callers
+    /// reusing a buffer don't pay for both.
+    fn call_in<T, LowerParams: Copy, LowerReturn: Copy>(
+        &self,
+        store: &mut StoreContextMut<'_, T>,
+        params: &Params,
+        buffer: Option<&mut CallBuffer>,
+        lower: impl FnOnce(
+            &mut StoreContextMut<'_, T>,
+            &Options,
+            &Params,
+            &mut MaybeUninit<LowerParams>,
+        ) -> Result<()>,
+        lift: impl FnOnce(&StoreOpaque, &Options, &LowerReturn) -> Result<Return>,
+    ) -> Result<Return> {
+        match buffer {
+            Some(buffer) => self
+                .func
+                .call_raw_in(store, params, buffer.space(), lower, lift),
+            None => self.func.call_raw(store, params, lower, lift),
+        }
+    }
+
     /// Lower parameters directly onto the stack specified by the `dst`
     /// location.
     ///
@@ -231,7 +361,9 @@ where
         // in-bounds.
         let mut memory = MemoryMut::new(store.as_context_mut(), options);
         let ptr = memory.realloc(0, 0, Params::ALIGN32, Params::SIZE32)?;
-        params.store(&mut memory, ptr)?;
+        memory.free_on_error(ptr, Params::SIZE32, Params::ALIGN32, |memory| {
+            params.store(memory, ptr)
+        })?;
 
         // Note that the pointer here is stored as a 64-bit integer. This allows
         // this to work with either 32 or 64-bit memories. For a 32-bit memory
@@ -258,6 +390,16 @@ where
         dst: &Return::Lower,
     ) -> Result<Return> {
         assert!(Return::flatten_count() <= MAX_FLAT_RESULTS);
+
+        // Functions returning `()` have nothing to lift, so skip that
+        // entirely. Note that this condition is known at compile time.
+        if Return::IS_RUST_UNIT_TYPE {
+            // This is only `true` for `()`, so `Return` is `()` here and this
+            // is a no-op "conversion" from `()` to itself.
+            debug_assert_eq!(mem::size_of::<Return>(), 0);
+            return Ok(unsafe { mem::transmute_copy::<(), Return>(&()) });
+        }
+
         Return::lift(store, options, dst)
     }
 
@@ -268,22 +410,191 @@ where
         // FIXME: needs to read an i64 for memory64
         let ptr = usize::try_from(dst.get_u32())?;
         if ptr % usize::try_from(Return::ALIGN32)? != 0 {
-            bail!("return pointer not aligned");
+            return Err(LiftError::Misaligned {
+                what: "return",
+                ptr,
+                align: Return::ALIGN32,
+            }
+            .into());
         }
 
         let memory = Memory::new(store, options);
-        let bytes = memory
-            .as_slice()
-            .get(ptr..)
-            .and_then(|b| b.get(..Return::SIZE32))
-            .ok_or_else(|| anyhow::anyhow!("pointer out of bounds of memory"))?;
-        Return::load(&memory, bytes)
+        let range = bounds_check("return", memory.as_slice().len(), ptr, 1, Return::SIZE32)?;
+        Return::load(&memory, &memory.as_slice()[range])
     }
 
     /// See [`Func::post_return`]
     pub fn post_return(&self, store: impl AsContextMut) -> Result<()> {
         self.func.post_return(store)
     }
+
+    /// See [`Func::post_return_async`]
+    #[cfg(feature = "async")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "async")))]
+    pub async fn post_return_async<T: Send>(
+        &self,
+        store: impl AsContextMut<Data = T>,
+    ) -> Result<()> {
+        self.func.post_return_async(store).await
+    }
+
+    /// Calls this function with `params` and then immediately invokes
+    /// [`TypedFunc::post_return`], returning the result of the call.
+    ///
+    /// This is a convenience for the common case where the return value is
+    /// fully processed by the host as soon as it's returned. It's intended to
+    /// be used with owned return types such as integers, [`String`], or
+    /// [`Vec<T>`], all of which are materialized into host memory before
+    /// `post-return` runs.
+    ///
+    /// Note that lazy types like [`WasmStr`] and [`WasmList`] still refer to
+    /// guest memory after this function returns. Their contents may have been
+    /// modified or deallocated by `post-return`, so returning those types from
+    /// this method is generally not what's wanted. Use [`TypedFunc::call`] or
+    /// [`TypedFunc::call_guarded`] to process such values before
+    /// `post-return` runs instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the same situations as [`TypedFunc::call`], in
+    /// which case `post-return` is not run. If `post-return` itself fails then
+    /// the error returned has a "failed to invoke post-return" context
+    /// attached to distinguish it from an error in the call itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same situations as [`TypedFunc::call`].
+    pub fn call_and_post_return(
+        &self,
+        mut store: impl AsContextMut,
+        params: Params,
+    ) -> Result<Return> {
+        let mut store = store.as_context_mut();
+        let result = self.call(&mut store, params)?;
+        self.post_return(&mut store)
+            .context("failed to invoke post-return")?;
+        Ok(result)
+    }
+
+    /// Same as [`TypedFunc::call`], except that the result is wrapped in a
+    /// [`ReturnValue`] guard which is responsible for invoking
+    /// [`TypedFunc::post_return`].
+    ///
+    /// The returned guard dereferences to the `Return` value so it can be
+    /// inspected as usual, and then [`ReturnValue::finish`] must be called to
+    /// run the `post-return` step. This means that the requirement to call
+    /// `post_return` after each successful call is encoded in the type of the
+    /// return value rather than only being documented.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the same situations as [`TypedFunc::call`].
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same situations as [`TypedFunc::call`].
+    pub fn call_guarded(
+        &self,
+        store: impl AsContextMut,
+        params: Params,
+    ) -> Result<ReturnValue<Return>> {
+        let value = self.call(store, params)?;
+        Ok(ReturnValue {
+            func: self.func,
+            value: Some(value),
+        })
+    }
+}
+
+/// Reusable scratch space for the lowered parameters and results of a call
+/// made with [`TypedFunc::call_with_space`].
+///
+/// A buffer grows as needed to fit the flattened parameters and results of
+/// the functions it's used with, and that space is then reused by subsequent
+/// calls. It isn't tied to any particular function or store.
+#[derive(Default)]
+pub struct CallBuffer {
+    space: Vec<ValRaw>,
+}
+
+impl CallBuffer {
+    /// Creates a new empty buffer.
+    pub fn new() -> CallBuffer {
+        CallBuffer::default()
+    }
+
+    /// Returns space within this buffer suitable for a `ParamsAndResults`,
+    /// growing the buffer if necessary.
+    fn space<P: Copy, R: Copy>(&mut self) -> &mut MaybeUninit<ParamsAndResults<P, R>> {
+        let size = mem::size_of::<ParamsAndResults<P, R>>();
+        let val_size = mem::size_of::<ValRaw>();
+        assert!(size % val_size == 0);
+        assert!(mem::align_of::<ParamsAndResults<P, R>>() == mem::align_of::<ValRaw>());
+        let len = size / val_size;
+        if self.space.len() < len {
+            self.space.resize(len, ValRaw::u64(0));
+        }
+        // The buffer is now large enough and has the same alignment as
+        // `ParamsAndResults`, as asserted above, and `MaybeUninit` makes no
+        // assumptions about the contents, so this cast is valid.
+        unsafe { &mut *self.space.as_mut_ptr().cast() }
+    }
+}
+
+/// The result of [`TypedFunc::call_guarded`], which must be consumed with
+/// [`ReturnValue::finish`].
+///
+/// This guard dereferences to the value returned by the component function.
+/// Calling [`ReturnValue::finish`] invokes the function's `post-return` and
+/// hands back the value, after which the component instance may be entered
+/// again.
+///
+/// Dropping a [`ReturnValue`] without calling [`ReturnValue::finish`] leaves
+/// the component instance unable to be entered, and will panic when debug
+/// assertions are enabled.
+#[must_use = "`ReturnValue::finish` must be called to run the function's post-return"]
+pub struct ReturnValue<Return> {
+    func: Func,
+    value: Option<Return>,
+}
+
+impl<Return> ReturnValue<Return> {
+    /// Invokes the `post-return` of the function which produced this value,
+    /// returning the value itself.
+    ///
+    /// As with [`TypedFunc::post_return`], the returned value remains usable
+    /// from the embedder's perspective but values which refer to guest memory,
+    /// such as [`WasmStr`] or [`WasmList`], may no longer view the same
+    /// contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `post-return` function traps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own the function which produced this value.
+    pub fn finish(mut self, store: impl AsContextMut) -> Result<Return> {
+        let value = self.value.take().unwrap();
+        self.func.post_return(store)?;
+        Ok(value)
+    }
+}
+
+impl<Return> std::ops::Deref for ReturnValue<Return> {
+    type Target = Return;
+
+    fn deref(&self) -> &Return {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<Return> Drop for ReturnValue<Return> {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) && self.value.is_some() && !std::thread::panicking() {
+            panic!("`ReturnValue` dropped without calling `ReturnValue::finish`");
+        }
+    }
 }
 
 /// A trait representing a static list of parameters that can be passed to a
@@ -310,6 +621,11 @@ pub unsafe trait ComponentParams: ComponentType {
         params: &[(Option<String>, InterfaceType)],
         types: &ComponentTypes,
     ) -> Result<()>;
+
+    /// Returns a description of each parameter, as with
+    /// [`ComponentType::describe`].
+    #[doc(hidden)]
+    fn describe_params() -> Vec<String>;
 }
 
 /// A trait representing types which can be passed to and read from components
@@ -325,6 +641,199 @@ pub unsafe trait ComponentParams: ComponentType {
 /// The contents of this trait are hidden as it's intended to be an
 /// implementation detail of Wasmtime. The contents of this trait are not
 /// covered by Wasmtime's stability guarantees.
+///
+/// # Deriving
+///
+/// This trait, along with [`Lift`] and [`Lower`], can be derived for Rust
+/// `struct`s and `enum`s with `#[derive(ComponentType)]`. Generic type
+/// parameters are supported and the generated implementations require each
+/// parameter used by a field to implement the derived trait as well. Fields of
+/// type `PhantomData<T>` are skipped entirely and are not part of the
+/// component type.
+///
+/// ```
+/// use std::marker::PhantomData;
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(record)]
+/// struct Pair<T, Tag> {
+///     a: T,
+///     b: T,
+///     tag: PhantomData<Tag>,
+/// }
+///
+/// fn assert_lower<T: Lower>() {}
+/// assert_lower::<Pair<u32, std::fs::File>>();
+/// ```
+///
+/// Using a generic type with a parameter that doesn't itself implement the
+/// trait fails to compile:
+///
+/// ```compile_fail
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(record)]
+/// struct Pair<T> {
+///     a: T,
+///     b: T,
+/// }
+///
+/// fn assert_lower<T: Lower>() {}
+/// assert_lower::<Pair<std::fs::File>>();
+/// ```
+///
+/// The fields of a record are normally expected in the component type in the
+/// same order as they're declared in Rust. Records may instead list the
+/// component names of their fields in the order the component type declares
+/// them with `#[component(field_order = "...")]`, in which case they're
+/// typechecked, lifted, and lowered in that order. The component type must
+/// still declare its fields in exactly this order since it determines the
+/// canonical ABI layout of the record:
+///
+/// ```
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// // Typechecks against `(record (field "y" u32) (field "x" u32))`.
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(record)]
+/// #[component(field_order = "y, x")]
+/// struct Position {
+///     x: u32,
+///     y: u32,
+/// }
+/// ```
+///
+/// Only records have fields to reorder, so this fails to compile for other
+/// types:
+///
+/// ```compile_fail
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(enum)]
+/// #[component(field_order = "b, a")]
+/// enum Letter {
+///     A,
+///     B,
+/// }
+/// ```
+///
+/// A `struct` with exactly one field can instead be derived with
+/// `#[component(transparent)]`, in which case it's represented exactly as its
+/// field is rather than as a `record`. This is useful for newtypes:
+///
+/// ```
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// // Typechecks against, and is passed as, a plain `u64`.
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(transparent)]
+/// struct UserId(u64);
+/// ```
+///
+/// Deriving a transparent type for a `struct` with more than one field fails
+/// to compile:
+///
+/// ```compile_fail
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(transparent)]
+/// struct Position(u32, u32);
+/// ```
+///
+/// ```compile_fail
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(transparent)]
+/// struct Position {
+///     x: u32,
+///     y: u32,
+/// }
+/// ```
+///
+/// Records whose fields are all integers and floats, without any padding in
+/// between them or at the end, can additionally be marked with
+/// `#[component(pod)]`. Such records must be `#[repr(C)]` so their
+/// representation in Rust is the same as in linear memory, which allows lists
+/// of them to be lifted, for example with [`WasmList::to_vec`], by copying
+/// all of their bytes at once rather than loading each field individually:
+///
+/// ```
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(record)]
+/// #[component(pod)]
+/// #[repr(C)]
+/// struct Vec4 {
+///     x: f32,
+///     y: f32,
+///     z: f32,
+///     w: f32,
+/// }
+/// ```
+///
+/// Marking records with padding, `bool`s, or `char`s as `pod` fails to
+/// compile:
+///
+/// ```compile_fail
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(record)]
+/// #[component(pod)]
+/// #[repr(C)]
+/// struct Padded {
+///     a: u8,
+///     b: u32,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(record)]
+/// #[component(pod)]
+/// #[repr(C)]
+/// struct Flag {
+///     set: bool,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(record)]
+/// #[component(pod)]
+/// #[repr(C)]
+/// struct Letter {
+///     c: char,
+/// }
+/// ```
+///
+/// This is checked based on the actual types of the fields rather than their
+/// names, so aliases can't be used to get around it either:
+///
+/// ```compile_fail
+/// use wasmtime::component::{ComponentType, Lift, Lower};
+///
+/// #[allow(non_camel_case_types)]
+/// type u8 = bool;
+///
+/// #[derive(ComponentType, Lift, Lower)]
+/// #[component(record)]
+/// #[component(pod)]
+/// #[repr(C)]
+/// struct Flag {
+///     set: u8,
+/// }
+/// ```
 //
 // Note that this is an `unsafe` trait as `TypedFunc`'s safety heavily relies on
 // the correctness of the implementations of this trait. Some ways in which this
@@ -382,12 +891,31 @@ pub unsafe trait ComponentType {
         mem::size_of::<Self::Lower>() / mem::size_of::<ValRaw>()
     }
 
+    /// Whether this is the Rust unit type `()`, whose lifted value doesn't
+    /// depend on the lowered results of a call at all.
+    ///
+    /// This lets calls of functions returning `()` skip lifting entirely, and
+    /// must only be `true` for `()` itself.
+    #[doc(hidden)]
+    const IS_RUST_UNIT_TYPE: bool = false;
+
     // FIXME: need SIZE64 and ALIGN64 probably
 
     /// Performs a type-check to see whether this component value type matches
     /// the interface type `ty` provided.
     #[doc(hidden)]
     fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()>;
+
+    /// Returns a description of this type for diagnostics, such as
+    /// `list<u8>`.
+    ///
+    /// This uses the same syntax that interface types are rendered with where
+    /// possible and otherwise falls back to the name of the Rust type, for
+    /// example for types using `#[derive(ComponentType)]`.
+    #[doc(hidden)]
+    fn describe() -> String {
+        short_type_name(std::any::type_name::<Self>())
+    }
 }
 
 /// Host types which can be passed to WebAssembly components.
@@ -435,6 +963,36 @@ pub unsafe trait Lower: ComponentType {
     /// This will only be called if `typecheck` passes for `Op::Lower`.
     #[doc(hidden)]
     fn store<T>(&self, memory: &mut MemoryMut<'_, T>, offset: usize) -> Result<()>;
+
+    /// Same as [`Lower::lower`], except the destination is a slice of core
+    /// wasm values rather than `Self::Lower`.
+    ///
+    /// This is intended for embeddings which only know the number of core
+    /// wasm values at runtime, such as dynamic invocation layers. An error is
+    /// returned if `dst` doesn't have exactly [`ComponentType::flatten_count`]
+    /// elements. Like `lower`, `dst` is fully initialized if this returns
+    /// successfully.
+    #[doc(hidden)]
+    fn lower_to_raws<T>(
+        &self,
+        store: &mut StoreContextMut<T>,
+        options: &Options,
+        dst: &mut [MaybeUninit<ValRaw>],
+    ) -> Result<()> {
+        let expected = Self::flatten_count();
+        if dst.len() != expected {
+            bail!(
+                "expected {} core wasm values to lower into, found {}",
+                expected,
+                dst.len()
+            );
+        }
+        // Note that `Self::Lower` is required to be a list of `ValRaw` with
+        // the alignment of `ValRaw`, and `flatten_count` asserts as much, so
+        // a slice of exactly `flatten_count` values has the same layout.
+        let dst = unsafe { &mut *dst.as_mut_ptr().cast::<MaybeUninit<Self::Lower>>() };
+        self.lower(store, options, dst)
+    }
 }
 
 /// Host types which can be created from the canonical ABI.
@@ -464,6 +1022,40 @@ pub unsafe trait Lift: Sized + ComponentType {
     /// for `Op::Lift` this needs to be overridden.
     #[doc(hidden)]
     fn load(memory: &Memory<'_>, bytes: &[u8]) -> Result<Self>;
+
+    /// Loads `len` consecutive values of `Self` from `bytes`, which is exactly
+    /// `len * Self::SIZE32` bytes large, appending them to `dst`.
+    ///
+    /// The default implementation loads each value individually, but
+    /// `#[component(pod)]` records override this to copy all of the bytes at
+    /// once. Large lists are split into chunks of at most
+    /// [`Config::component_model_copy_check_interval`](crate::Config::component_model_copy_check_interval)
+    /// bytes, with checks for interruption in between, before this is called,
+    /// so implementations don't need to check for interruption themselves.
+    #[doc(hidden)]
+    fn load_list(memory: &Memory<'_>, bytes: &[u8], len: usize, dst: &mut Vec<Self>) -> Result<()> {
+        load_list(memory, bytes, len, dst)
+    }
+
+    /// Same as [`Lift::lift`], except the source is a slice of core wasm
+    /// values rather than `Self::Lower`.
+    ///
+    /// This is the mirror of [`Lower::lower_to_raws`] and returns an error if
+    /// `src` doesn't have exactly [`ComponentType::flatten_count`] elements.
+    #[doc(hidden)]
+    fn lift_from_raws(store: &StoreOpaque, options: &Options, src: &[ValRaw]) -> Result<Self> {
+        let expected = Self::flatten_count();
+        if src.len() != expected {
+            bail!(
+                "expected {} core wasm values to lift from, found {}",
+                expected,
+                src.len()
+            );
+        }
+        // See `Lower::lower_to_raws` for why this cast is valid.
+        let src = unsafe { &*src.as_ptr().cast::<Self::Lower>() };
+        Self::lift(store, options, src)
+    }
 }
 
 // Macro to help generate "forwarding implementations" of `ComponentType` to
@@ -478,6 +1070,10 @@ macro_rules! forward_type_impls {
             const SIZE32: usize = <$b as ComponentType>::SIZE32;
             const ALIGN32: u32 = <$b as ComponentType>::ALIGN32;
 
+            fn describe() -> String {
+                <$b as ComponentType>::describe()
+            }
+
             #[inline]
             fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
                 <$b as ComponentType>::typecheck(ty, types)
@@ -549,12 +1145,12 @@ macro_rules! forward_list_lifts {
         unsafe impl <T: Lift> Lift for $a {
             fn lift(store: &StoreOpaque, options: &Options, src: &Self::Lower) -> Result<Self> {
                 let list = <WasmList::<T> as Lift>::lift(store, options, src)?;
-                (0..list.len).map(|index| list.get_from_store(store, index).unwrap()).collect()
+                Ok(list.load_all(store)?.into())
             }
 
             fn load(memory: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
                 let list = <WasmList::<T> as Lift>::load(memory, bytes)?;
-                (0..list.len).map(|index| list.get_from_store(&memory.store, index).unwrap()).collect()
+                Ok(list.load_all(&memory.store)?.into())
             }
         }
     )*)
@@ -567,6 +1163,125 @@ forward_list_lifts! {
     Vec<T>,
 }
 
+// Maps are represented in the component model as a `list<tuple<K, V>>` of their
+// entries. Lowering produces the entries in the map's iteration order, and
+// lifting inserts each entry in order, so the last entry wins if a key appears
+// more than once unless strict validation is enabled, in which case that's an
+// error.
+forward_type_impls! {
+    (K: ComponentType, V: ComponentType, S) HashMap<K, V, S> => [(K, V)],
+    (K: ComponentType, V: ComponentType) BTreeMap<K, V> => [(K, V)],
+}
+
+macro_rules! map_lowers {
+    ($(($($generics:tt)*) $a:ty,)*) => ($(
+        unsafe impl <$($generics)*> Lower for $a {
+            fn lower<U>(
+                &self,
+                store: &mut StoreContextMut<U>,
+                options: &Options,
+                dst: &mut MaybeUninit<[ValRaw; 2]>,
+            ) -> Result<()> {
+                LowerList(self.iter()).lower(store, options, dst)
+            }
+
+            fn store<U>(&self, memory: &mut MemoryMut<'_, U>, offset: usize) -> Result<()> {
+                LowerList(self.iter()).store(memory, offset)
+            }
+        }
+    )*)
+}
+
+map_lowers! {
+    (K: Lower, V: Lower, S) HashMap<K, V, S>,
+    (K: Lower, V: Lower) BTreeMap<K, V>,
+}
+
+unsafe impl<K, V, S> Lift for HashMap<K, V, S>
+where
+    K: Lift + Eq + Hash,
+    V: Lift,
+    S: BuildHasher + Default,
+{
+    fn lift(store: &StoreOpaque, options: &Options, src: &Self::Lower) -> Result<Self> {
+        let memory = Memory::new(store, options);
+        let list = <WasmList<(K, V)> as Lift>::lift(store, options, src)?;
+        let map = HashMap::with_capacity_and_hasher(map_capacity(list.len), S::default());
+        lift_map(&memory, &list, map, |map, k, v| map.insert(k, v).is_some())
+    }
+
+    fn load(memory: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
+        let list = <WasmList<(K, V)> as Lift>::load(memory, bytes)?;
+        let map = HashMap::with_capacity_and_hasher(map_capacity(list.len), S::default());
+        lift_map(memory, &list, map, |map, k, v| map.insert(k, v).is_some())
+    }
+}
+
+unsafe impl<K, V> Lift for BTreeMap<K, V>
+where
+    K: Lift + Ord,
+    V: Lift,
+{
+    fn lift(store: &StoreOpaque, options: &Options, src: &Self::Lower) -> Result<Self> {
+        let memory = Memory::new(store, options);
+        let list = <WasmList<(K, V)> as Lift>::lift(store, options, src)?;
+        lift_map(&memory, &list, BTreeMap::new(), |map, k, v| {
+            map.insert(k, v).is_some()
+        })
+    }
+
+    fn load(memory: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
+        let list = <WasmList<(K, V)> as Lift>::load(memory, bytes)?;
+        lift_map(memory, &list, BTreeMap::new(), |map, k, v| {
+            map.insert(k, v).is_some()
+        })
+    }
+}
+
+/// The largest number of entries preallocated when lifting a map.
+///
+/// The length of the list is controlled by the guest, so larger maps grow as
+/// their entries are inserted instead.
+const MAX_PREALLOCATED_MAP_ENTRIES: usize = 1024;
+
+/// Returns the initial capacity of a map lifted from a list of `len` entries.
+fn map_capacity(len: usize) -> usize {
+    len.min(MAX_PREALLOCATED_MAP_ENTRIES)
+}
+
+/// Inserts each entry of `list` into `map` with `insert`, which returns
+/// whether the key was already present.
+///
+/// Duplicate keys are an error if strict validation is enabled, otherwise
+/// it's up to `insert` to replace the previous value.
+fn lift_map<K: Lift, V: Lift, M>(
+    memory: &Memory<'_>,
+    list: &WasmList<(K, V)>,
+    mut map: M,
+    mut insert: impl FnMut(&mut M, K, V) -> bool,
+) -> Result<M> {
+    let strict = memory.strict_validation();
+    // Large maps are lifted in chunks to periodically check whether lifting
+    // should be interrupted, like `WasmList::load_range`.
+    let chunk_len = memory.elements_between_checks(<(K, V)>::SIZE32);
+    for start in (0..list.len).step_by(chunk_len) {
+        if start > 0 {
+            memory.check_interrupt()?;
+        }
+        for index in start..list.len.min(start.saturating_add(chunk_len)) {
+            let (k, v) = list.get_from_store(&memory.store, index).unwrap()?;
+            if insert(&mut map, k, v) && strict {
+                return Err(LiftError::DuplicateMapKey {
+                    index,
+                    len: list.len,
+                }
+                .into());
+            }
+        }
+    }
+    Ok(map)
+}
+
 // Macro to help generate `ComponentType` implementations for primitive types
 // such as integers, char, bool, etc.
 macro_rules! integers {
@@ -582,10 +1297,18 @@ macro_rules! integers {
             // types.
             const ALIGN32: u32 = mem::size_of::<$primitive>() as u32;
 
-            fn typecheck(ty: &InterfaceType, _types: &ComponentTypes) -> Result<()> {
+            fn describe() -> String {
+                desc(&InterfaceType::$ty).to_string()
+            }
+
+            fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
                 match ty {
                     InterfaceType::$ty => Ok(()),
-                    other => bail!("expected `{}` found `{}`", desc(&InterfaceType::$ty), desc(other))
+                    other => bail!(
+                        "expected `{}` found `{}`",
+                        desc(&InterfaceType::$ty),
+                        DisplayInterfaceType::new(other, types)
+                    ),
                 }
             }
         }
@@ -658,10 +1381,18 @@ macro_rules! floats {
             // respect the canonical abi, not host platforms.
             const ALIGN32: u32 = mem::size_of::<$float>() as u32;
 
-            fn typecheck(ty: &InterfaceType, _types: &ComponentTypes) -> Result<()> {
+            fn describe() -> String {
+                desc(&InterfaceType::$ty).to_string()
+            }
+
+            fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
                 match ty {
                     InterfaceType::$ty => Ok(()),
-                    other => bail!("expected `{}` found `{}`", desc(&InterfaceType::$ty), desc(other))
+                    other => bail!(
+                        "expected `{}` found `{}`",
+                        desc(&InterfaceType::$ty),
+                        DisplayInterfaceType::new(other, types)
+                    ),
                 }
             }
         }
@@ -711,10 +1442,17 @@ unsafe impl ComponentType for bool {
     const SIZE32: usize = 1;
     const ALIGN32: u32 = 1;
 
-    fn typecheck(ty: &InterfaceType, _types: &ComponentTypes) -> Result<()> {
+    fn describe() -> String {
+        "bool".to_string()
+    }
+
+    fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
         match ty {
             InterfaceType::Bool => Ok(()),
-            other => bail!("expected `bool` found `{}`", desc(other)),
+            other => bail!(
+                "expected `bool` found `{}`",
+                DisplayInterfaceType::new(other, types)
+            ),
         }
     }
 }
@@ -737,19 +1475,35 @@ unsafe impl Lower for bool {
     }
 }
 
+// Note that any nonzero value is interpreted as `true` here unless strict
+// validation is enabled, in which case only 0 and 1 are accepted.
 unsafe impl Lift for bool {
     #[inline]
-    fn lift(_store: &StoreOpaque, _options: &Options, src: &Self::Lower) -> Result<Self> {
+    fn lift(store: &StoreOpaque, _options: &Options, src: &Self::Lower) -> Result<Self> {
         match src.get_i32() {
             0 => Ok(false),
+            1 => Ok(true),
+            n if store.engine().config().component_model_strict_validation => {
+                Err(LiftError::InvalidBool {
+                    value: n as u32,
+                    source: "lifted from the stack",
+                }
+                .into())
+            }
             _ => Ok(true),
         }
     }
 
     #[inline]
-    fn load(_mem: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
+    fn load(memory: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
         match bytes[0] {
             0 => Ok(false),
+            1 => Ok(true),
+            n if memory.strict_validation() => Err(LiftError::InvalidBool {
+                value: n.into(),
+                source: "loaded from linear memory",
+            }
+            .into()),
             _ => Ok(true),
         }
     }
@@ -761,10 +1515,17 @@ unsafe impl ComponentType for char {
     const SIZE32: usize = 4;
     const ALIGN32: u32 = 4;
 
-    fn typecheck(ty: &InterfaceType, _types: &ComponentTypes) -> Result<()> {
+    fn describe() -> String {
+        "char".to_string()
+    }
+
+    fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
         match ty {
             InterfaceType::Char => Ok(()),
-            other => bail!("expected `char` found `{}`", desc(other)),
+            other => bail!(
+                "expected `char` found `{}`",
+                DisplayInterfaceType::new(other, types)
+            ),
         }
     }
 }
@@ -789,15 +1550,33 @@ unsafe impl Lower for char {
 
 unsafe impl Lift for char {
     #[inline]
-    fn lift(_store: &StoreOpaque, _options: &Options, src: &Self::Lower) -> Result<Self> {
-        Ok(char::try_from(src.get_u32())?)
+    fn lift(store: &StoreOpaque, _options: &Options, src: &Self::Lower) -> Result<Self> {
+        let lossy = store.engine().config().component_model_lossy_chars;
+        char_from_u32(src.get_u32(), "lifted from the stack", lossy)
     }
 
     #[inline]
-    fn load(_memory: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
+    fn load(memory: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
         debug_assert!((bytes.as_ptr() as usize) % Self::SIZE32 == 0);
         let bits = u32::from_le_bytes(bytes.try_into().unwrap());
-        Ok(char::try_from(bits)?)
+        char_from_u32(bits, "loaded from linear memory", memory.lossy_chars())
+    }
+}
+
+/// Converts `bits` to a `char`, producing an error which describes the
+/// invalid value and where it came from if it isn't a Unicode scalar value.
+///
+/// If `lossy` is set then invalid values are instead replaced with
+/// `char::REPLACEMENT_CHARACTER`.
+fn char_from_u32(bits: u32, source: &'static str, lossy: bool) -> Result<char> {
+    match char::from_u32(bits) {
+        Some(c) => Ok(c),
+        None if lossy => Ok(char::REPLACEMENT_CHARACTER),
+        None => Err(LiftError::InvalidChar {
+            value: bits,
+            source,
+        }
+        .into()),
     }
 }
 
@@ -809,10 +1588,17 @@ unsafe impl ComponentType for str {
     const SIZE32: usize = 8;
     const ALIGN32: u32 = 4;
 
-    fn typecheck(ty: &InterfaceType, _types: &ComponentTypes) -> Result<()> {
+    fn describe() -> String {
+        "string".to_string()
+    }
+
+    fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
         match ty {
             InterfaceType::String => Ok(()),
-            other => bail!("expected `string` found `{}`", desc(other)),
+            other => bail!(
+                "expected `string` found `{}`",
+                DisplayInterfaceType::new(other, types)
+            ),
         }
     }
 }
@@ -843,23 +1629,75 @@ unsafe impl Lower for str {
 }
 
 fn lower_string<T>(mem: &mut MemoryMut<'_, T>, string: &str) -> Result<(usize, usize)> {
+    // Empty strings don't occupy any linear memory, so avoid touching memory
+    // at all to support components which have neither `memory` nor `realloc`
+    // options.
+    if string.is_empty() {
+        return Ok((mem.realloc(0, 0, 1, 0)?, 0));
+    }
     match mem.string_encoding() {
         StringEncoding::Utf8 => {
-            let ptr = mem.realloc(0, 0, 1, string.len())?;
-            mem.as_slice_mut()[ptr..][..string.len()].copy_from_slice(string.as_bytes());
+            let size = lowered_size(|| "string".to_string(), string.len(), 1)?;
+            let ptr = mem.realloc(0, 0, 1, size)?;
+            // Large strings are copied in chunks to periodically check
+            // whether lowering should be interrupted.
+            let chunk_len = mem.elements_between_checks(1);
+            mem.free_on_error(ptr, size, 1, |mem| {
+                for (i, chunk) in string.as_bytes().chunks(chunk_len).enumerate() {
+                    if i > 0 {
+                        mem.check_interrupt()?;
+                    }
+                    mem.as_slice_mut()[ptr + i * chunk_len..][..chunk.len()].copy_from_slice(chunk);
+                }
+                Ok(())
+            })?;
             Ok((ptr, string.len()))
         }
         StringEncoding::Utf16 => {
-            let size = string.len() * 2;
+            let size = lowered_size(|| "string".to_string(), string.len(), 2)?;
             let mut ptr = mem.realloc(0, 0, 2, size)?;
-            let bytes = &mut mem.as_slice_mut()[ptr..][..size];
-            let mut copied = 0;
-            for (u, bytes) in string.encode_utf16().zip(bytes.chunks_mut(2)) {
-                let u_bytes = u.to_le_bytes();
-                bytes[0] = u_bytes[0];
-                bytes[1] = u_bytes[1];
-                copied += 1;
-            }
+            let chunk_len = mem.elements_between_checks(2);
+            let copied = mem.free_on_error(ptr, size, 2, |mem| {
+                // Each byte of an ascii prefix of the string is one code
+                // unit, so widen those directly. The general loop below then
+                // resumes from the first non-ascii character, if any.
+                let ascii = string
+                    .bytes()
+                    .position(|b| !b.is_ascii())
+                    .unwrap_or(string.len());
+                for start in (0..ascii).step_by(chunk_len) {
+                    if start > 0 {
+                        mem.check_interrupt()?;
+                    }
+                    let src = &string.as_bytes()[start..ascii.min(start.saturating_add(chunk_len))];
+                    let dst = &mut mem.as_slice_mut()[ptr + start * 2..][..src.len() * 2];
+                    for (b, dst) in src.iter().zip(dst.chunks_exact_mut(2)) {
+                        dst[0] = *b;
+                        dst[1] = 0;
+                    }
+                }
+
+                let mut copied = ascii;
+                let mut units = string[ascii..].encode_utf16().peekable();
+                while units.peek().is_some() {
+                    // Checks are made at the same multiples of `chunk_len`
+                    // code units as for the ascii prefix.
+                    if copied > 0 && copied % chunk_len == 0 {
+                        mem.check_interrupt()?;
+                    }
+                    let n = chunk_len - copied % chunk_len;
+                    let dst = &mut mem.as_slice_mut()[ptr + copied * 2..ptr + size];
+                    for (u, bytes) in units.by_ref().take(n).zip(dst.chunks_mut(2)) {
+                        let u_bytes = u.to_le_bytes();
+                        bytes[0] = u_bytes[0];
+                        bytes[1] = u_bytes[1];
+                        copied += 1;
+                    }
+                }
+                Ok(copied)
+            })?;
+            // Note that if shrinking fails the original allocation isn't freed
+            // since it's unknown whether the guest still owns it.
             if (copied * 2) < size {
                 ptr = mem.realloc(ptr, size, 2, copied * 2)?;
             }
@@ -871,6 +1709,28 @@ fn lower_string<T>(mem: &mut MemoryMut<'_, T>, string: &str) -> Result<(usize, u
     }
 }
 
+/// Returns the size, in bytes, of `len` elements which are each `size` bytes
+/// large when lowered as the type described by `ty`.
+///
+/// Both the number of elements and the size in bytes must fit in 32 bits,
+/// otherwise they'd be truncated when passed to `realloc` or written as the
+/// length of a string or list. Note that this is the case even for memories
+/// marked with [`Options::with_memory64`] since lowering only supports 32-bit
+/// memories. This is checked before anything is allocated and an error is
+/// returned if either doesn't fit.
+pub(crate) fn lowered_size(ty: impl FnOnce() -> String, len: usize, size: usize) -> Result<usize> {
+    let fits = |n: usize| u32::try_from(n).is_ok();
+    match len.checked_mul(size) {
+        Some(bytes) if fits(len) && fits(bytes) => Ok(bytes),
+        _ => Err(LowerError::TooLarge {
+            ty: ty(),
+            len,
+            bits: 32,
+        }
+        .into()),
+    }
+}
+
 /// Representation of a string located in linear memory in a WebAssembly
 /// instance.
 ///
@@ -889,17 +1749,67 @@ pub struct WasmStr {
     options: Options,
 }
 
-impl WasmStr {
-    fn new(ptr: usize, len: usize, memory: &Memory<'_>) -> Result<WasmStr> {
-        let byte_len = match memory.string_encoding() {
-            StringEncoding::Utf8 => Some(len),
-            StringEncoding::Utf16 => len.checked_mul(2),
-            StringEncoding::CompactUtf16 => unimplemented!(),
-        };
-        match byte_len.and_then(|len| ptr.checked_add(len)) {
-            Some(n) if n <= memory.as_slice().len() => {}
-            _ => bail!("string pointer/length out of bounds of memory"),
+/// Returns the byte range of `len` elements, each `size` bytes large, starting
+/// at `ptr`, or an error if that range doesn't fit in a memory of `memory_len`
+/// bytes.
+///
+/// All arithmetic here is checked since on 32-bit hosts guest-provided pointers
+/// and lengths can overflow `usize`. Once this has succeeded, offsets within
+/// the returned range can be computed with unchecked arithmetic.
+pub(super) fn bounds_check(
+    what: &'static str,
+    memory_len: usize,
+    ptr: usize,
+    len: usize,
+    size: usize,
+) -> Result<Range<usize>> {
+    checked_range(memory_len, ptr, len, size).ok_or_else(|| {
+        LiftError::OutOfBounds {
+            what,
+            ptr,
+            len,
+            memory_size: memory_len,
         }
+        .into()
+    })
+}
+
+/// Same as `bounds_check`, but returns `None` instead of an error.
+pub(super) fn checked_range(
+    memory_len: usize,
+    ptr: usize,
+    len: usize,
+    size: usize,
+) -> Option<Range<usize>> {
+    match len
+        .checked_mul(size)
+        .and_then(|bytes| ptr.checked_add(bytes))
+    {
+        Some(end) if end <= memory_len => Some(ptr..end),
+        _ => None,
+    }
+}
+
+/// Verifies that a lazily-lifted value using `options` belongs to `store`.
+///
+/// Using a `WasmStr` or `WasmList` with the wrong store is a recoverable
+/// embedder error, so unlike most other store mismatches this doesn't panic.
+fn check_store(options: &Options, store: &StoreOpaque) -> Result<()> {
+    if options.store_id() != store.id() {
+        bail!("value belongs to a different store");
+    }
+    Ok(())
+}
+
+impl WasmStr {
+    fn new(ptr: usize, len: usize, memory: &Memory<'_>) -> Result<WasmStr> {
+        let code_unit_size = match memory.string_encoding() {
+            StringEncoding::Utf8 => 1,
+            StringEncoding::Utf16 => 2,
+            StringEncoding::CompactUtf16 => unimplemented!(),
+        };
+        memory.check_string_limit(len.saturating_mul(code_unit_size))?;
+        bounds_check("string", memory.as_slice().len(), ptr, len, code_unit_size)?;
         Ok(WasmStr {
             ptr,
             len,
@@ -918,11 +1828,7 @@ impl WasmStr {
     /// # Errors
     ///
     /// Returns an error if the string wasn't encoded correctly (e.g. invalid
-    /// utf-8).
-    ///
-    /// # Panics
-    ///
-    /// Panics if this string is not owned by `store`.
+    /// utf-8) or if this string is not owned by `store`.
     //
     // TODO: should add accessors for specifically utf-8 and utf-16 that perhaps
     // in an opt-in basis don't do validation. Additionally there should be some
@@ -933,33 +1839,457 @@ impl WasmStr {
     }
 
     fn to_str_from_store<'a>(&self, store: &'a StoreOpaque) -> Result<Cow<'a, str>> {
+        check_store(&self.options, store)?;
+        match self.options.string_encoding() {
+            StringEncoding::Utf8 => Ok(self.decode_utf8(store)?.into()),
+            StringEncoding::Utf16 => self.decode_utf16(store, false),
+            StringEncoding::CompactUtf16 => unimplemented!(),
+        }
+    }
+
+    /// Same as [`WasmStr::to_str`], except that invalid sequences in the
+    /// string are replaced with U+FFFD (the replacement character) instead of
+    /// returning an error.
+    ///
+    /// For utf-8 strings this has the same semantics as
+    /// [`String::from_utf8_lossy`], and for utf-16 strings each unpaired
+    /// surrogate is replaced, as with [`String::from_utf16_lossy`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this string is not owned by `store` or if it's no
+    /// longer in bounds of linear memory.
+    pub fn to_string_lossy<'a, T: 'a>(
+        &self,
+        store: impl Into<StoreContext<'a, T>>,
+    ) -> Result<Cow<'a, str>> {
+        let store = store.into().0;
+        check_store(&self.options, store)?;
+        match self.options.string_encoding() {
+            StringEncoding::Utf8 => Ok(String::from_utf8_lossy(self.bytes(store)?)),
+            StringEncoding::Utf16 => self.decode_utf16(store, true),
+            StringEncoding::CompactUtf16 => unimplemented!(),
+        }
+    }
+
+    /// Returns the encoding of this string in linear memory.
+    ///
+    /// This can be used to determine up front whether [`WasmStr::as_str`]
+    /// will succeed.
+    pub fn encoding(&self) -> StringEncoding {
+        self.options.string_encoding()
+    }
+
+    /// Returns the underlying string that this cursor points to, borrowed
+    /// directly from linear memory.
+    ///
+    /// This is only possible for strings which are encoded as utf-8 in linear
+    /// memory, and unlike [`WasmStr::to_str`] never copies the string. Strings
+    /// in other encodings must be decoded with [`WasmStr::to_str`] instead.
+    ///
+    /// The `store` provided must be the store where this string lives to
+    /// access the correct memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't encoded as utf-8, if it isn't
+    /// valid utf-8, or if this string is not owned by `store`.
+    pub fn as_str<'a, T: 'a>(&self, store: impl Into<StoreContext<'a, T>>) -> Result<&'a str> {
+        let store = store.into().0;
+        check_store(&self.options, store)?;
         match self.options.string_encoding() {
             StringEncoding::Utf8 => self.decode_utf8(store),
-            StringEncoding::Utf16 => self.decode_utf16(store),
+            other => bail!(
+                "cannot borrow a string encoded as {:?} as `&str`, \
+                 use `WasmStr::to_str` instead",
+                other
+            ),
+        }
+    }
+
+    /// Copies this string, encoded as utf-8, into `dst` without allocating.
+    ///
+    /// Strings in other encodings are transcoded to utf-8 as they're copied.
+    /// If the whole string doesn't fit in `dst` then as much of it as fits is
+    /// copied, ending on a character boundary, and [`WasmStrCopy::Partial`] is
+    /// returned. The number of bytes needed to copy the whole string can be
+    /// determined with [`WasmStr::required_capacity`].
+    ///
+    /// Bytes of `dst` after those which were written are left unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string wasn't encoded correctly (e.g. invalid
+    /// utf-8) or if this string is not owned by `store`. The whole string is
+    /// validated even if only part of it is copied, and the contents of `dst`
+    /// are unspecified if an error is returned.
+    pub fn copy_to<'a, T: 'a>(
+        &self,
+        store: impl Into<StoreContext<'a, T>>,
+        dst: &mut [u8],
+    ) -> Result<WasmStrCopy> {
+        let store = store.into().0;
+        check_store(&self.options, store)?;
+        match self.options.string_encoding() {
+            StringEncoding::Utf8 => {
+                let src = self.decode_utf8(store)?;
+                if src.len() <= dst.len() {
+                    dst[..src.len()].copy_from_slice(src.as_bytes());
+                    return Ok(WasmStrCopy::Complete(src.len()));
+                }
+                // Note that 0 is always a character boundary so this always
+                // finds one.
+                let len = (0..=dst.len())
+                    .rev()
+                    .find(|i| src.is_char_boundary(*i))
+                    .unwrap();
+                dst[..len].copy_from_slice(&src.as_bytes()[..len]);
+                Ok(WasmStrCopy::Partial(len))
+            }
+            StringEncoding::Utf16 => {
+                let memory = Memory::new(store, &self.options);
+                let chunk_len = memory.elements_between_checks(2);
+                let check = || memory.check_interrupt();
+                let mut len = 0;
+                let mut complete = true;
+                for_each_utf16_char(self.bytes(store)?, false, chunk_len, check, |c, _| {
+                    if !complete {
+                        return;
+                    }
+                    match dst.get_mut(len..len + c.len_utf8()) {
+                        Some(dst) => {
+                            c.encode_utf8(dst);
+                            len += c.len_utf8();
+                        }
+                        None => complete = false,
+                    }
+                })?;
+                Ok(if complete {
+                    WasmStrCopy::Complete(len)
+                } else {
+                    WasmStrCopy::Partial(len)
+                })
+            }
             StringEncoding::CompactUtf16 => unimplemented!(),
         }
     }
 
-    fn decode_utf8<'a>(&self, store: &'a StoreOpaque) -> Result<Cow<'a, str>> {
-        let memory = self.options.memory(store);
-        // Note that bounds-checking already happen in construction of `WasmStr`
-        // so this is never expected to panic. This could theoretically be
-        // unchecked indexing if we're feeling wild enough.
-        Ok(str::from_utf8(&memory[self.ptr..][..self.len])?.into())
+    /// Returns the number of bytes this string takes when encoded as utf-8,
+    /// which is the size of the buffer needed by [`WasmStr::copy_to`] to copy
+    /// the whole string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string wasn't encoded correctly (e.g. invalid
+    /// utf-8) or if this string is not owned by `store`.
+    pub fn required_capacity<'a, T: 'a>(
+        &self,
+        store: impl Into<StoreContext<'a, T>>,
+    ) -> Result<usize> {
+        let store = store.into().0;
+        check_store(&self.options, store)?;
+        match self.options.string_encoding() {
+            StringEncoding::Utf8 => Ok(self.decode_utf8(store)?.len()),
+            StringEncoding::Utf16 => {
+                let memory = Memory::new(store, &self.options);
+                let chunk_len = memory.elements_between_checks(2);
+                let check = || memory.check_interrupt();
+                let mut len = 0;
+                for_each_utf16_char(self.bytes(store)?, false, chunk_len, check, |c, _| {
+                    len += c.len_utf8()
+                })?;
+                Ok(len)
+            }
+            StringEncoding::CompactUtf16 => unimplemented!(),
+        }
     }
 
-    fn decode_utf16<'a>(&self, store: &'a StoreOpaque) -> Result<Cow<'a, str>> {
+    /// Returns the bytes of this string in linear memory.
+    ///
+    /// The string was bounds-checked when it was lifted, but that's checked
+    /// again here rather than assuming that linear memory is still at least
+    /// as large, since this view may be used long after it was created and
+    /// arbitrary code may have run in the meantime.
+    fn bytes<'a>(&self, store: &'a StoreOpaque) -> Result<&'a [u8]> {
+        let code_unit_size = match self.options.string_encoding() {
+            StringEncoding::Utf8 => 1,
+            StringEncoding::Utf16 => 2,
+            StringEncoding::CompactUtf16 => unimplemented!(),
+        };
         let memory = self.options.memory(store);
-        // See notes in `decode_utf8` for why this is panicking indexing.
-        let memory = &memory[self.ptr..][..self.len * 2];
-        Ok(std::char::decode_utf16(
-            memory
-                .chunks(2)
-                .map(|chunk| u16::from_le_bytes(chunk.try_into().unwrap())),
-        )
-        .collect::<Result<String, _>>()?
-        .into())
+        let range = bounds_check("string", memory.len(), self.ptr, self.len, code_unit_size)?;
+        Ok(&memory[range])
     }
+
+    /// Validates this utf-8 string.
+    ///
+    /// Large strings periodically check whether lifting should be
+    /// interrupted while they're validated.
+    fn decode_utf8<'a>(&self, store: &'a StoreOpaque) -> Result<&'a str> {
+        let memory = Memory::new(store, &self.options);
+        let chunk_len = memory.elements_between_checks(1);
+        let bytes = self.bytes(store)?;
+        let mut start = 0;
+        while start < bytes.len() {
+            if start > 0 {
+                memory.check_interrupt()?;
+            }
+            // A character cut off at the end of a chunk is validated again as
+            // part of the next one, and chunks extend 3 bytes past `chunk_len`
+            // so that each one still makes progress in that case.
+            let end = bytes
+                .len()
+                .min(start.saturating_add(chunk_len).saturating_add(3));
+            match str::from_utf8(&bytes[start..end]) {
+                Ok(_) => start = end,
+                Err(e) if e.error_len().is_none() && end < bytes.len() => start += e.valid_up_to(),
+                // Validate the whole string again for an error with the
+                // position of the invalid sequence within it.
+                Err(_) => {
+                    return str::from_utf8(bytes).map_err(|e| LiftError::InvalidUtf8(e).into())
+                }
+            }
+        }
+        // Safety: all of `bytes` was validated above.
+        Ok(unsafe { str::from_utf8_unchecked(bytes) })
+    }
+
+    /// Decodes this utf-16 string, either returning an error for the first
+    /// unpaired surrogate or, if `lossy` is set, replacing each unpaired
+    /// surrogate with U+FFFD and continuing.
+    ///
+    /// Large strings periodically check whether lifting should be
+    /// interrupted while they're transcoded.
+    fn decode_utf16<'a>(&self, store: &'a StoreOpaque, lossy: bool) -> Result<Cow<'a, str>> {
+        let memory = Memory::new(store, &self.options);
+        let chunk_len = memory.elements_between_checks(2);
+        let bytes = self.bytes(store)?;
+        let check = || memory.check_interrupt();
+        Ok(utf16_to_utf8_interruptible(bytes, lossy, chunk_len, check)?.into())
+    }
+
+    /// Returns an adapter which formats a preview of the contents of this
+    /// string with `Display` and `Debug`.
+    ///
+    /// The preview is quoted and escaped like the `Debug` output of a `str`,
+    /// invalid sequences are replaced with U+FFFD, and it's truncated to
+    /// [`WasmStrDisplay::DEFAULT_MAX_CHARS`] characters followed by `…` unless
+    /// otherwise configured with [`WasmStrDisplay::max_chars`]. If the string
+    /// can't be read from `store` then the error is shown instead as
+    /// `<invalid: ...>`.
+    pub fn display<'a, T: 'a>(
+        &'a self,
+        store: impl Into<StoreContext<'a, T>>,
+    ) -> WasmStrDisplay<'a> {
+        WasmStrDisplay {
+            string: self,
+            store: store.into().0,
+            max_chars: WasmStrDisplay::DEFAULT_MAX_CHARS,
+        }
+    }
+
+    /// Decodes at least the first `chars + 1` characters of this string, if
+    /// it has that many, replacing invalid sequences with U+FFFD.
+    fn decode_prefix<'a>(&self, store: &'a StoreOpaque, chars: usize) -> Result<Cow<'a, str>> {
+        check_store(&self.options, store)?;
+        let bytes = self.bytes(store)?;
+        // A character is at most 4 bytes of utf-8 or 2 code units of utf-16,
+        // so these prefixes hold at least `chars + 1` complete characters and
+        // any character cut off at the end comes after them.
+        let units = chars.saturating_add(1);
+        match self.options.string_encoding() {
+            StringEncoding::Utf8 => {
+                let len = bytes.len().min(units.saturating_mul(4));
+                Ok(String::from_utf8_lossy(&bytes[..len]))
+            }
+            StringEncoding::Utf16 => {
+                let len = bytes.len().min(units.saturating_mul(4));
+                Ok(utf16_to_utf8(&bytes[..len], true)?.into())
+            }
+            StringEncoding::CompactUtf16 => unimplemented!(),
+        }
+    }
+}
+
+/// Shows where a string is and how it's encoded without its contents, which
+/// can only be read with a store. See [`WasmStr::display`] for a preview of
+/// the contents.
+impl fmt::Debug for WasmStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WasmStr")
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .field("encoding", &self.options.string_encoding())
+            .finish()
+    }
+}
+
+/// A preview of the contents of a [`WasmStr`], created with
+/// [`WasmStr::display`].
+pub struct WasmStrDisplay<'a> {
+    string: &'a WasmStr,
+    store: &'a StoreOpaque,
+    max_chars: usize,
+}
+
+impl WasmStrDisplay<'_> {
+    /// The number of characters shown by default.
+    pub const DEFAULT_MAX_CHARS: usize = 64;
+
+    /// Sets the number of characters shown before the rest of the string is
+    /// elided as `…`.
+    pub fn max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+}
+
+impl fmt::Display for WasmStrDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.string.decode_prefix(self.store, self.max_chars) {
+            Ok(prefix) => prefix,
+            Err(e) => return write!(f, "<invalid: {}>", e),
+        };
+        let mut chars = prefix.chars();
+        f.write_str("\"")?;
+        for c in chars.by_ref().take(self.max_chars) {
+            write!(f, "{}", c.escape_debug())?;
+        }
+        f.write_str("\"")?;
+        if chars.next().is_some() {
+            f.write_str("…")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for WasmStrDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// The number of bytes written by [`WasmStr::copy_to`], and whether that was
+/// the whole string.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WasmStrCopy {
+    /// The whole string was copied into the destination, which took this many
+    /// bytes.
+    Complete(usize),
+    /// The destination was too small for the whole string, so only this many
+    /// bytes were written. This is always a character boundary.
+    Partial(usize),
+}
+
+impl WasmStrCopy {
+    /// Returns the number of bytes written.
+    pub fn written(&self) -> usize {
+        match *self {
+            WasmStrCopy::Complete(len) | WasmStrCopy::Partial(len) => len,
+        }
+    }
+
+    /// Returns whether the whole string was copied.
+    pub fn is_complete(&self) -> bool {
+        matches!(self, WasmStrCopy::Complete(_))
+    }
+}
+
+/// Transcodes the little-endian utf-16 code units in `src` to utf-8.
+///
+/// Unpaired surrogates are reported as an error, or replaced with U+FFFD if
+/// `lossy` is set.
+///
+/// The output is initially sized for all-ascii input and is resized to the
+/// worst case for the rest of the input when the first non-ascii code unit is
+/// encountered.
+fn utf16_to_utf8(src: &[u8], lossy: bool) -> Result<String, LiftError> {
+    utf16_to_utf8_interruptible(src, lossy, usize::MAX, || Ok(()))
+}
+
+/// Same as `utf16_to_utf8`, except that `check` is invoked after every
+/// `chunk_len` code units and transcoding is aborted if it fails.
+fn utf16_to_utf8_interruptible<E: From<LiftError>>(
+    src: &[u8],
+    lossy: bool,
+    chunk_len: usize,
+    check: impl FnMut() -> Result<(), E>,
+) -> Result<String, E> {
+    let mut dst = String::with_capacity(src.len() / 2);
+    for_each_utf16_char(src, lossy, chunk_len, check, |c, remaining| {
+        // Each remaining code unit takes at most 3 bytes, and a surrogate pair
+        // takes 4 bytes for two code units, so this reserves enough for the
+        // rest of the string at once.
+        if !c.is_ascii() && dst.capacity() - dst.len() < 4 {
+            dst.reserve(4 + remaining * 3);
+        }
+        dst.push(c);
+    })?;
+    Ok(dst)
+}
+
+/// Decodes the little-endian utf-16 code units in `src`, passing each
+/// character to `push` along with the number of code units after it.
+///
+/// Unpaired surrogates are reported as an error, or replaced with U+FFFD if
+/// `lossy` is set. `check` is invoked after every `chunk_len` code units and
+/// decoding is aborted if it fails.
+///
+/// This is a single pass over `src`, which is not necessarily aligned for
+/// `u16`, with a fast path for runs of ascii.
+fn for_each_utf16_char<E: From<LiftError>>(
+    src: &[u8],
+    lossy: bool,
+    chunk_len: usize,
+    mut check: impl FnMut() -> Result<(), E>,
+    mut push: impl FnMut(char, usize),
+) -> Result<(), E> {
+    debug_assert!(src.len() % 2 == 0);
+    let len = src.len() / 2;
+    let unit = |i: usize| u16::from_le_bytes([src[2 * i], src[2 * i + 1]]);
+    let mut i = 0;
+    let mut next_check = chunk_len;
+    while i < len {
+        if i >= next_check {
+            check()?;
+            next_check = i.saturating_add(chunk_len);
+        }
+        // Check four code units at a time for ascii, which is when the upper
+        // nine bits of each are zero.
+        if i + 4 <= len {
+            let word = u64::from_le_bytes(src[2 * i..][..8].try_into().unwrap());
+            if word & 0xff80_ff80_ff80_ff80 == 0 {
+                for j in 0..4 {
+                    push(char::from(src[2 * (i + j)]), len - (i + j + 1));
+                }
+                i += 4;
+                continue;
+            }
+        }
+
+        let u = unit(i);
+        i += 1;
+        let c = match u {
+            0xd800..=0xdbff if i < len && (0xdc00..=0xdfff).contains(&unit(i)) => {
+                let low = unit(i);
+                i += 1;
+                0x10000 + ((u32::from(u) - 0xd800) << 10) + (u32::from(low) - 0xdc00)
+            }
+            0xd800..=0xdfff if lossy => u32::from(char::REPLACEMENT_CHARACTER),
+            0xd800..=0xdfff => {
+                return Err(LiftError::InvalidUtf16 {
+                    unpaired_surrogate: u,
+                    position: i - 1,
+                    len,
+                }
+                .into())
+            }
+            _ => u32::from(u),
+        };
+        // This can't fail since surrogates were handled above.
+        push(char::from_u32(c).unwrap(), len - i);
+    }
+    Ok(())
 }
 
 // Note that this is similar to `ComponentType for str` except it can only be
@@ -970,10 +2300,17 @@ unsafe impl ComponentType for WasmStr {
     const SIZE32: usize = <str as ComponentType>::SIZE32;
     const ALIGN32: u32 = <str as ComponentType>::ALIGN32;
 
-    fn typecheck(ty: &InterfaceType, _types: &ComponentTypes) -> Result<()> {
+    fn describe() -> String {
+        "string".to_string()
+    }
+
+    fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
         match ty {
             InterfaceType::String => Ok(()),
-            other => bail!("expected `string` found `{}`", desc(other)),
+            other => bail!(
+                "expected `string` found `{}`",
+                DisplayInterfaceType::new(other, types)
+            ),
         }
     }
 }
@@ -991,7 +2328,7 @@ unsafe impl Lift for WasmStr {
         debug_assert!((bytes.as_ptr() as usize) % (Self::ALIGN32 as usize) == 0);
         // FIXME: needs memory64 treatment
         let ptr = u32::from_le_bytes(bytes[..4].try_into().unwrap());
-        let len = u32::from_le_bytes(bytes[4..].try_into().unwrap());
+        let len = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
         let (ptr, len) = (usize::try_from(ptr)?, usize::try_from(len)?);
         WasmStr::new(ptr, len, memory)
     }
@@ -1006,10 +2343,19 @@ where
     const SIZE32: usize = 8;
     const ALIGN32: u32 = 4;
 
+    fn describe() -> String {
+        format!("list<{}>", T::describe())
+    }
+
     fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
         match ty {
-            InterfaceType::List(t) => T::typecheck(&types[*t], types),
-            other => bail!("expected `list` found `{}`", desc(other)),
+            InterfaceType::List(t) => with_type_path(T::typecheck(&types[*t], types), || {
+                " (list element)".to_string()
+            }),
+            other => bail!(
+                "expected `list` found `{}`",
+                DisplayInterfaceType::new(other, types)
+            ),
         }
     }
 }
@@ -1061,19 +2407,235 @@ where
     T: Lower,
 {
     let elem_size = T::SIZE32;
-    let size = list
-        .len()
-        .checked_mul(elem_size)
-        .ok_or_else(|| anyhow::anyhow!("size overflow copying a list"))?;
+    let size = lowered_size(<[T]>::describe, list.len(), elem_size)?;
     let ptr = mem.realloc(0, 0, T::ALIGN32, size)?;
-    let mut cur = ptr;
-    for item in list {
-        item.store(mem, cur)?;
-        cur += elem_size;
-    }
+    let chunk_len = mem.elements_between_checks(elem_size);
+    mem.free_on_error(ptr, size, T::ALIGN32, |mem| {
+        let mut cur = ptr;
+        for (i, chunk) in list.chunks(chunk_len).enumerate() {
+            if i > 0 {
+                mem.check_interrupt()?;
+            }
+            for item in chunk {
+                item.store(mem, cur)?;
+                cur += elem_size;
+            }
+        }
+        Ok(())
+    })?;
     Ok((ptr, list.len()))
 }
 
+/// An adapter to lower the items of an iterator as a component model `list`.
+///
+/// Normally passing a `list` to a component requires a `&[T]` or `Vec<T>`
+/// which means that computed sequences must first be collected into a
+/// temporary allocation on the host before being copied into linear memory.
+/// This adapter instead reserves space in linear memory for `len()` elements
+/// and then stores each element yielded by the iterator directly into that
+/// space.
+///
+/// Note that lowering only has access to `&self`, so a clone of the iterator
+/// is consumed each time this value is lowered, hence the `Clone` requirement.
+/// The iterator must also report an exact length via [`ExactSizeIterator`] as
+/// that's used to size the allocation. If the iterator yields a different
+/// number of items than it reported then lowering will return an error.
+///
+/// # Examples
+///
+/// ```
+/// # use wasmtime::component::{Func, LowerList};
+/// # use wasmtime::Store;
+/// # fn foo(func: &Func, mut store: Store<()>) -> anyhow::Result<()> {
+/// let typed = func.typed::<(LowerList<std::ops::Range<u32>>,), (), _>(&store)?;
+/// typed.call(&mut store, (LowerList(0..1000),))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct LowerList<I>(pub I);
+
+unsafe impl<I> ComponentType for LowerList<I>
+where
+    I: ExactSizeIterator,
+    I::Item: ComponentType,
+{
+    type Lower = <[I::Item] as ComponentType>::Lower;
+
+    const SIZE32: usize = <[I::Item] as ComponentType>::SIZE32;
+    const ALIGN32: u32 = <[I::Item] as ComponentType>::ALIGN32;
+
+    fn describe() -> String {
+        <[I::Item] as ComponentType>::describe()
+    }
+
+    fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
+        <[I::Item] as ComponentType>::typecheck(ty, types)
+    }
+}
+
+unsafe impl<I> Lower for LowerList<I>
+where
+    I: ExactSizeIterator + Clone,
+    I::Item: Lower,
+{
+    fn lower<U>(
+        &self,
+        store: &mut StoreContextMut<U>,
+        options: &Options,
+        dst: &mut MaybeUninit<[ValRaw; 2]>,
+    ) -> Result<()> {
+        let (ptr, len) = lower_list_iter(
+            &mut MemoryMut::new(store.as_context_mut(), options),
+            self.0.clone(),
+        )?;
+        // See "WRITEPTR64" above for why this is always storing a 64-bit
+        // integer.
+        map_maybe_uninit!(dst[0]).write(ValRaw::i64(ptr as i64));
+        map_maybe_uninit!(dst[1]).write(ValRaw::i64(len as i64));
+        Ok(())
+    }
+
+    fn store<U>(&self, mem: &mut MemoryMut<'_, U>, offset: usize) -> Result<()> {
+        debug_assert!(offset % (Self::ALIGN32 as usize) == 0);
+        let (ptr, len) = lower_list_iter(mem, self.0.clone())?;
+        *mem.get(offset + 0) = (ptr as i32).to_le_bytes();
+        *mem.get(offset + 4) = (len as i32).to_le_bytes();
+        Ok(())
+    }
+}
+
+/// Same as `lower_list` except that elements are pulled from an iterator
+/// rather than a slice.
+fn lower_list_iter<I, U>(mem: &mut MemoryMut<'_, U>, iter: I) -> Result<(usize, usize)>
+where
+    I: ExactSizeIterator,
+    I::Item: Lower,
+{
+    let elem_size = <I::Item as ComponentType>::SIZE32;
+    let len = iter.len();
+    let size = lowered_size(<[I::Item]>::describe, len, elem_size)?;
+    let align = <I::Item as ComponentType>::ALIGN32;
+    let ptr = mem.realloc(0, 0, align, size)?;
+    let chunk_len = mem.elements_between_checks(elem_size);
+    mem.free_on_error(ptr, size, align, |mem| {
+        let mut cur = ptr;
+        let mut count = 0;
+        let mut until_check = chunk_len;
+        for item in iter {
+            if count == len {
+                bail!("iterator yielded more items than its reported length of {len}");
+            }
+            if until_check == 0 {
+                mem.check_interrupt()?;
+                until_check = chunk_len;
+            }
+            until_check -= 1;
+            item.store(mem, cur)?;
+            cur += elem_size;
+            count += 1;
+        }
+        if count != len {
+            bail!("iterator yielded {count} items but reported a length of {len}");
+        }
+        Ok(())
+    })?;
+    Ok((ptr, len))
+}
+
+/// Loads `len` values of type `T` from `bytes` one at a time, appending them
+/// to `dst`.
+///
+/// This is the default implementation of `Lift::load_list`.
+#[doc(hidden)]
+pub fn load_list<T: Lift>(
+    memory: &Memory<'_>,
+    bytes: &[u8],
+    len: usize,
+    dst: &mut Vec<T>,
+) -> Result<()> {
+    debug_assert_eq!(bytes.len(), len * T::SIZE32);
+    dst.reserve(len);
+    if T::SIZE32 == 0 {
+        for _ in 0..len {
+            dst.push(T::load(memory, bytes)?);
+        }
+    } else {
+        for bytes in bytes.chunks_exact(T::SIZE32) {
+            dst.push(T::load(memory, bytes)?);
+        }
+    }
+    Ok(())
+}
+
+/// Types which may be fields of `#[component(pod)]` records, namely the
+/// integers and floats.
+///
+/// Every bit pattern of the size of these types is a valid value, and their
+/// size and alignment in the canonical ABI are the same as in Rust, except
+/// that the host may align 64-bit values less strictly.
+///
+/// # Safety
+///
+/// This must only be implemented for types with the above properties since
+/// `load_pod_list` copies arbitrary bytes from linear memory into them.
+#[doc(hidden)]
+pub unsafe trait PodField: Lift {}
+
+macro_rules! pod_fields {
+    ($($ty:ty)*) => ($(
+        unsafe impl PodField for $ty {}
+    )*)
+}
+
+pod_fields! { u8 i8 u16 i16 u32 i32 u64 i64 f32 f64 }
+
+/// Used by `#[component(pod)]` records to require at compile time that each
+/// of their fields is a [`PodField`], whatever the name of its type.
+#[doc(hidden)]
+#[inline]
+pub fn assert_pod_field<T: PodField>() {}
+
+/// Appends the `len` values of type `T` stored in `bytes` to `dst` with a
+/// single copy, returning the newly appended values.
+///
+/// This is used by `#[component(pod)]` records, whose fields are all integers
+/// or floats without any padding between them, so their representation in
+/// linear memory is the same as their `#[repr(C)]` representation on
+/// little-endian hosts. Note that floats still need to have their NaNs
+/// canonicalized by the caller.
+///
+/// # Panics
+///
+/// Panics if the size of `T` isn't `T::SIZE32` or if `bytes` isn't exactly
+/// `len * T::SIZE32` bytes large.
+///
+/// # Safety
+///
+/// `T` must be a `#[repr(C)]` struct whose fields all implement [`PodField`],
+/// laid out exactly as in the canonical ABI, and the host must be
+/// little-endian.
+#[doc(hidden)]
+pub unsafe fn load_pod_list<'a, T: Lift>(
+    bytes: &[u8],
+    len: usize,
+    dst: &'a mut Vec<T>,
+) -> &'a mut [T] {
+    assert_eq!(mem::size_of::<T>(), T::SIZE32);
+    assert_eq!(bytes.len(), len * T::SIZE32);
+    let start = dst.len();
+    dst.reserve(len);
+    // Note that `bytes` may not be aligned for `T` so this copies bytes
+    // rather than values.
+    std::ptr::copy_nonoverlapping(
+        bytes.as_ptr(),
+        dst.as_mut_ptr().add(start).cast::<u8>(),
+        bytes.len(),
+    );
+    dst.set_len(start + len);
+    &mut dst[start..]
+}
+
 /// Representation of a list of values that are owned by a WebAssembly instance.
 ///
 /// This type is used whenever a `(list T)` is returned from a [`TypedFunc`],
@@ -1083,6 +2645,13 @@ where
 /// Note that this type represents only a valid range of bytes for the list
 /// itself, it does not represent validity of the elements themselves and that's
 /// performed when they're iterated.
+///
+/// Lists of strings and of lists can be lifted lazily as well by nesting views,
+/// for example a `(list string)` as `WasmList<WasmStr>` or a
+/// `(list (list u32))` as `WasmList<WasmList<u32>>`. Loading an element of
+/// such a list only reads its pointer and length and validates that they're in
+/// bounds, so hosts which only need some of the elements don't pay for
+/// decoding the others.
 pub struct WasmList<T> {
     ptr: usize,
     len: usize,
@@ -1092,15 +2661,15 @@ pub struct WasmList<T> {
 
 impl<T: Lift> WasmList<T> {
     fn new(ptr: usize, len: usize, memory: &Memory<'_>) -> Result<WasmList<T>> {
-        match len
-            .checked_mul(T::SIZE32)
-            .and_then(|len| ptr.checked_add(len))
-        {
-            Some(n) if n <= memory.as_slice().len() => {}
-            _ => bail!("list pointer/length out of bounds of memory"),
-        }
+        memory.check_list_limit(len)?;
+        bounds_check("list", memory.as_slice().len(), ptr, len, T::SIZE32)?;
         if ptr % usize::try_from(T::ALIGN32)? != 0 {
-            bail!("list pointer is not aligned")
+            return Err(LiftError::Misaligned {
+                what: "list",
+                ptr,
+                align: T::ALIGN32,
+            }
+            .into());
         }
         Ok(WasmList {
             ptr,
@@ -1119,8 +2688,8 @@ impl<T: Lift> WasmList<T> {
     /// Gets the `n`th element of this list.
     ///
     /// Returns `None` if `index` is out of bounds. Returns `Some(Err(..))` if
-    /// the value couldn't be decoded (it was invalid). Returns `Some(Ok(..))`
-    /// if the value is valid.
+    /// the value couldn't be decoded (it was invalid) or if this list is not
+    /// owned by `store`. Returns `Some(Ok(..))` if the value is valid.
     //
     // TODO: given that interface values are intended to be consumed in one go
     // should we even expose a random access iteration API? In theory all
@@ -1129,31 +2698,300 @@ impl<T: Lift> WasmList<T> {
         self.get_from_store(store.as_context().0, index)
     }
 
-    fn get_from_store(&self, store: &StoreOpaque, index: usize) -> Option<Result<T>> {
-        if index >= self.len {
-            return None;
+    fn get_from_store(&self, store: &StoreOpaque, index: usize) -> Option<Result<T>> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.load_at(store, index))
+    }
+
+    /// Decodes the element at `index`, which must be less than `self.len`.
+    fn load_at(&self, store: &StoreOpaque, index: usize) -> Result<T> {
+        debug_assert!(index < self.len);
+        check_store(&self.options, store)?;
+        let memory = Memory::new(store, &self.options);
+        // Note that the indexing here can't panic for any `index < self.len`
+        // since `bytes` revalidates that the whole list is in bounds. That
+        // check also guarantees that `self.len * T::SIZE32` doesn't overflow,
+        // so neither does the offset arithmetic here.
+        let bytes = &self.bytes(memory.as_slice())?[index * T::SIZE32..][..T::SIZE32];
+        T::load(&memory, bytes)
+    }
+
+    /// Returns the bytes of this list within `memory`.
+    ///
+    /// The list was bounds-checked when it was lifted, but that's checked
+    /// again here rather than assuming that linear memory is still at least
+    /// as large, since this view may be used long after it was created and
+    /// arbitrary code may have run in the meantime. This also guarantees that
+    /// `self.len * T::SIZE32` doesn't overflow.
+    fn bytes<'a>(&self, memory: &'a [u8]) -> Result<&'a [u8]> {
+        let range = bounds_check("list", memory.len(), self.ptr, self.len, T::SIZE32)?;
+        Ok(&memory[range])
+    }
+
+    /// Decodes the elements in `range` of this list into a `Vec`.
+    ///
+    /// This borrows linear memory once for the whole range and decodes each
+    /// element from a single contiguous byte slice, which is more efficient
+    /// than calling [`WasmList::get`] in a loop.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this list is not owned by `store` or if `range` is
+    /// not within the bounds of this list. If
+    /// any element in `range` fails to decode then decoding stops at that
+    /// element and its error is returned; values decoded before it are
+    /// discarded.
+    pub fn get_range(&self, store: impl AsContext, range: Range<usize>) -> Result<Vec<T>> {
+        let store = store.as_context().0;
+        let mut dst = Vec::new();
+        self.load_range(store, range, &mut dst)?;
+        Ok(dst)
+    }
+
+    /// Decodes all elements of this list into a `Vec`.
+    ///
+    /// This is the same as [`WasmList::get_range`] for the whole list.
+    /// Lists of `#[component(pod)]` records are copied out of linear memory
+    /// all at once rather than decoding each element individually.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this list is not owned by `store` or if any
+    /// element fails to decode.
+    pub fn to_vec(&self, store: impl AsContext) -> Result<Vec<T>> {
+        self.load_all(store.as_context().0)
+    }
+
+    fn load_all(&self, store: &StoreOpaque) -> Result<Vec<T>> {
+        let mut dst = Vec::new();
+        self.load_range(store, 0..self.len, &mut dst)?;
+        Ok(dst)
+    }
+
+    /// Decodes this list in chunks of `chunk_len` elements, invoking `f` with
+    /// the index of the first element of each chunk and its decoded values.
+    ///
+    /// Linear memory is borrowed once per chunk, so this is an efficient way
+    /// to process large lists without materializing all of them at once. The
+    /// final chunk may be shorter than `chunk_len`.
+    ///
+    /// # Errors
+    ///
+    /// If an element fails to decode then the chunk containing it is
+    /// abandoned, `f` is not invoked for it or any later chunk, and the
+    /// element's error is returned. Chunks before it have already been passed
+    /// to `f`. Errors returned by `f` also abort iteration and are propagated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is 0.
+    pub fn for_each_chunk(
+        &self,
+        store: impl AsContext,
+        chunk_len: usize,
+        mut f: impl FnMut(usize, Vec<T>) -> Result<()>,
+    ) -> Result<()> {
+        assert!(chunk_len != 0, "chunk size must be non-zero");
+        let store = store.as_context().0;
+        let mut start = 0;
+        while start < self.len {
+            let end = self.len.min(start.saturating_add(chunk_len));
+            let mut chunk = Vec::new();
+            self.load_range(store, start..end, &mut chunk)?;
+            f(start, chunk)?;
+            start = end;
+        }
+        Ok(())
+    }
+
+    fn load_range(&self, store: &StoreOpaque, range: Range<usize>, dst: &mut Vec<T>) -> Result<()> {
+        check_store(&self.options, store)?;
+        if range.start > range.end || range.end > self.len {
+            bail!(
+                "range {}..{} out of bounds for list of length {}",
+                range.start,
+                range.end,
+                self.len
+            );
+        }
+        let memory = Memory::new(store, &self.options);
+        // See comments in `load_at` for the panicking indexing.
+        let bytes =
+            &self.bytes(memory.as_slice())?[range.start * T::SIZE32..][..range.len() * T::SIZE32];
+        // Large ranges are loaded in chunks to periodically check whether
+        // lifting should be interrupted.
+        dst.reserve(range.len());
+        let chunk_len = memory.elements_between_checks(T::SIZE32);
+        for start in (0..range.len()).step_by(chunk_len) {
+            if start > 0 {
+                memory.check_interrupt()?;
+            }
+            let len = chunk_len.min(range.len() - start);
+            let bytes = &bytes[start * T::SIZE32..][..len * T::SIZE32];
+            T::load_list(&memory, bytes, len, dst)?;
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the elements of this list.
+    ///
+    /// Each item of the list may fail to decode and is represented through the
+    /// `Result` value of the iterator. If this list is not owned by `store`
+    /// then every item is an error. The returned iterator can be consumed
+    /// from either end and supports random access through
+    /// [`Iterator::nth`] without decoding the skipped elements.
+    pub fn iter<'a, U: 'a>(&'a self, store: impl Into<StoreContext<'a, U>>) -> WasmListIter<'a, T> {
+        WasmListIter {
+            list: self,
+            store: store.into().0,
+            range: 0..self.len,
+        }
+    }
+}
+
+/// An iterator over the elements of a [`WasmList`], created with
+/// [`WasmList::iter`].
+pub struct WasmListIter<'a, T> {
+    list: &'a WasmList<T>,
+    store: &'a StoreOpaque,
+    range: Range<usize>,
+}
+
+impl<T: Lift> Iterator for WasmListIter<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        let index = self.range.next()?;
+        Some(self.list.load_at(self.store, index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.range.len()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Result<T>> {
+        let index = self.range.nth(n)?;
+        Some(self.list.load_at(self.store, index))
+    }
+
+    fn last(mut self) -> Option<Result<T>> {
+        self.next_back()
+    }
+}
+
+impl<T: Lift> DoubleEndedIterator for WasmListIter<'_, T> {
+    fn next_back(&mut self) -> Option<Result<T>> {
+        let index = self.range.next_back()?;
+        Some(self.list.load_at(self.store, index))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Result<T>> {
+        let index = self.range.nth_back(n)?;
+        Some(self.list.load_at(self.store, index))
+    }
+}
+
+impl<T: Lift> ExactSizeIterator for WasmListIter<'_, T> {}
+
+impl<T: Lift> std::iter::FusedIterator for WasmListIter<'_, T> {}
+
+impl<T: Lift + fmt::Debug> WasmList<T> {
+    /// Returns an adapter which formats a preview of the elements of this list
+    /// with `Debug`.
+    ///
+    /// The preview is formatted like the `Debug` output of a slice and shows
+    /// up to [`WasmListDebug::DEFAULT_MAX_ELEMENTS`] elements followed by `…`
+    /// unless otherwise configured with [`WasmListDebug::max_elements`].
+    /// Elements which fail to decode are shown as `<invalid: ...>` in place,
+    /// and if the list can't be read from `store` at all then the error is
+    /// shown instead of the list.
+    pub fn debug_with<'a, U: 'a>(
+        &'a self,
+        store: impl Into<StoreContext<'a, U>>,
+    ) -> WasmListDebug<'a, T> {
+        WasmListDebug {
+            list: self,
+            store: store.into().0,
+            max_elements: WasmListDebug::<T>::DEFAULT_MAX_ELEMENTS,
+        }
+    }
+}
+
+/// Shows where a list is and how long it is without its elements, which can
+/// only be read with a store. See [`WasmList::debug_with`] for a preview of
+/// the elements.
+impl<T> fmt::Debug for WasmList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(&short_type_name(std::any::type_name::<Self>()))
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// A preview of the elements of a [`WasmList`], created with
+/// [`WasmList::debug_with`].
+pub struct WasmListDebug<'a, T> {
+    list: &'a WasmList<T>,
+    store: &'a StoreOpaque,
+    max_elements: usize,
+}
+
+impl<T> WasmListDebug<'_, T> {
+    /// The number of elements shown by default.
+    pub const DEFAULT_MAX_ELEMENTS: usize = 16;
+
+    /// Sets the number of elements shown before the rest of the list is
+    /// elided as `…`.
+    pub fn max_elements(mut self, max_elements: usize) -> Self {
+        self.max_elements = max_elements;
+        self
+    }
+}
+
+impl<T: Lift + fmt::Debug> fmt::Debug for WasmListDebug<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// Formats an element, or the error from decoding it, in the list.
+        struct Element<T>(Result<T>);
+
+        impl<T: fmt::Debug> fmt::Debug for Element<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match &self.0 {
+                    Ok(value) => fmt::Debug::fmt(value, f),
+                    Err(e) => write!(f, "<invalid: {}>", e),
+                }
+            }
+        }
+
+        /// Formats the marker for elided elements without quotes.
+        struct Elided;
+
+        impl fmt::Debug for Elided {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("…")
+            }
         }
-        let memory = Memory::new(store, &self.options);
-        // Note that this is using panicking indexing and this is expected to
-        // never fail. The bounds-checking here happened during the construction
-        // of the `WasmList` itself which means these should always be in-bounds
-        // (and wasm memory can only grow). This could theoretically be
-        // unchecked indexing if we're confident enough and it's actually a perf
-        // issue one day.
-        let bytes = &memory.as_slice()[self.ptr + index * T::SIZE32..][..T::SIZE32];
-        Some(T::load(&memory, bytes))
-    }
 
-    /// Returns an iterator over the elements of this list.
-    ///
-    /// Each item of the list may fail to decode and is represented through the
-    /// `Result` value of the iterator.
-    pub fn iter<'a, U: 'a>(
-        &'a self,
-        store: impl Into<StoreContext<'a, U>>,
-    ) -> impl ExactSizeIterator<Item = Result<T>> + 'a {
-        let store = store.into().0;
-        (0..self.len).map(move |i| self.get_from_store(store, i).unwrap())
+        if let Err(e) = check_store(&self.list.options, self.store)
+            .and_then(|()| self.list.bytes(self.list.options.memory(self.store)))
+        {
+            return write!(f, "<invalid: {}>", e);
+        }
+        let mut list = f.debug_list();
+        let shown = self.list.len.min(self.max_elements);
+        for index in 0..shown {
+            list.entry(&Element(self.list.load_at(self.store, index)));
+        }
+        if shown < self.list.len {
+            list.entry(&Elided);
+        }
+        list.finish()
     }
 }
 
@@ -1173,14 +3011,14 @@ macro_rules! raw_wasm_list_accessors {
             /// byte-endianness. The `from_le` constructors in the Rust standard
             /// library should be suitable for converting from little-endian.
             ///
-            /// # Panics
+            /// # Errors
             ///
-            /// Panics if the `store` provided is not the one from which this
-            /// slice originated.
-            pub fn as_le_slice<'a, T: 'a>(&self, store: impl Into<StoreContext<'a, T>>) -> &'a [$i] {
-                // See comments in `WasmList::get` for the panicking indexing
-                let byte_size = self.len * mem::size_of::<$i>();
-                let bytes = &self.options.memory(store.into().0)[self.ptr..][..byte_size];
+            /// Returns an error if the `store` provided is not the one from
+            /// which this slice originated.
+            pub fn as_le_slice<'a, T: 'a>(&self, store: impl Into<StoreContext<'a, T>>) -> Result<&'a [$i]> {
+                let store = store.into().0;
+                check_store(&self.options, store)?;
+                let bytes = self.bytes(self.options.memory(store))?;
 
                 // The canonical ABI requires that everything is aligned to its
                 // own size, so this should be an aligned array. Furthermore the
@@ -1196,7 +3034,7 @@ macro_rules! raw_wasm_list_accessors {
                 unsafe {
                     let (head, body, tail) = bytes.align_to::<$i>();
                     assert!(head.is_empty() && tail.is_empty());
-                    body
+                    Ok(body)
                 }
             }
         }
@@ -1208,6 +3046,136 @@ raw_wasm_list_accessors! {
     u8 u16 u32 u64
 }
 
+macro_rules! copy_wasm_list_accessors {
+    ($($i:ident => $from_le:expr,)*) => ($(
+        impl WasmList<$i> {
+            /// Copies the elements of this list into `dst`, which must have
+            /// the same length as this list.
+            ///
+            /// This is equivalent to collecting the results of
+            /// [`WasmList::iter`] into `dst`, but copies the whole list out of
+            /// linear memory at once. Unlike [`WasmList::as_le_slice`] the
+            /// values are converted to the host's byte order, and `dst` can
+            /// outlive the borrow of `store`.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the `store` provided is not the one from
+            /// which this list originated, or if `dst` isn't the same length
+            /// as this list.
+            pub fn copy_to_slice<'a, T: 'a>(
+                &self,
+                store: impl Into<StoreContext<'a, T>>,
+                dst: &mut [$i],
+            ) -> Result<()> {
+                let store = store.into().0;
+                check_store(&self.options, store)?;
+                if dst.len() != self.len {
+                    bail!(
+                        "destination slice has length {} but the list has length {}",
+                        dst.len(),
+                        self.len
+                    );
+                }
+                let bytes = self.bytes(self.options.memory(store))?;
+                assert_eq!(bytes.len(), mem::size_of_val(dst));
+
+                // Any bit pattern is valid for these primitives, so this copies
+                // the raw little-endian values and fixes them up in place
+                // afterwards, which is a no-op for integers on little-endian
+                // hosts.
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        bytes.as_ptr(),
+                        dst.as_mut_ptr().cast::<u8>(),
+                        bytes.len(),
+                    );
+                }
+                let from_le: fn($i) -> $i = $from_le;
+                for item in dst.iter_mut() {
+                    *item = from_le(*item);
+                }
+                Ok(())
+            }
+        }
+    )*)
+}
+
+copy_wasm_list_accessors! {
+    i8 => i8::from_le,
+    i16 => i16::from_le,
+    i32 => i32::from_le,
+    i64 => i64::from_le,
+    u8 => u8::from_le,
+    u16 => u16::from_le,
+    u32 => u32::from_le,
+    u64 => u64::from_le,
+    // Floats additionally have their nan payloads canonicalized, as when
+    // they're lifted individually.
+    f32 => |f| match f32::from_bits(u32::from_le(f.to_bits())) {
+        f if f.is_nan() => f32::NAN,
+        f => f,
+    },
+    f64 => |f| match f64::from_bits(u64::from_le(f.to_bits())) {
+        f if f.is_nan() => f64::NAN,
+        f => f,
+    },
+}
+
+impl WasmList<bool> {
+    /// Get access to the raw underlying bytes of this list.
+    ///
+    /// Like [`WasmList::as_le_slice`] for integers this returns a direct slice
+    /// into the original wasm module's linear memory. Each `bool` is stored as
+    /// one byte which is 0 for `false` and 1 for `true`, but note that the
+    /// bytes aren't validated so other values may be present as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `store` provided is not the one from which this
+    /// list originated.
+    pub fn as_raw_bytes<'a, T: 'a>(
+        &self,
+        store: impl Into<StoreContext<'a, T>>,
+    ) -> Result<&'a [u8]> {
+        self.raw_bytes(store.into().0)
+    }
+
+    /// Decodes all elements of this list into a `Vec<bool>`.
+    ///
+    /// This is equivalent to collecting the results of [`WasmList::iter`] but
+    /// validates and converts all bytes of the list at once rather than
+    /// loading each element individually. Nonzero bytes other than 1 are
+    /// converted to `true`, or are rejected if
+    /// [`Config::component_model_strict_validation`](crate::Config::component_model_strict_validation)
+    /// is enabled, just as when lifting a single `bool`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `store` provided is not the one from which this
+    /// list originated, or if strict validation is enabled and the list
+    /// contains an invalid `bool`.
+    pub fn to_bools<'a, T: 'a>(&self, store: impl Into<StoreContext<'a, T>>) -> Result<Vec<bool>> {
+        let store = store.into().0;
+        let bytes = self.raw_bytes(store)?;
+        if store.engine().config().component_model_strict_validation {
+            if let Some(n) = bytes.iter().copied().find(|b| *b > 1) {
+                return Err(LiftError::InvalidBool {
+                    value: n.into(),
+                    source: "loaded from linear memory",
+                }
+                .into());
+            }
+        }
+        Ok(bytes.iter().map(|b| *b != 0).collect())
+    }
+
+    fn raw_bytes<'a>(&self, store: &'a StoreOpaque) -> Result<&'a [u8]> {
+        check_store(&self.options, store)?;
+        self.bytes(self.options.memory(store))
+    }
+}
+
 // Note that this is similar to `ComponentType for str` except it can only be
 // used for lifting, not lowering.
 unsafe impl<T: ComponentType> ComponentType for WasmList<T> {
@@ -1216,10 +3184,19 @@ unsafe impl<T: ComponentType> ComponentType for WasmList<T> {
     const SIZE32: usize = <[T] as ComponentType>::SIZE32;
     const ALIGN32: u32 = <[T] as ComponentType>::ALIGN32;
 
+    fn describe() -> String {
+        format!("list<{}>", T::describe())
+    }
+
     fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
         match ty {
-            InterfaceType::List(t) => T::typecheck(&types[*t], types),
-            other => bail!("expected `list` found `{}`", desc(other)),
+            InterfaceType::List(t) => with_type_path(T::typecheck(&types[*t], types), || {
+                " (list element)".to_string()
+            }),
+            other => bail!(
+                "expected `list` found `{}`",
+                DisplayInterfaceType::new(other, types)
+            ),
         }
     }
 }
@@ -1237,7 +3214,7 @@ unsafe impl<T: Lift> Lift for WasmList<T> {
         debug_assert!((bytes.as_ptr() as usize) % (Self::ALIGN32 as usize) == 0);
         // FIXME: needs memory64 treatment
         let ptr = u32::from_le_bytes(bytes[..4].try_into().unwrap());
-        let len = u32::from_le_bytes(bytes[4..].try_into().unwrap());
+        let len = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
         let (ptr, len) = (usize::try_from(ptr)?, usize::try_from(len)?);
         WasmList::new(ptr, len, memory)
     }
@@ -1261,6 +3238,188 @@ pub fn next_field<T: ComponentType>(offset: &mut usize) -> usize {
     result
 }
 
+/// Returns the offsets of fields with the given `(size, align)` layouts when
+/// they're laid out one after another, as in a record or tuple.
+///
+/// This is a `const fn` so offsets can be computed once at compile time rather
+/// than with `next_field` for every value lifted or lowered.
+pub const fn field_offsets<const N: usize>(fields: [(usize, u32); N]) -> [usize; N] {
+    let mut offsets = [0; N];
+    let mut offset = 0;
+    let mut i = 0;
+    while i < N {
+        let (size, align) = fields[i];
+        offset = align_to(offset, align);
+        offsets[i] = offset;
+        offset += size;
+        i += 1;
+    }
+    offsets
+}
+
+/// Validates the bytes of a variant-like value (`option`, `expected`,
+/// `variant`, etc) in linear memory which don't contribute to its value.
+///
+/// The `bytes` are the entire value, `discriminant_size` bytes of discriminant
+/// followed by the payload at `payload_offset`, of which only the first
+/// `payload_size` bytes are used by the case being lifted. All other bytes are
+/// padding which is only required to be zero if strict validation is enabled,
+/// otherwise this does nothing.
+pub fn validate_variant_padding(
+    memory: &Memory<'_>,
+    bytes: &[u8],
+    discriminant_size: usize,
+    payload_offset: usize,
+    payload_size: usize,
+) -> Result<()> {
+    if !memory.strict_validation() {
+        return Ok(());
+    }
+    let unused =
+        (discriminant_size..payload_offset).chain(payload_offset + payload_size..bytes.len());
+    for offset in unused {
+        if bytes[offset] != 0 {
+            let what = if offset < payload_offset {
+                "padding after discriminant"
+            } else {
+                "unused payload"
+            };
+            return Err(LiftError::NonzeroPadding {
+                what,
+                value: bytes[offset],
+                offset,
+                size: bytes.len(),
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Error from a failed typecheck which records where within a possibly nested
+/// type the mismatch was found, for example
+/// `params[1].entries (list element).name`.
+#[derive(Debug)]
+struct TypecheckError {
+    /// Segments of the path to the mismatched type, innermost first since
+    /// they're pushed as the error propagates outwards.
+    path: Vec<String>,
+    /// The index of the parameter where the mismatch was found, if it was
+    /// found within a parameter.
+    param: Option<usize>,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for TypecheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self
+            .path
+            .iter()
+            .rev()
+            .map(|s| s.as_str())
+            .collect::<String>();
+        write!(
+            f,
+            "type mismatch for {}: {}",
+            path.trim_start_matches(|c| c == '.' || c == ' '),
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for TypecheckError {}
+
+/// Typechecks the signature `ty` against `Params` and `Return`.
+///
+/// On failure the returned error describes where the mismatch happened and
+/// renders both the entire signature of `ty` and the signature requested by
+/// `Params` and `Return`, with the details of the mismatch as its cause.
+pub(crate) fn typecheck_signature<Params, Return>(
+    ty: &TypeFunc,
+    types: &ComponentTypes,
+) -> Result<()>
+where
+    Params: ComponentParams,
+    Return: ComponentType,
+{
+    let (location, error) = match Params::typecheck_params(&ty.params, types) {
+        Ok(()) => match with_type_path(Return::typecheck(&ty.result, types), || {
+            "result".to_string()
+        }) {
+            Ok(()) => return Ok(()),
+            Err(error) => ("type mismatch in result".to_string(), error),
+        },
+        Err(error) => {
+            let index = error.downcast_ref::<TypecheckError>().and_then(|e| e.param);
+            let location = match index {
+                Some(i) => match &ty.params[i].0 {
+                    Some(name) => format!("type mismatch at parameter {} (`{}`)", i + 1, name),
+                    None => format!("type mismatch at parameter {}", i + 1),
+                },
+                None => "type mismatch in number of parameters".to_string(),
+            };
+            (location, error)
+        }
+    };
+
+    let result = match &ty.result {
+        InterfaceType::Unit => None,
+        result => Some(DisplayInterfaceType::new(result, types).to_string()),
+    };
+    let have = describe_signature(
+        ty.params
+            .iter()
+            .map(|(_, ty)| DisplayInterfaceType::new(ty, types).to_string()),
+        result,
+    );
+    let requested = describe_signature(
+        Params::describe_params().into_iter(),
+        Some(Return::describe()).filter(|s| s != "unit"),
+    );
+    Err(error).with_context(|| format!("{}: have `{}`, requested `{}`", location, have, requested))
+}
+
+fn describe_signature(params: impl Iterator<Item = String>, result: Option<String>) -> String {
+    let mut signature = format!("({})", params.collect::<Vec<_>>().join(", "));
+    if let Some(result) = result {
+        signature.push_str(" -> ");
+        signature.push_str(&result);
+    }
+    signature
+}
+
+/// Records `segment` as the next outermost part of the type path for a
+/// typecheck error in `result`, if there is one.
+pub(crate) fn with_type_path(result: Result<()>, segment: impl FnOnce() -> String) -> Result<()> {
+    push_type_path(result, segment, None)
+}
+
+/// Same as `with_type_path` for the type of the parameter at `index`, which is
+/// also recorded to report which parameter didn't match.
+fn with_param_path(result: Result<()>, index: usize) -> Result<()> {
+    push_type_path(result, || format!("params[{index}]"), Some(index))
+}
+
+fn push_type_path(
+    result: Result<()>,
+    segment: impl FnOnce() -> String,
+    param: Option<usize>,
+) -> Result<()> {
+    result.map_err(|error| match error.downcast::<TypecheckError>() {
+        Ok(mut error) => {
+            error.path.push(segment());
+            error.param = param.or(error.param);
+            error.into()
+        }
+        Err(error) => TypecheckError {
+            path: vec![segment()],
+            param,
+            error,
+        }
+        .into(),
+    })
+}
+
 /// Verify that the given wasm type is a tuple with the expected fields in the right order.
 fn typecheck_tuple(
     ty: &InterfaceType,
@@ -1284,15 +3443,21 @@ fn typecheck_tuple(
                     tuple.types.len()
                 );
             }
-            for (ty, check) in tuple.types.iter().zip(expected) {
-                check(ty, types)?;
+            for (index, (ty, check)) in tuple.types.iter().zip(expected).enumerate() {
+                with_type_path(check(ty, types), || format!(".{index}"))?;
             }
             Ok(())
         }
         other if expected.len() == 0 => {
-            bail!("expected `unit` or 0-tuple found `{}`", desc(other))
+            bail!(
+                "expected `unit` or 0-tuple found `{}`",
+                DisplayInterfaceType::new(other, types)
+            )
         }
-        other => bail!("expected `tuple` found `{}`", desc(other)),
+        other => bail!(
+            "expected `tuple` found `{}`",
+            DisplayInterfaceType::new(other, types)
+        ),
     }
 }
 
@@ -1316,8 +3481,7 @@ pub fn typecheck_record(
             }
 
             for (field, &(name, check)) in fields.iter().zip(expected) {
-                check(&field.ty, types)
-                    .with_context(|| format!("type mismatch for field {}", name))?;
+                with_type_path(check(&field.ty, types), || format!(".{name}"))?;
 
                 if field.name != name {
                     bail!("expected record field named {}, found {}", name, field.name);
@@ -1326,7 +3490,66 @@ pub fn typecheck_record(
 
             Ok(())
         }
-        other => bail!("expected `record` found `{}`", desc(other)),
+        other => bail!(
+            "expected `record` found `{}`",
+            DisplayInterfaceType::new(other, types)
+        ),
+    }
+}
+
+/// Verify that the given wasm type is a record with the expected fields in the
+/// right order, for records declaring that order with `field_order`.
+///
+/// This accepts exactly the same records as [`typecheck_record`], since the
+/// order of `expected` determines the canonical ABI layout which the host type
+/// lifts and lowers. Missing, unexpected, and misplaced fields, however, are
+/// all reported in terms of field names, as the Rust declaration order of the
+/// host type doesn't necessarily match `expected`.
+pub fn typecheck_record_with_field_order(
+    ty: &InterfaceType,
+    types: &ComponentTypes,
+    expected: &[(&str, fn(&InterfaceType, &ComponentTypes) -> Result<()>)],
+) -> Result<()> {
+    match ty {
+        InterfaceType::Record(index) => {
+            let fields = &types[*index].fields;
+
+            for field in fields.iter() {
+                if !expected.iter().any(|(name, _)| *name == field.name) {
+                    bail!("record has unexpected field named {}", field.name);
+                }
+            }
+
+            // Build up a permutation from the host's field order to the
+            // positions of the same fields in the component's record.
+            let mut permutation = Vec::with_capacity(expected.len());
+            for &(name, check) in expected {
+                let position = match fields.iter().position(|field| field.name == name) {
+                    Some(position) => position,
+                    None => bail!("record is missing field named {}", name),
+                };
+                with_type_path(check(&fields[position].ty, types), || format!(".{name}"))?;
+                permutation.push(position);
+            }
+
+            for (index, position) in permutation.into_iter().enumerate() {
+                if index != position {
+                    bail!(
+                        "record field named {} is declared at position {} but is expected at \
+                         position {}",
+                        expected[index].0,
+                        position,
+                        index
+                    );
+                }
+            }
+
+            Ok(())
+        }
+        other => bail!(
+            "expected `record` found `{}`",
+            DisplayInterfaceType::new(other, types)
+        ),
     }
 }
 
@@ -1343,24 +3566,35 @@ pub fn typecheck_variant(
 
             if cases.len() != expected.len() {
                 bail!(
-                    "expected variant of {} cases, found {} cases",
+                    "expected variant of {} cases, found {} cases{}",
                     expected.len(),
-                    cases.len()
+                    cases.len(),
+                    describe_name_diff(
+                        expected.iter().map(|(name, _)| *name),
+                        cases.iter().map(|case| case.name.as_str()),
+                    )
                 );
             }
 
-            for (case, &(name, check)) in cases.iter().zip(expected) {
-                check(&case.ty, types)
-                    .with_context(|| format!("type mismatch for case {}", name))?;
-
+            for (position, (case, &(name, check))) in cases.iter().zip(expected).enumerate() {
                 if case.name != name {
-                    bail!("expected variant case named {}, found {}", name, case.name);
+                    bail!(
+                        "expected variant case {} to be named `{}`, found `{}`",
+                        position,
+                        name,
+                        case.name
+                    );
                 }
+
+                with_type_path(check(&case.ty, types), || format!(" (case {name})"))?;
             }
 
             Ok(())
         }
-        other => bail!("expected `variant` found `{}`", desc(other)),
+        other => bail!(
+            "expected `variant` found `{}`",
+            DisplayInterfaceType::new(other, types)
+        ),
     }
 }
 
@@ -1373,21 +3607,33 @@ pub fn typecheck_enum(ty: &InterfaceType, types: &ComponentTypes, expected: &[&s
 
             if names.len() != expected.len() {
                 bail!(
-                    "expected enum of {} names, found {} names",
+                    "expected enum of {} names, found {} names{}",
                     expected.len(),
-                    names.len()
+                    names.len(),
+                    describe_name_diff(
+                        expected.iter().copied(),
+                        names.iter().map(|name| name.as_str()),
+                    )
                 );
             }
 
-            for (name, expected) in names.iter().zip(expected) {
+            for (position, (name, expected)) in names.iter().zip(expected).enumerate() {
                 if name != expected {
-                    bail!("expected enum case named {}, found {}", expected, name);
+                    bail!(
+                        "expected enum case {} to be named `{}`, found `{}`",
+                        position,
+                        expected,
+                        name
+                    );
                 }
             }
 
             Ok(())
         }
-        other => bail!("expected `enum` found `{}`", desc(other)),
+        other => bail!(
+            "expected `enum` found `{}`",
+            DisplayInterfaceType::new(other, types)
+        ),
     }
 }
 
@@ -1403,20 +3649,50 @@ pub fn typecheck_union(
 
             if union_types.len() != expected.len() {
                 bail!(
-                    "expected union of {} types, found {} types",
+                    "expected union of {} types, found {} types in `{}`",
                     expected.len(),
-                    union_types.len()
+                    union_types.len(),
+                    DisplayInterfaceType::new(ty, types)
                 );
             }
 
             for (index, (ty, check)) in union_types.iter().zip(expected).enumerate() {
-                check(ty, types).with_context(|| format!("type mismatch for case {}", index))?;
+                with_type_path(check(ty, types), || format!(" (case {index})"))?;
             }
 
             Ok(())
         }
-        other => bail!("expected `union` found `{}`", desc(other)),
+        other => bail!(
+            "expected `union` found `{}`",
+            DisplayInterfaceType::new(other, types)
+        ),
+    }
+}
+
+/// Describes the difference between the `expected` and `found` case names in
+/// the style of a diff, listing names which are missing with `-` and names
+/// which are unexpected with `+`, one per line.
+fn describe_name_diff<'a>(
+    expected: impl Iterator<Item = &'a str> + Clone,
+    found: impl Iterator<Item = &'a str> + Clone,
+) -> String {
+    let mut diff = String::new();
+    for name in expected.clone() {
+        if !found.clone().any(|found| found == name) {
+            diff.push_str("\n  - ");
+            diff.push_str(name);
+        }
     }
+    for name in found {
+        if !expected.clone().any(|expected| expected == name) {
+            diff.push_str("\n  + ");
+            diff.push_str(name);
+        }
+    }
+    if !diff.is_empty() {
+        diff.insert(0, ':');
+    }
+    diff
 }
 
 /// Verify that the given wasm type is a flags type with the expected flags in the right order and with the right
@@ -1446,16 +3722,34 @@ pub fn typecheck_flags(
 
             Ok(())
         }
-        other => bail!("expected `flags` found `{}`", desc(other)),
+        other => bail!(
+            "expected `flags` found `{}`",
+            DisplayInterfaceType::new(other, types)
+        ),
     }
 }
 
 /// Format the specified bitflags using the specified names for debugging
-pub fn format_flags(bits: &[u32], names: &[&str], f: &mut fmt::Formatter) -> fmt::Result {
+///
+/// The `bits` are expected to be `words` 32-bit words large, which must be
+/// enough to store one bit for each of `names`. Flags beyond the end of `bits`
+/// are never printed.
+pub fn format_flags(
+    bits: &[u32],
+    words: usize,
+    names: &[&str],
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    debug_assert_eq!(bits.len(), words);
+    debug_assert!(names.len() <= words * 32);
     f.write_str("(")?;
     let mut wrote = false;
     for (index, name) in names.iter().enumerate() {
-        if ((bits[index / 32] >> (index % 32)) & 1) != 0 {
+        let word = match bits.get(index / 32) {
+            Some(word) => *word,
+            None => break,
+        };
+        if ((word >> (index % 32)) & 1) != 0 {
             if wrote {
                 f.write_str("|")?;
             } else {
@@ -1468,6 +3762,19 @@ pub fn format_flags(bits: &[u32], names: &[&str], f: &mut fmt::Formatter) -> fmt
     f.write_str(")")
 }
 
+/// Returns an iterator over the entries of `names` whose bit is set in `bits`.
+pub fn flag_names<const N: usize>(
+    bits: [u32; N],
+    names: &'static [&'static str],
+) -> impl Iterator<Item = &'static str> {
+    debug_assert!(names.len() <= N * 32);
+    names
+        .iter()
+        .enumerate()
+        .filter(move |(index, _)| ((bits[index / 32] >> (index % 32)) & 1) != 0)
+        .map(|(_, name)| *name)
+}
+
 unsafe impl<T> ComponentType for Option<T>
 where
     T: ComponentType,
@@ -1477,10 +3784,19 @@ where
     const SIZE32: usize = align_to(1, T::ALIGN32) + T::SIZE32;
     const ALIGN32: u32 = T::ALIGN32;
 
+    fn describe() -> String {
+        format!("option<{}>", T::describe())
+    }
+
     fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
         match ty {
-            InterfaceType::Option(t) => T::typecheck(&types[*t], types),
-            other => bail!("expected `option` found `{}`", desc(other)),
+            InterfaceType::Option(t) => with_type_path(T::typecheck(&types[*t], types), || {
+                " (option payload)".to_string()
+            }),
+            other => bail!(
+                "expected `option` found `{}`",
+                DisplayInterfaceType::new(other, types)
+            ),
         }
     }
 }
@@ -1539,18 +3855,36 @@ where
         Ok(match src.A1.get_i32() {
             0 => None,
             1 => Some(T::lift(store, options, &src.A2)?),
-            _ => bail!("invalid option discriminant"),
+            n => {
+                return Err(LiftError::InvalidDiscriminant {
+                    ty: "option",
+                    value: n as u32,
+                }
+                .into())
+            }
         })
     }
 
     fn load(memory: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
         debug_assert!((bytes.as_ptr() as usize) % (Self::ALIGN32 as usize) == 0);
         let discrim = bytes[0];
-        let payload = &bytes[align_to(1, T::ALIGN32)..];
+        let payload_offset = align_to(1, T::ALIGN32);
+        let payload = &bytes[payload_offset..];
+        let bytes = &bytes[..Self::SIZE32];
         match discrim {
-            0 => Ok(None),
-            1 => Ok(Some(T::load(memory, payload)?)),
-            _ => bail!("invalid option discriminant"),
+            0 => {
+                validate_variant_padding(memory, bytes, 1, payload_offset, 0)?;
+                Ok(None)
+            }
+            1 => {
+                validate_variant_padding(memory, bytes, 1, payload_offset, T::SIZE32)?;
+                Ok(Some(T::load(memory, payload)?))
+            }
+            n => Err(LiftError::InvalidDiscriminant {
+                ty: "option",
+                value: n.into(),
+            }
+            .into()),
         }
     }
 }
@@ -1588,15 +3922,22 @@ where
         E::ALIGN32
     };
 
+    fn describe() -> String {
+        format!("expected<{}, {}>", T::describe(), E::describe())
+    }
+
     fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
         match ty {
             InterfaceType::Expected(r) => {
                 let expected = &types[*r];
-                T::typecheck(&expected.ok, types)?;
-                E::typecheck(&expected.err, types)?;
+                with_type_path(T::typecheck(&expected.ok, types), || " (ok)".to_string())?;
+                with_type_path(E::typecheck(&expected.err, types), || " (err)".to_string())?;
                 Ok(())
             }
-            other => bail!("expected `expected` found `{}`", desc(other)),
+            other => bail!(
+                "expected `expected` found `{}`",
+                DisplayInterfaceType::new(other, types)
+            ),
         }
     }
 }
@@ -1687,7 +4028,13 @@ where
         Ok(match src.tag.get_i32() {
             0 => Ok(unsafe { T::lift(store, options, &src.payload.ok)? }),
             1 => Err(unsafe { E::lift(store, options, &src.payload.err)? }),
-            _ => bail!("invalid expected discriminant"),
+            n => {
+                return Err(LiftError::InvalidDiscriminant {
+                    ty: "expected",
+                    value: n as u32,
+                }
+                .into())
+            }
         })
     }
 
@@ -1695,11 +4042,23 @@ where
         debug_assert!((bytes.as_ptr() as usize) % (Self::ALIGN32 as usize) == 0);
         let align = Self::ALIGN32;
         let discrim = bytes[0];
-        let payload = &bytes[align_to(1, align)..];
+        let payload_offset = align_to(1, align);
+        let payload = &bytes[payload_offset..];
+        let bytes = &bytes[..Self::SIZE32];
         match discrim {
-            0 => Ok(Ok(T::load(memory, &payload[..T::SIZE32])?)),
-            1 => Ok(Err(E::load(memory, &payload[..E::SIZE32])?)),
-            _ => bail!("invalid expected discriminant"),
+            0 => {
+                validate_variant_padding(memory, bytes, 1, payload_offset, T::SIZE32)?;
+                Ok(Ok(T::load(memory, &payload[..T::SIZE32])?))
+            }
+            1 => {
+                validate_variant_padding(memory, bytes, 1, payload_offset, E::SIZE32)?;
+                Ok(Err(E::load(memory, &payload[..E::SIZE32])?))
+            }
+            n => Err(LiftError::InvalidDiscriminant {
+                ty: "expected",
+                value: n.into(),
+            }
+            .into()),
         }
     }
 }
@@ -1715,6 +4074,14 @@ macro_rules! impl_component_ty_for_tuples {
             _align_tuple_lower0_correctly: [ValRaw; 0],
         }
 
+        /// Carries the offsets of the fields of a tuple of this arity, since
+        /// inherent constants can't be defined on tuples themselves.
+        struct [<TupleOffsets$n>]<$($t),*>(marker::PhantomData<($($t,)*)>);
+
+        impl<$($t: ComponentType),*> [<TupleOffsets$n>]<$($t),*> {
+            const OFFSETS: [usize; $n] = field_offsets([$(($t::SIZE32, $t::ALIGN32)),*]);
+        }
+
         #[allow(non_snake_case)]
         unsafe impl<$($t,)*> ComponentType for ($($t,)*)
             where $($t: ComponentType),*
@@ -1738,6 +4105,12 @@ macro_rules! impl_component_ty_for_tuples {
                 _align
             };
 
+            const IS_RUST_UNIT_TYPE: bool = $n == 0;
+
+            fn describe() -> String {
+                describe_tuple(&[$($t::describe()),*])
+            }
+
             fn typecheck(
                 ty: &InterfaceType,
                 types: &ComponentTypes,
@@ -1761,10 +4134,11 @@ macro_rules! impl_component_ty_for_tuples {
                 Ok(())
             }
 
-            fn store<U>(&self, _memory: &mut MemoryMut<'_, U>, mut _offset: usize) -> Result<()> {
+            fn store<U>(&self, _memory: &mut MemoryMut<'_, U>, _offset: usize) -> Result<()> {
                 debug_assert!(_offset % (Self::ALIGN32 as usize) == 0);
                 let ($($t,)*) = self;
-                $($t.store(_memory, next_field::<$t>(&mut _offset))?;)*
+                let [$([<_offset_ $t>],)*] = [<TupleOffsets$n>]::<$($t),*>::OFFSETS;
+                $($t.store(_memory, _offset + [<_offset_ $t>])?;)*
                 Ok(())
             }
         }
@@ -1779,8 +4153,8 @@ macro_rules! impl_component_ty_for_tuples {
 
             fn load(_memory: &Memory<'_>, bytes: &[u8]) -> Result<Self> {
                 debug_assert!((bytes.as_ptr() as usize) % (Self::ALIGN32 as usize) == 0);
-                let mut _offset = 0;
-                $(let $t = $t::load(_memory, &bytes[next_field::<$t>(&mut _offset)..][..$t::SIZE32])?;)*
+                let [$([<_offset_ $t>],)*] = [<TupleOffsets$n>]::<$($t),*>::OFFSETS;
+                $(let $t = $t::load(_memory, &bytes[[<_offset_ $t>]..][..$t::SIZE32])?;)*
                 Ok(($($t,)*))
             }
         }
@@ -1794,21 +4168,75 @@ macro_rules! impl_component_ty_for_tuples {
                 _types: &ComponentTypes,
             ) -> Result<()> {
                 if params.len() != $n {
-                    bail!("expected {} types, found {}", $n, params.len());
+                    return with_type_path(
+                        Err(anyhow::anyhow!("expected {} types, found {}", $n, params.len())),
+                        || "params".to_string(),
+                    );
                 }
-                let mut params = params.iter().map(|i| &i.1);
-                $($t::typecheck(params.next().unwrap(), _types)?;)*
+                let mut params = params.iter().map(|i| &i.1).enumerate();
+                $(
+                    let (index, ty) = params.next().unwrap();
+                    with_param_path($t::typecheck(ty, _types), index)?;
+                )*
                 debug_assert!(params.next().is_none());
                 Ok(())
             }
+
+            fn describe_params() -> Vec<String> {
+                vec![$($t::describe()),*]
+            }
         }
 
     }};
 }
 
-for_each_function_signature!(impl_component_ty_for_tuples);
+/// Invokes `$mac` for each tuple arity which implements the component traits
+/// in this module.
+///
+/// This is intentionally separate from `for_each_function_signature!`: that
+/// macro's bound is the arity of host functions, but tuples (and records
+/// flattened into tuples) in component types are commonly larger than that.
+macro_rules! for_each_component_tuple {
+    ($mac:ident) => {
+        $mac!(0);
+        $mac!(1 A1);
+        $mac!(2 A1 A2);
+        $mac!(3 A1 A2 A3);
+        $mac!(4 A1 A2 A3 A4);
+        $mac!(5 A1 A2 A3 A4 A5);
+        $mac!(6 A1 A2 A3 A4 A5 A6);
+        $mac!(7 A1 A2 A3 A4 A5 A6 A7);
+        $mac!(8 A1 A2 A3 A4 A5 A6 A7 A8);
+        $mac!(9 A1 A2 A3 A4 A5 A6 A7 A8 A9);
+        $mac!(10 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10);
+        $mac!(11 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11);
+        $mac!(12 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12);
+        $mac!(13 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13);
+        $mac!(14 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14);
+        $mac!(15 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15);
+        $mac!(16 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16);
+        $mac!(17 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17);
+        $mac!(18 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18);
+        $mac!(19 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19);
+        $mac!(20 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20);
+        $mac!(21 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21);
+        $mac!(22 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22);
+        $mac!(23 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23);
+        $mac!(24 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24);
+        $mac!(25 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24 A25);
+        $mac!(26 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24 A25 A26);
+        $mac!(27 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24 A25 A26 A27);
+        $mac!(28 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24 A25 A26 A27 A28);
+        $mac!(29 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24 A25 A26 A27 A28 A29);
+        $mac!(30 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24 A25 A26 A27 A28 A29 A30);
+        $mac!(31 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24 A25 A26 A27 A28 A29 A30 A31);
+        $mac!(32 A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12 A13 A14 A15 A16 A17 A18 A19 A20 A21 A22 A23 A24 A25 A26 A27 A28 A29 A30 A31 A32);
+    };
+}
+
+for_each_component_tuple!(impl_component_ty_for_tuples);
 
-fn desc(ty: &InterfaceType) -> &'static str {
+pub(crate) fn desc(ty: &InterfaceType) -> &'static str {
     match ty {
         InterfaceType::U8 => "u8",
         InterfaceType::S8 => "s8",
@@ -1836,3 +4264,533 @@ fn desc(ty: &InterfaceType) -> &'static str {
         InterfaceType::Union(_) => "union",
     }
 }
+
+/// Describes a tuple with the element descriptions `types`, for
+/// [`ComponentType::describe`].
+fn describe_tuple(types: &[String]) -> String {
+    if types.is_empty() {
+        "unit".to_string()
+    } else {
+        format!("tuple<{}>", types.join(", "))
+    }
+}
+
+/// Strips the module paths from the Rust type name `name`, for example
+/// turning `alloc::vec::Vec<my_crate::Foo>` into `Vec<Foo>`.
+fn short_type_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut segment = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            segment.push(c);
+        } else if c == ':' {
+            segment.clear();
+        } else {
+            result.push_str(&segment);
+            segment.clear();
+            result.push(c);
+        }
+    }
+    result.push_str(&segment);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::func::options::tests::store_and_options;
+    use crate::component::{Component, Linker};
+    use crate::{Config, Engine, Store};
+
+    // On 32-bit hosts the full range of guest pointers and lengths can reach
+    // `usize::MAX`, so simulate that by using a memory which is as large as
+    // the address space.
+    #[test]
+    fn bounds_check_near_usize_max() {
+        let max = usize::MAX;
+        assert_eq!(bounds_check("list", max, 0, max, 1).unwrap(), 0..max);
+        assert_eq!(bounds_check("list", max, max, 0, 8).unwrap(), max..max);
+        assert_eq!(
+            bounds_check("list", max, max - 8, 1, 8).unwrap(),
+            max - 8..max
+        );
+
+        // `ptr + len * size` overflows
+        let err = bounds_check("list", max, max - 7, 1, 8).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("list pointer/length out of bounds"));
+        // `len * size` overflows
+        assert!(bounds_check("list", max, 0, max / 2 + 1, 2).is_err());
+        assert!(bounds_check("string", max, 0, max, 2).is_err());
+        assert!(bounds_check("string", max, 8, max / 8, 8).is_err());
+
+        let err = bounds_check("string", 0x10000, max, 1, 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "string pointer/length out of bounds of memory \
+                 (ptr {:#x}, len 1, memory size 65536)",
+                max
+            )
+        );
+    }
+
+    #[test]
+    fn field_offsets_match_next_field() {
+        fn next_field_offsets<A: ComponentType, B: ComponentType, C: ComponentType>() -> [usize; 3]
+        {
+            let mut offset = 0;
+            [
+                next_field::<A>(&mut offset),
+                next_field::<B>(&mut offset),
+                next_field::<C>(&mut offset),
+            ]
+        }
+
+        fn check<A: ComponentType, B: ComponentType, C: ComponentType>() {
+            assert_eq!(
+                TupleOffsets3::<A, B, C>::OFFSETS,
+                next_field_offsets::<A, B, C>(),
+                "{}",
+                <(A, B, C)>::describe()
+            );
+        }
+
+        check::<u8, u64, u16>();
+        check::<u64, u8, u32>();
+        check::<bool, (u8, u16), f64>();
+        check::<(), char, u8>();
+        check::<u8, WasmStr, Option<u8>>();
+        check::<Result<u8, u64>, u8, WasmList<u16>>();
+        assert_eq!(TupleOffsets3::<u8, u64, u16>::OFFSETS, [0, 8, 16]);
+        assert!(TupleOffsets0::OFFSETS.is_empty());
+        assert_eq!(
+            field_offsets([(1, 1), (4, 4), (0, 1), (2, 2)]),
+            [0, 4, 8, 8]
+        );
+    }
+
+    #[test]
+    fn lowered_size_limits() {
+        let max = u32::MAX as usize;
+        let ty = || "list<u16>".to_string();
+        assert_eq!(lowered_size(ty, 0, 2).unwrap(), 0);
+        assert_eq!(lowered_size(ty, max / 2, 2).unwrap(), max - 1);
+        assert_eq!(lowered_size(ty, max, 0).unwrap(), 0);
+
+        // The size in bytes doesn't fit in 32 bits.
+        let err = lowered_size(ty, max / 2 + 1, 2).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`list<u16>` of length 2147483648 is too large to lower into a 32-bit linear memory"
+        );
+        assert!(lowered_size(ty, usize::MAX, 2).is_err());
+
+        if cfg!(target_pointer_width = "64") {
+            // The number of elements doesn't fit in 32 bits, even though the
+            // elements don't take up any space.
+            assert!(lowered_size(ty, max + 1, 0).is_err());
+            assert!(lowered_size(ty, max + 1, 1).is_err());
+        }
+    }
+
+    #[test]
+    fn describe_rust_types() {
+        assert_eq!(
+            short_type_name("alloc::vec::Vec<my_crate::Foo<u8>>"),
+            "Vec<Foo<u8>>"
+        );
+        assert_eq!(short_type_name("(a::B, [c::D; 4])"), "(B, [D; 4])");
+        assert_eq!(
+            <(u32, &str, Vec<Option<char>>)>::describe(),
+            "tuple<u32, string, list<option<char>>>"
+        );
+        assert_eq!(<()>::describe(), "unit");
+        assert_eq!(<Result<WasmStr, f64>>::describe(), "expected<string, f64>");
+        assert_eq!(
+            <(Box<[u8]>, WasmList<u16>)>::describe_params(),
+            ["list<u8>", "list<u16>"]
+        );
+    }
+
+    #[test]
+    fn unit_results_skip_lifting() -> Result<()> {
+        assert!(<() as ComponentType>::IS_RUST_UNIT_TYPE);
+        assert!(!<((),) as ComponentType>::IS_RUST_UNIT_TYPE);
+        assert!(!<(u32,) as ComponentType>::IS_RUST_UNIT_TYPE);
+        assert!(!<u32 as ComponentType>::IS_RUST_UNIT_TYPE);
+
+        // Lifting `()` doesn't look at the store or options at all, so even
+        // options belonging to another store are fine.
+        let (store, _) = store_and_options()?;
+        let (_, options) = store_and_options()?;
+        let lower = TupleLower0 {
+            _align_tuple_lower0_correctly: [],
+        };
+        TypedFunc::<(), ()>::lift_stack_result(store.as_context().0, &options, &lower)?;
+        Ok(())
+    }
+
+    #[test]
+    fn display_interface_types() -> Result<()> {
+        let mut config = Config::new();
+        config.wasm_component_model(true);
+        let engine = Engine::new(&config)?;
+        let component = Component::new(
+            &engine,
+            r#"(component
+                (type $r (record (field "name" string) (field "age" u32)))
+                (type $f (flags "read" "write" "exec"))
+                (type $v (variant
+                    (case "none" unit)
+                    (case "pair" (tuple u8 (tuple string $r)))
+                    (case "perms" $f)))
+                (type $a (list $v))
+                (type $b (list (tuple
+                    (tuple float32 char)
+                    (expected unit (union u8 string))
+                    (enum "a" "b"))))
+                (type $c (list (list (list (list u8)))))
+                (core module $m
+                    (memory (export "memory") 1)
+                    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                        unreachable)
+                    (func (export "f") (param i32 i32 i32 i32 i32 i32))
+                )
+                (core instance $i (instantiate $m))
+                (func (export "f") (param $a) (param $b) (param $c)
+                    (canon lift (core func $i "f")
+                        (memory $i "memory")
+                        (realloc (func $i "realloc"))
+                    )
+                )
+            )"#,
+        )?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let func = instance.get_func(&mut store, "f").unwrap();
+        let data = &store.as_context()[func.0];
+        let types = &*data.types;
+        let params = &types[data.ty].params;
+
+        assert_eq!(
+            DisplayInterfaceType::new(&params[0].1, types).to_string(),
+            "list<variant { none, \
+             pair(tuple<u8, tuple<string, record { name: string, age: u32 }>>), \
+             perms(flags { read, write, exec }) }>"
+        );
+        assert_eq!(
+            DisplayInterfaceType::new(&params[1].1, types).to_string(),
+            "list<tuple<tuple<f32, char>, expected<unit, union { u8, string }>, enum { a, b }>>"
+        );
+
+        let deep = &params[2].1;
+        assert_eq!(
+            DisplayInterfaceType::new(deep, types).to_string(),
+            "list<list<list<list<u8>>>>"
+        );
+        assert_eq!(
+            DisplayInterfaceType::new(deep, types)
+                .max_depth(4)
+                .to_string(),
+            "list<list<list<list<u8>>>>"
+        );
+        assert_eq!(
+            DisplayInterfaceType::new(deep, types)
+                .max_depth(2)
+                .to_string(),
+            "list<list<...>>"
+        );
+        assert_eq!(
+            DisplayInterfaceType::new(deep, types)
+                .max_depth(0)
+                .to_string(),
+            "..."
+        );
+        // Primitives are never elided.
+        assert_eq!(
+            DisplayInterfaceType::new(&InterfaceType::U8, types)
+                .max_depth(0)
+                .to_string(),
+            "u8"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lower_string_with_overridden_encoding() -> Result<()> {
+        let (mut store, declared) = store_and_options()?;
+        assert!(matches!(declared.string_encoding(), StringEncoding::Utf8));
+        assert!(!declared.memory64());
+        assert!(declared.with_memory64(true).memory64());
+
+        for encoding in [StringEncoding::Utf8, StringEncoding::Utf16] {
+            let options = declared.with_string_encoding(encoding);
+
+            for s in [
+                "",
+                "hello",
+                "h\u{e9}llo \u{1f370}",
+                // Ascii prefixes of various lengths followed by characters of
+                // each utf-8 length.
+                "a\u{4e16}\u{754c}",
+                "abcdefg\u{4e16}\u{754c}",
+                "0123456789abcdef\u{3b1}",
+                "xyz\u{1f30d}!",
+                "\u{4e16}ascii after",
+                "\u{7f}\u{80}",
+                "\0\0",
+            ] {
+                let mut dst = MaybeUninit::<[ValRaw; 2]>::uninit();
+                s.lower(&mut store.as_context_mut(), &options, &mut dst)?;
+                let dst = unsafe { dst.assume_init() };
+                let ptr = usize::try_from(dst[0].get_u32())?;
+                let len = usize::try_from(dst[1].get_u32())?;
+
+                let memory = options.memory(store.as_context().0);
+                match options.string_encoding() {
+                    StringEncoding::Utf8 => {
+                        assert_eq!(len, s.len());
+                        assert_eq!(&memory[ptr..][..len], s.as_bytes());
+                    }
+                    StringEncoding::Utf16 => {
+                        let expected = s
+                            .encode_utf16()
+                            .flat_map(|u| u.to_le_bytes())
+                            .collect::<Vec<_>>();
+                        assert_eq!(len * 2, expected.len());
+                        assert_eq!(&memory[ptr..][..len * 2], expected);
+                    }
+                    StringEncoding::CompactUtf16 => unreachable!(),
+                }
+
+                let lifted = WasmStr::lift(store.as_context().0, &options, &dst)?;
+                assert_eq!(lifted.to_str(&store)?, s);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn raw_slices_match_typed_lowering() -> Result<()> {
+        type Record = (u8, String, Option<u32>, f64, Vec<u16>);
+
+        let (mut store, options) = store_and_options()?;
+        let value: Record = (1, "hello".to_string(), Some(2), 3.5, vec![4, 5, 6]);
+        let count = Record::flatten_count();
+        assert_eq!(count, 8);
+
+        let mut typed = MaybeUninit::<<Record as ComponentType>::Lower>::uninit();
+        value.lower(&mut store.as_context_mut(), &options, &mut typed)?;
+        let typed = unsafe { typed.assume_init() };
+        let typed_raws =
+            unsafe { std::slice::from_raw_parts((&typed as *const _).cast::<ValRaw>(), count) };
+
+        let mut raws = vec![MaybeUninit::<ValRaw>::uninit(); count];
+        value.lower_to_raws(&mut store.as_context_mut(), &options, &mut raws)?;
+        let raws = raws
+            .into_iter()
+            .map(|raw| unsafe { raw.assume_init() })
+            .collect::<Vec<_>>();
+
+        // Everything other than the pointers to the string and list, which
+        // are freshly allocated each time, is lowered identically.
+        for i in [0, 2, 3, 4, 7] {
+            assert_eq!(raws[i].get_u32(), typed_raws[i].get_u32(), "index {}", i);
+        }
+        assert_eq!(raws[5].get_f64(), typed_raws[5].get_f64());
+        assert_eq!(raws[2].get_u32(), 5);
+        assert_eq!(raws[7].get_u32(), 3);
+
+        // Lift the record back out as its lazy counterpart, which has the
+        // same lowered representation, both from the typed representation and
+        // from the slice.
+        type Lifted = (u8, WasmStr, Option<u32>, f64, WasmList<u16>);
+        let check = |(a, b, c, d, e): Lifted| -> Result<()> {
+            assert_eq!(a, value.0);
+            assert_eq!(b.to_str(&store)?, value.1);
+            assert_eq!(c, value.2);
+            assert_eq!(d, value.3);
+            assert_eq!(e.iter(&store).collect::<Result<Vec<_>>>()?, value.4);
+            Ok(())
+        };
+        let opaque = store.as_context().0;
+        check(Lifted::lift(opaque, &options, &typed)?)?;
+        check(Lifted::lift_from_raws(opaque, &options, &raws)?)?;
+
+        let err = Lifted::lift_from_raws(opaque, &options, &raws[1..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected 8 core wasm values to lift from, found 7"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lower_to_raws_wrong_length() -> Result<()> {
+        let (mut store, options) = store_and_options()?;
+        let mut raws = vec![MaybeUninit::<ValRaw>::uninit(); 3];
+        let err = (1u32, 2u64)
+            .lower_to_raws(&mut store.as_context_mut(), &options, &mut raws)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected 2 core wasm values to lower into, found 3"
+        );
+
+        // Types which don't lower to anything work with empty slices.
+        ().lower_to_raws(&mut store.as_context_mut(), &options, &mut [])?;
+        <()>::lift_from_raws(store.as_context().0, &options, &[])?;
+        Ok(())
+    }
+
+    #[test]
+    fn views_revalidate_bounds() -> Result<()> {
+        use std::ptr::NonNull;
+        use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+        use wasmtime_runtime::VMMemoryDefinition;
+
+        // Linear memory can't shrink, so simulate that, or the memory being
+        // replaced by a smaller one, with a memory definition of our own.
+        let (store, _) = store_and_options()?;
+        let mut buf = vec![0; 64];
+        buf[16..21].copy_from_slice(b"hello");
+        let def = VMMemoryDefinition {
+            base: buf.as_mut_ptr(),
+            current_length: AtomicUsize::new(buf.len()),
+        };
+        let options = unsafe {
+            Options::new(
+                store.as_context().0.id(),
+                NonNull::new(&def as *const VMMemoryDefinition as *mut VMMemoryDefinition),
+                None,
+                StringEncoding::Utf8,
+            )
+        };
+        let memory = Memory::new(store.as_context().0, &options);
+        let string = WasmStr::new(16, 5, &memory)?;
+        let list = WasmList::<u8>::new(16, 5, &memory)?;
+        let bools = WasmList::<bool>::new(16, 5, &memory)?;
+        assert_eq!(string.to_str(&store)?, "hello");
+        assert_eq!(list.as_le_slice(&store)?, b"hello");
+
+        // Views which are no longer in bounds are errors rather than panics.
+        def.current_length.store(18, Relaxed);
+        let check = |err: anyhow::Error, what| {
+            assert_eq!(
+                err.downcast_ref::<LiftError>(),
+                Some(&LiftError::OutOfBounds {
+                    what,
+                    ptr: 16,
+                    len: 5,
+                    memory_size: 18,
+                })
+            );
+        };
+        check(string.to_str(&store).unwrap_err(), "string");
+        check(string.as_str(&store).unwrap_err(), "string");
+        check(list.as_le_slice(&store).unwrap_err(), "list");
+        check(list.get(&store, 0).unwrap().unwrap_err(), "list");
+        check(list.get_range(&store, 0..1).unwrap_err(), "list");
+        check(list.iter(&store).next().unwrap().unwrap_err(), "list");
+        check(bools.to_bools(&store).unwrap_err(), "list");
+        check(bools.as_raw_bytes(&store).unwrap_err(), "list");
+
+        // Once memory is large enough again the views work again.
+        def.current_length.store(21, Relaxed);
+        assert_eq!(string.to_str(&store)?, "hello");
+        assert_eq!(list.get(&store, 4).unwrap()?, b'o');
+
+        Ok(())
+    }
+
+    #[test]
+    fn utf16_to_utf8_matches_std() {
+        // The straightforward implementation which `utf16_to_utf8` replaces.
+        fn reference(units: &[u16], lossy: bool) -> Result<String, LiftError> {
+            let mut result = String::new();
+            let mut position = 0;
+            for c in std::char::decode_utf16(units.iter().copied()) {
+                match c {
+                    Ok(c) => {
+                        result.push(c);
+                        position += c.len_utf16();
+                    }
+                    Err(_) if lossy => {
+                        result.push(char::REPLACEMENT_CHARACTER);
+                        position += 1;
+                    }
+                    Err(e) => {
+                        return Err(LiftError::InvalidUtf16 {
+                            unpaired_surrogate: e.unpaired_surrogate(),
+                            position,
+                            len: units.len(),
+                        })
+                    }
+                }
+            }
+            Ok(result)
+        }
+
+        fn check(units: &[u16]) {
+            // Also check an unaligned copy of the input.
+            let mut bytes = vec![0];
+            bytes.extend(units.iter().flat_map(|u| u.to_le_bytes()));
+            for lossy in [false, true] {
+                assert_eq!(
+                    utf16_to_utf8(&bytes[1..], lossy),
+                    reference(units, lossy),
+                    "{:x?}",
+                    units
+                );
+            }
+        }
+
+        // Code units around each boundary in the encodings, including lone
+        // and reversed surrogates.
+        let interesting = [
+            0x00, 0x41, 0x7f, 0x80, 0xff, 0x100, 0x7ff, 0x800, 0xd7ff, 0xd800, 0xdbff, 0xdc00,
+            0xdfff, 0xe000, 0xfffd, 0xffff,
+        ];
+        check(&[]);
+        for a in interesting {
+            check(&[a]);
+            for b in interesting {
+                check(&[a, b]);
+                check(&[a, b, a]);
+            }
+        }
+
+        // Runs of ascii of various lengths, so the fast path sees every
+        // alignment of the non-ascii units within a run.
+        for len in 0..12 {
+            for at in 0..=len {
+                for special in [0x80, 0xd83d, 0xde00, 0xffff] {
+                    let mut units = vec![u16::from(b'a'); len];
+                    units.insert(at, special);
+                    check(&units);
+                    units.insert(at + 1, 0xde00);
+                    check(&units);
+                }
+            }
+        }
+
+        // Pseudo-random mixes of the above.
+        let mut state = 1u32;
+        for _ in 0..1000 {
+            let units = (0..state % 37)
+                .map(|_| {
+                    state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                    match (state >> 16) % 4 {
+                        0 => interesting[(state >> 20) as usize % interesting.len()],
+                        _ => (state >> 24) as u16 & 0x7f,
+                    }
+                })
+                .collect::<Vec<_>>();
+            check(&units);
+            state = state.wrapping_add(units.len() as u32 + 1);
+        }
+    }
+}
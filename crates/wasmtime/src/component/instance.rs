@@ -1,4 +1,4 @@
-use crate::component::func::HostFunc;
+use crate::component::func::{HostFunc, TypecheckCache};
 use crate::component::{Component, ComponentParams, Func, Lift, Lower, TypedFunc};
 use crate::instance::OwnedImports;
 use crate::store::{StoreOpaque, Stored};
@@ -10,9 +10,9 @@ use std::sync::Arc;
 use wasmtime_environ::component::{
     AlwaysTrap, ComponentTypes, CoreDef, CoreExport, Export, ExportItem, ExtractMemory,
     ExtractPostReturn, ExtractRealloc, GlobalInitializer, InstantiateModule, LowerImport,
-    RuntimeImportIndex, RuntimeInstanceIndex, RuntimeModuleIndex,
+    RuntimeImportIndex, RuntimeInstanceIndex, RuntimeMemoryIndex, RuntimeModuleIndex,
 };
-use wasmtime_environ::{EntityIndex, Global, GlobalInit, PrimaryMap, WasmType};
+use wasmtime_environ::{EntityIndex, EntitySet, Global, GlobalInit, PrimaryMap, WasmType};
 use wasmtime_runtime::component::{ComponentInstance, OwnedComponentInstance};
 
 /// An instantiated component.
@@ -41,6 +41,14 @@ pub(crate) struct InstanceData {
     /// into the functions within the `OwnedComponentInstance` but it's our job
     /// to keep them alive.
     funcs: Vec<Arc<HostFunc>>,
+
+    /// Successful typechecks of this instance's exported functions, see
+    /// [`Func::typed`].
+    typecheck_cache: Arc<TypecheckCache>,
+
+    /// The memories used in canonical ABI options of this instance which are
+    /// shared memories, recorded as they're extracted during instantiation.
+    shared_memories: EntitySet<RuntimeMemoryIndex>,
 }
 
 impl Instance {
@@ -182,6 +190,16 @@ impl InstanceData {
     pub fn component_types(&self) -> &Arc<ComponentTypes> {
         self.component.types()
     }
+
+    pub fn typecheck_cache(&self) -> &Arc<TypecheckCache> {
+        &self.typecheck_cache
+    }
+
+    /// Returns whether the memory `idx` used in canonical ABI options is a
+    /// shared memory, which lifting and lowering don't support.
+    pub fn is_shared_memory(&self, idx: RuntimeMemoryIndex) -> bool {
+        self.shared_memories.contains(idx)
+    }
 }
 
 struct Instantiator<'a> {
@@ -216,6 +234,10 @@ impl<'a> Instantiator<'a> {
                 ),
                 state: OwnedComponentInstance::new(env_component, store.traitobj()),
                 funcs: Vec::new(),
+                typecheck_cache: Default::default(),
+                shared_memories: EntitySet::with_capacity(
+                    env_component.num_runtime_memories as usize,
+                ),
             },
         }
     }
@@ -258,8 +280,13 @@ impl<'a> Instantiator<'a> {
                     // validity of the component means that type-checks have
                     // already been performed. This maens that the unsafety due
                     // to imports having the wrong type should not happen here.
-                    let i =
-                        unsafe { crate::Instance::new_started(store, module, imports.as_ref())? };
+                    //
+                    // Also note that the asyncness of the store has already
+                    // been handled by `InstancePre`, so this may be running on
+                    // a fiber.
+                    let i = unsafe {
+                        crate::Instance::new_started_impl(store, module, imports.as_ref())?
+                    };
                     self.data.instances.push(i);
                 }
 
@@ -299,9 +326,13 @@ impl<'a> Instantiator<'a> {
             RuntimeImport::Func(func) => func,
             _ => unreachable!(),
         };
+        let shared_memory = import
+            .options
+            .memory
+            .map_or(false, |i| self.data.is_shared_memory(i));
         self.data.state.set_lowering(
             import.index,
-            func.lowering(),
+            func.lowering(shared_memory),
             self.component.lowering_ptr(import.index),
             self.component
                 .signatures()
@@ -335,6 +366,9 @@ impl<'a> Instantiator<'a> {
             wasmtime_runtime::Export::Memory(m) => m,
             _ => unreachable!(),
         };
+        if mem.memory.memory.shared {
+            self.data.shared_memories.insert(memory.index);
+        }
         self.data
             .state
             .set_runtime_memory(memory.index, mem.definition);
@@ -439,12 +473,51 @@ impl<T> InstancePre<T> {
     }
 
     /// Performs the instantiation process into the store specified.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called on a store with async support enabled, in
+    /// which case [`InstancePre::instantiate_async`] must be used instead.
     //
     // TODO: needs more docs
     pub fn instantiate(&self, mut store: impl AsContextMut<Data = T>) -> Result<Instance> {
         let mut store = store.as_context_mut();
+        assert!(
+            !store.0.async_support(),
+            "must use async instantiation when async support is enabled"
+        );
+        self.instantiate_impl(&mut store)
+    }
+
+    /// Same as [`InstancePre::instantiate`], except for use with stores that
+    /// have async support enabled.
+    ///
+    /// Instantiation runs on a fiber so any core wasm `start` functions, and
+    /// the host imports they call, are able to yield back to the caller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called on a store without async support enabled.
+    #[cfg(feature = "async")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "async")))]
+    pub async fn instantiate_async(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+    ) -> Result<Instance>
+    where
+        T: Send,
+    {
+        let mut store = store.as_context_mut();
+        assert!(
+            store.0.async_support(),
+            "must use sync instantiation when async support is disabled"
+        );
+        store.on_fiber(|store| self.instantiate_impl(store)).await?
+    }
+
+    fn instantiate_impl(&self, store: &mut StoreContextMut<'_, T>) -> Result<Instance> {
         let mut i = Instantiator::new(&self.component, store.0, &self.imports);
-        i.run(&mut store)?;
+        i.run(store)?;
         let data = Box::new(i.data);
         Ok(Instance(store.0.store_data_mut().insert(Some(data))))
     }
@@ -528,6 +601,7 @@ impl<'a, 'store> ExportInstance<'a, 'store> {
     pub fn func(&mut self, name: &str) -> Option<Func> {
         match self.exports.get(name)? {
             Export::LiftedFunction { ty, func, options } => Some(Func::from_lifted_func(
+                name,
                 self.store,
                 self.instance,
                 self.data,
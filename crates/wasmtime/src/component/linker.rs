@@ -1,8 +1,10 @@
 use crate::component::func::HostFunc;
 use crate::component::instance::RuntimeImport;
 use crate::component::matching::TypeChecker;
-use crate::component::{Component, Instance, InstancePre, IntoComponentFunc};
-use crate::{AsContextMut, Engine, Module};
+use crate::component::{
+    Component, ComponentParams, Instance, InstancePre, IntoComponentFunc, Lift, Lower,
+};
+use crate::{AsContextMut, Engine, Module, StoreContextMut};
 use anyhow::{anyhow, bail, Context, Result};
 use std::collections::hash_map::{Entry, HashMap};
 use std::marker;
@@ -97,6 +99,48 @@ impl<T> Linker<T> {
         self.root().into_instance(name)
     }
 
+    /// Defines a host function named `name` within the instance named
+    /// `instance`, creating that instance if it isn't already defined.
+    ///
+    /// This is a shorthand for defining functions of imported instances, for
+    /// example:
+    ///
+    /// ```ignore
+    /// linker.func_wrap("host", "log", |cx, (level, msg): (u32, String)| -> Result<()> {
+    ///     // ...
+    /// })?;
+    /// ```
+    ///
+    /// Unlike [`LinkerInstance::func_wrap`] the parameters from wasm are passed
+    /// to `func` as a single `Params` tuple after the leading
+    /// [`StoreContextMut`]. Strings and lists in the parameters are lifted out
+    /// of the caller's memory, and the `Return` value is lowered back into
+    /// it, according to the canonical options of the caller's `canon lower`.
+    /// The signature is checked against the component's import when it is
+    /// instantiated. Errors returned by `func` trap in the same way as
+    /// described for [`LinkerInstance::func_wrap`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `instance` is already defined as something other
+    /// than an instance, or if `name` is already defined within it and
+    /// shadowing isn't allowed.
+    pub fn func_wrap<Params, Return, F>(
+        &mut self,
+        instance: &str,
+        name: &str,
+        func: F,
+    ) -> Result<()>
+    where
+        F: Fn(StoreContextMut<'_, T>, Params) -> Result<Return> + Send + Sync + 'static,
+        Params: ComponentParams + Lift + 'static,
+        Return: Lower + 'static,
+    {
+        let mut instance = self.root().into_existing_instance(instance)?;
+        let name = instance.strings.intern(name);
+        instance.insert(name, Definition::Func(HostFunc::from_closure(func)))
+    }
+
     /// Performs a "pre-instantiation" to resolve the imports of the
     /// [`Component`] specified with the items defined within this linker.
     ///
@@ -188,6 +232,31 @@ impl<T> Linker<T> {
     ) -> Result<Instance> {
         self.instantiate_pre(component)?.instantiate(store)
     }
+
+    /// Instantiates the [`Component`] provided into the `store` specified.
+    ///
+    /// This is exactly like [`Linker::instantiate`] except for async stores.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this [`Linker`] doesn't define an import that
+    /// `component` requires or if it is of the wrong type. Additionally this
+    /// can return an error if something goes wrong during instantiation such as
+    /// a runtime trap or a runtime limit being exceeded.
+    #[cfg(feature = "async")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "async")))]
+    pub async fn instantiate_async(
+        &self,
+        store: impl AsContextMut<Data = T>,
+        component: &Component,
+    ) -> Result<Instance>
+    where
+        T: Send,
+    {
+        self.instantiate_pre(component)?
+            .instantiate_async(store)
+            .await
+    }
 }
 
 impl<T> LinkerInstance<'_, T> {
@@ -219,6 +288,19 @@ impl<T> LinkerInstance<'_, T> {
     /// type parameter from [`Store<T>`](crate::Store) which is accessible
     /// through the leading [`StoreContextMut<'_, T>`](crate::StoreContextMut)
     /// argument which can be provided to the `func` given here.
+    ///
+    /// # Errors and traps
+    ///
+    /// The `func` returns an [`anyhow::Result`] whose error doesn't become a
+    /// value visible to wasm. Instead it traps, unwinding the calling wasm,
+    /// and the call into the component fails with a [`Trap`](crate::Trap)
+    /// carrying the error. A panic in `func` similarly unwinds the calling wasm
+    /// and then resumes on the host.
+    ///
+    /// Errors which should be visible to wasm are instead part of the returned
+    /// value: for an import whose result is `expected<T, E>` the `func` returns
+    /// `Result<Result<T, E>>`. An `Ok(Err(e))` is lowered into the `expected`'s
+    /// error case like any other [`Result`] value while an `Err(_)` traps.
     //
     // TODO: needs more words and examples
     pub fn func_wrap<Params, Return>(
@@ -230,6 +312,42 @@ impl<T> LinkerInstance<'_, T> {
         self.insert(name, Definition::Func(func.into_host_func()))
     }
 
+    /// Defines a new host-provided async function into this [`Linker`].
+    ///
+    /// This is like [`LinkerInstance::func_wrap`] except that `func` produces
+    /// its result asynchronously. The parameters from wasm are passed as a
+    /// single `Params` tuple after the leading
+    /// [`StoreContextMut`](crate::StoreContextMut) argument. The returned
+    /// future is polled on the fiber of the wasm that called it, so the
+    /// embedder's executor is yielded to whenever it is pending.
+    ///
+    /// Components using async host functions must be instantiated with
+    /// [`Linker::instantiate_async`] and their exports invoked with
+    /// [`TypedFunc::call_async`](crate::component::TypedFunc::call_async).
+    ///
+    /// # Panics
+    ///
+    /// If the defined function is called from wasm in a store which doesn't
+    /// have async support enabled then a panic will happen.
+    #[cfg(feature = "async")]
+    #[cfg_attr(nightlydoc, doc(cfg(feature = "async")))]
+    pub fn func_wrap_async<Params, Return, F>(&mut self, name: &str, func: F) -> Result<()>
+    where
+        F: for<'a> Fn(
+                crate::StoreContextMut<'a, T>,
+                Params,
+            )
+                -> Box<dyn std::future::Future<Output = Result<Return>> + Send + 'a>
+            + Send
+            + Sync
+            + 'static,
+        Params: crate::component::ComponentParams + crate::component::Lift + 'static,
+        Return: crate::component::Lower + 'static,
+    {
+        let name = self.strings.intern(name);
+        self.insert(name, Definition::Func(HostFunc::new_async(func)))
+    }
+
     /// Defines a [`Module`] within this instance.
     ///
     /// This can be used to provide a core wasm [`Module`] as an import to a
@@ -271,6 +389,24 @@ impl<T> LinkerInstance<'_, T> {
         Ok(self)
     }
 
+    /// Like [`LinkerInstance::into_instance`] except that an instance named
+    /// `name` which is already defined is extended rather than replaced.
+    fn into_existing_instance(mut self, name: &str) -> Result<Self> {
+        let name = self.strings.intern(name);
+        let slot = self
+            .map
+            .entry(name)
+            .or_insert_with(|| Definition::Instance(NameMap::default()));
+        self.map = match slot {
+            Definition::Instance(map) => map,
+            _ => bail!(
+                "import of `{}` is already defined as something other than an instance",
+                self.strings.strings[name]
+            ),
+        };
+        Ok(self)
+    }
+
     fn insert(&mut self, key: usize, item: Definition) -> Result<()> {
         match self.map.entry(key) {
             Entry::Occupied(_) if !self.allow_shadowing => {
@@ -13,14 +13,16 @@ pub mod types;
 mod values;
 pub use self::component::Component;
 pub use self::func::{
-    ComponentParams, ComponentType, Func, IntoComponentFunc, Lift, Lower, TypedFunc, WasmList,
-    WasmStr,
+    CallBuffer, ComponentParams, ComponentType, Func, InstancePoisoned, IntoComponentFunc, Lift,
+    LiftError, Lower, LowerError, LowerList, Memory, MemoryMut, Options, ReturnValue, TypedFunc,
+    WasmList, WasmListDebug, WasmListIter, WasmStr, WasmStrCopy, WasmStrDisplay,
 };
 pub use self::instance::{ExportInstance, Exports, Instance, InstancePre};
 pub use self::linker::{Linker, LinkerInstance};
 pub use self::types::Type;
 pub use self::values::Val;
 pub use wasmtime_component_macro::{flags, ComponentType, Lift, Lower};
+pub use wasmtime_environ::component::StringEncoding;
 
 // These items are expected to be used by an eventual
 // `#[derive(ComponentType)]`, they are not part of Wasmtime's API stability
@@ -28,8 +30,10 @@ pub use wasmtime_component_macro::{flags, ComponentType, Lift, Lower};
 #[doc(hidden)]
 pub mod __internal {
     pub use super::func::{
-        align_to, format_flags, next_field, typecheck_enum, typecheck_flags, typecheck_record,
-        typecheck_union, typecheck_variant, MaybeUninitExt, Memory, MemoryMut, Options,
+        align_to, assert_pod_field, field_offsets, flag_names, format_flags, load_list,
+        load_pod_list, next_field, typecheck_enum, typecheck_flags, typecheck_record,
+        typecheck_record_with_field_order, typecheck_union, typecheck_variant,
+        validate_variant_padding, LiftError, MaybeUninitExt, Memory, MemoryMut, Options, PodField,
     };
     pub use crate::map_maybe_uninit;
     pub use crate::store::StoreOpaque;
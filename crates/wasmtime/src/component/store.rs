@@ -5,6 +5,19 @@ macro_rules! component_store_data {
         #[derive(Default)]
         pub struct ComponentStoreData {
             $($field: Vec<$t>,)*
+
+            /// Functions which have been called but whose `post-return` is
+            /// deferred until their instance is next entered, see
+            /// `Config::component_model_deferred_post_return`.
+            pub(crate) pending_post_returns: Vec<crate::component::Func>,
+
+            /// Functions whose `post-return` trapped, poisoning their
+            /// instance, along with the error returned by further calls into
+            /// that instance.
+            pub(crate) poisoned: Vec<(
+                crate::component::Func,
+                crate::component::InstancePoisoned,
+            )>,
         }
 
         $(
@@ -632,6 +632,203 @@ impl Type {
     }
 }
 
+/// Renders this type in the same WIT-like syntax as [`DisplayInterfaceType`],
+/// for example `list<record { name: string, age: u32 }>`.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (ty, types) = match self {
+            Type::Unit => (InterfaceType::Unit, None),
+            Type::Bool => (InterfaceType::Bool, None),
+            Type::S8 => (InterfaceType::S8, None),
+            Type::U8 => (InterfaceType::U8, None),
+            Type::S16 => (InterfaceType::S16, None),
+            Type::U16 => (InterfaceType::U16, None),
+            Type::S32 => (InterfaceType::S32, None),
+            Type::U32 => (InterfaceType::U32, None),
+            Type::S64 => (InterfaceType::S64, None),
+            Type::U64 => (InterfaceType::U64, None),
+            Type::Float32 => (InterfaceType::Float32, None),
+            Type::Float64 => (InterfaceType::Float64, None),
+            Type::Char => (InterfaceType::Char, None),
+            Type::String => (InterfaceType::String, None),
+            Type::List(handle) => (InterfaceType::List(handle.0.index), Some(&handle.0.types)),
+            Type::Record(handle) => (InterfaceType::Record(handle.0.index), Some(&handle.0.types)),
+            Type::Tuple(handle) => (InterfaceType::Tuple(handle.0.index), Some(&handle.0.types)),
+            Type::Variant(handle) => (
+                InterfaceType::Variant(handle.0.index),
+                Some(&handle.0.types),
+            ),
+            Type::Enum(handle) => (InterfaceType::Enum(handle.0.index), Some(&handle.0.types)),
+            Type::Union(handle) => (InterfaceType::Union(handle.0.index), Some(&handle.0.types)),
+            Type::Option(handle) => (InterfaceType::Option(handle.0.index), Some(&handle.0.types)),
+            Type::Expected(handle) => (
+                InterfaceType::Expected(handle.0.index),
+                Some(&handle.0.types),
+            ),
+            Type::Flags(handle) => (InterfaceType::Flags(handle.0.index), Some(&handle.0.types)),
+        };
+        match types {
+            Some(types) => write!(f, "{}", DisplayInterfaceType::new(&ty, types)),
+            None => f.write_str(func::desc(&ty)),
+        }
+    }
+}
+
+/// Renders an interface type in a WIT-like syntax, for example
+/// `list<record { name: string, age: u32 }>`.
+///
+/// This works directly on the type information of a compiled component, so it
+/// doesn't require an `Arc<ComponentTypes>`, and the `Display` implementation
+/// of [`Type`] delegates to it.
+///
+/// Rendering is bounded: composite types nested more than
+/// [`max_depth`](DisplayInterfaceType::max_depth) levels deep, as well as any
+/// type which refers back to a type enclosing it, are elided as `...`.
+pub struct DisplayInterfaceType<'a> {
+    ty: &'a InterfaceType,
+    types: &'a ComponentTypes,
+    max_depth: usize,
+}
+
+impl<'a> DisplayInterfaceType<'a> {
+    /// The number of nested composite types rendered by default.
+    pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+    /// Creates a printer for `ty`, whose type indices refer to `types`.
+    pub fn new(ty: &'a InterfaceType, types: &'a ComponentTypes) -> Self {
+        Self {
+            ty,
+            types,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Sets the number of nested composite types rendered before the rest are
+    /// elided as `...`.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Renders `ty`, where `stack` holds the composite types enclosing it.
+    fn render(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        ty: &InterfaceType,
+        stack: &mut Vec<InterfaceType>,
+    ) -> fmt::Result {
+        match ty {
+            InterfaceType::List(_)
+            | InterfaceType::Record(_)
+            | InterfaceType::Tuple(_)
+            | InterfaceType::Variant(_)
+            | InterfaceType::Enum(_)
+            | InterfaceType::Union(_)
+            | InterfaceType::Option(_)
+            | InterfaceType::Expected(_)
+            | InterfaceType::Flags(_) => {}
+            ty => return f.write_str(func::desc(ty)),
+        }
+
+        if stack.len() >= self.max_depth || stack.contains(ty) {
+            return f.write_str("...");
+        }
+
+        stack.push(*ty);
+        let result = self.render_composite(f, ty, stack);
+        stack.pop();
+        result
+    }
+
+    fn render_composite(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        ty: &InterfaceType,
+        stack: &mut Vec<InterfaceType>,
+    ) -> fmt::Result {
+        let types = self.types;
+        match ty {
+            InterfaceType::List(t) => {
+                f.write_str("list<")?;
+                self.render(f, &types[*t], stack)?;
+                f.write_str(">")
+            }
+            InterfaceType::Record(t) => {
+                f.write_str("record { ")?;
+                for (i, field) in types[*t].fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: ", field.name)?;
+                    self.render(f, &field.ty, stack)?;
+                }
+                f.write_str(" }")
+            }
+            InterfaceType::Tuple(t) => {
+                f.write_str("tuple<")?;
+                self.render_list(f, &types[*t].types, stack)?;
+                f.write_str(">")
+            }
+            InterfaceType::Variant(t) => {
+                f.write_str("variant { ")?;
+                for (i, case) in types[*t].cases.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str(&case.name)?;
+                    if case.ty != InterfaceType::Unit {
+                        f.write_str("(")?;
+                        self.render(f, &case.ty, stack)?;
+                        f.write_str(")")?;
+                    }
+                }
+                f.write_str(" }")
+            }
+            InterfaceType::Enum(t) => write!(f, "enum {{ {} }}", types[*t].names.join(", ")),
+            InterfaceType::Union(t) => {
+                f.write_str("union { ")?;
+                self.render_list(f, &types[*t].types, stack)?;
+                f.write_str(" }")
+            }
+            InterfaceType::Option(t) => {
+                f.write_str("option<")?;
+                self.render(f, &types[*t], stack)?;
+                f.write_str(">")
+            }
+            InterfaceType::Expected(t) => {
+                let expected = &types[*t];
+                f.write_str("expected<")?;
+                self.render_list(f, &[expected.ok, expected.err], stack)?;
+                f.write_str(">")
+            }
+            InterfaceType::Flags(t) => write!(f, "flags {{ {} }}", types[*t].names.join(", ")),
+            ty => f.write_str(func::desc(ty)),
+        }
+    }
+
+    /// Renders `tys` separated by commas.
+    fn render_list(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        tys: &[InterfaceType],
+        stack: &mut Vec<InterfaceType>,
+    ) -> fmt::Result {
+        for (i, ty) in tys.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            self.render(f, ty, stack)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DisplayInterfaceType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f, self.ty, &mut Vec::new())
+    }
+}
+
 fn record_size_and_alignment(types: impl Iterator<Item = Type>) -> SizeAndAlignment {
     let mut offset = 0;
     let mut align = 1;
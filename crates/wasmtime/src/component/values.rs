@@ -1,4 +1,4 @@
-use crate::component::func::{self, Lift, Lower, Memory, MemoryMut, Options};
+use crate::component::func::{self, Lift, LiftError, Lower, Memory, MemoryMut, Options};
 use crate::component::types::{self, SizeAndAlignment, Type};
 use crate::store::StoreOpaque;
 use crate::{AsContextMut, StoreContextMut, ValRaw};
@@ -773,21 +773,39 @@ fn load_list(handle: &types::List, mem: &Memory, ptr: usize, len: usize) -> Resu
         alignment: element_alignment,
     } = element_type.size_and_alignment();
 
+    mem.check_list_limit(len)?;
     match len
         .checked_mul(element_size)
         .and_then(|len| ptr.checked_add(len))
     {
         Some(n) if n <= mem.as_slice().len() => {}
-        _ => bail!("list pointer/length out of bounds of memory"),
+        _ => {
+            return Err(LiftError::OutOfBounds {
+                what: "list",
+                ptr,
+                len,
+                memory_size: mem.as_slice().len(),
+            }
+            .into())
+        }
     }
     if ptr % usize::try_from(element_alignment)? != 0 {
-        bail!("list pointer is not aligned")
+        return Err(LiftError::Misaligned {
+            what: "list",
+            ptr,
+            align: element_alignment,
+        }
+        .into());
     }
 
+    let chunk_len = mem.elements_between_checks(element_size);
     Ok(Val::List(List {
         ty: handle.clone(),
         values: (0..len)
             .map(|index| {
+                if index > 0 && index % chunk_len == 0 {
+                    mem.check_interrupt()?;
+                }
                 Val::load(
                     &element_type,
                     mem,
@@ -874,16 +892,26 @@ fn lower_list<T>(
         size: element_size,
         alignment: element_alignment,
     } = element_type.size_and_alignment();
-    let size = items
-        .len()
-        .checked_mul(element_size)
-        .ok_or_else(|| anyhow::anyhow!("size overflow copying a list"))?;
+    let size = func::lowered_size(
+        || format!("list<{}>", element_type),
+        items.len(),
+        element_size,
+    )?;
     let ptr = mem.realloc(0, 0, element_alignment, size)?;
-    let mut element_ptr = ptr;
-    for item in items {
-        item.store(mem, element_ptr)?;
-        element_ptr += element_size;
-    }
+    let chunk_len = mem.elements_between_checks(element_size);
+    mem.free_on_error(ptr, size, element_alignment, |mem| {
+        let mut element_ptr = ptr;
+        for (i, chunk) in items.chunks(chunk_len).enumerate() {
+            if i > 0 {
+                mem.check_interrupt()?;
+            }
+            for item in chunk {
+                item.store(mem, element_ptr)?;
+                element_ptr += element_size;
+            }
+        }
+        Ok(())
+    })?;
     Ok((ptr, items.len()))
 }
 
@@ -106,6 +106,18 @@ pub struct Config {
     pub(crate) memory_init_cow: bool,
     pub(crate) memory_guaranteed_dense_image_size: u64,
     pub(crate) force_memory_init_memfd: bool,
+    #[cfg(feature = "component-model")]
+    pub(crate) component_model_strict_validation: bool,
+    #[cfg(feature = "component-model")]
+    pub(crate) component_model_max_lifted_string_bytes: usize,
+    #[cfg(feature = "component-model")]
+    pub(crate) component_model_max_lifted_list_elements: usize,
+    #[cfg(feature = "component-model")]
+    pub(crate) component_model_deferred_post_return: bool,
+    #[cfg(feature = "component-model")]
+    pub(crate) component_model_lossy_chars: bool,
+    #[cfg(feature = "component-model")]
+    pub(crate) component_model_copy_check_interval: usize,
 }
 
 /// User-provided configuration for the compiler.
@@ -189,6 +201,18 @@ impl Config {
             memory_init_cow: true,
             memory_guaranteed_dense_image_size: 16 << 20,
             force_memory_init_memfd: false,
+            #[cfg(feature = "component-model")]
+            component_model_strict_validation: false,
+            #[cfg(feature = "component-model")]
+            component_model_max_lifted_string_bytes: usize::MAX,
+            #[cfg(feature = "component-model")]
+            component_model_max_lifted_list_elements: usize::MAX,
+            #[cfg(feature = "component-model")]
+            component_model_deferred_post_return: false,
+            #[cfg(feature = "component-model")]
+            component_model_lossy_chars: false,
+            #[cfg(feature = "component-model")]
+            component_model_copy_check_interval: 1 << 20,
         };
         #[cfg(compiler)]
         {
@@ -712,6 +736,149 @@ impl Config {
         self
     }
 
+    /// Configures whether values lifted from WebAssembly components are
+    /// strictly validated against the canonical ABI.
+    ///
+    /// By default the canonical ABI is interpreted permissively, per
+    /// [WebAssembly/component-model#35], where bytes which don't contribute
+    /// to a value are ignored. For example the padding between an `option`'s
+    /// discriminant and its payload, or the payload area of a `none`, may
+    /// contain any bytes.
+    ///
+    /// When this is enabled such ignored bytes in linear memory are instead
+    /// required to be zero when lifting `option`, `expected`, and `variant`
+    /// values, and an error describing the offending offset is returned
+    /// otherwise. Additionally `bool` values are required to be either 0 or
+    /// 1 rather than any nonzero value being interpreted as `true`, and a
+    /// `list<tuple<K, V>>` lifted as a map such as `HashMap<K, V>` can't
+    /// contain the same key more than once. This is intended for conformance
+    /// testing of guests and catching buggy guests early.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [WebAssembly/component-model#35]: https://github.com/WebAssembly/component-model/issues/35
+    #[cfg(feature = "component-model")]
+    pub fn component_model_strict_validation(&mut self, enable: bool) -> &mut Self {
+        self.component_model_strict_validation = enable;
+        self
+    }
+
+    /// Configures whether invalid `char` values lifted from WebAssembly
+    /// components are replaced with U+FFFD (the replacement character).
+    ///
+    /// By default a `char` which isn't a Unicode scalar value, such as a
+    /// surrogate code point or a value larger than 0x10ffff, is rejected with
+    /// an error when it's lifted, which fails the whole call. When this is
+    /// enabled such values are instead lifted as
+    /// [`char::REPLACEMENT_CHARACTER`], which can be useful for hosts that
+    /// would rather tolerate buggy guests, for example when collecting
+    /// telemetry. Strings can be decoded lossily on a case-by-case basis with
+    /// [`WasmStr::to_string_lossy`](crate::component::WasmStr::to_string_lossy).
+    ///
+    /// This is independent of
+    /// [`Config::component_model_strict_validation`], and is `false` by
+    /// default.
+    #[cfg(feature = "component-model")]
+    pub fn component_model_lossy_chars(&mut self, enable: bool) -> &mut Self {
+        self.component_model_lossy_chars = enable;
+        self
+    }
+
+    /// Configures the maximum size, in bytes of linear memory, of strings
+    /// lifted from WebAssembly components.
+    ///
+    /// A component can return a string whose length spans nearly all of its
+    /// linear memory, and converting that to a host `String` would allocate
+    /// just as much on the host. With this limit such strings are rejected
+    /// with an error when they're lifted, before anything is allocated. This
+    /// applies to [`WasmStr`](crate::component::WasmStr) as well as to owned
+    /// strings such as `String`.
+    ///
+    /// For utf-16 strings the size is twice the number of code units.
+    ///
+    /// This is unlimited by default.
+    #[cfg(feature = "component-model")]
+    pub fn component_model_max_lifted_string_bytes(&mut self, max: usize) -> &mut Self {
+        self.component_model_max_lifted_string_bytes = max;
+        self
+    }
+
+    /// Configures the maximum number of elements of lists lifted from
+    /// WebAssembly components.
+    ///
+    /// Like [`Config::component_model_max_lifted_string_bytes`] this guards
+    /// the host against allocating large amounts of memory when collecting
+    /// lists returned by a component into, for example, a `Vec<T>`. Lists
+    /// with more elements than this are rejected with an error when they're
+    /// lifted, before anything is allocated. This applies to
+    /// [`WasmList`](crate::component::WasmList) as well as to owned lists
+    /// such as `Vec<T>`.
+    ///
+    /// This is unlimited by default.
+    #[cfg(feature = "component-model")]
+    pub fn component_model_max_lifted_list_elements(&mut self, max: usize) -> &mut Self {
+        self.component_model_max_lifted_list_elements = max;
+        self
+    }
+
+    /// Configures whether the `post-return` of component functions is run
+    /// automatically.
+    ///
+    /// By default after a successful
+    /// [`TypedFunc::call`](crate::component::TypedFunc::call) or
+    /// [`Func::call`](crate::component::Func::call) the embedder must invoke
+    /// `post_return` before the component instance can be entered again, and
+    /// calling into the instance before that is an error.
+    ///
+    /// When this is enabled the pending `post-return` is instead recorded in
+    /// the [`Store`](crate::Store) and run right before the next call into the
+    /// same component instance, or when
+    /// [`Store::flush_post_returns`](crate::Store::flush_post_returns) is
+    /// called. The component still observes its `post-return` before it's
+    /// entered again. Calling `post_return` explicitly continues to work, but
+    /// it must happen before the deferred `post-return` has been run.
+    ///
+    /// Note that values borrowing from the component's linear memory, such as
+    /// [`WasmStr`](crate::component::WasmStr) and
+    /// [`WasmList`](crate::component::WasmList), should be copied out before
+    /// the instance is called again since the `post-return` may free them.
+    ///
+    /// This is disabled by default.
+    #[cfg(feature = "component-model")]
+    pub fn component_model_deferred_post_return(&mut self, enable: bool) -> &mut Self {
+        self.component_model_deferred_post_return = enable;
+        self
+    }
+
+    /// Configures how many bytes of lists and strings are copied between
+    /// checks for interruption when lifting and lowering component values.
+    ///
+    /// Copying a large list or string between the host and a component's
+    /// linear memory is a loop on the host which isn't covered by the checks
+    /// in compiled WebAssembly. When [`Config::epoch_interruption`] or
+    /// [`Config::consume_fuel`] is enabled these copies instead check the
+    /// store every this many bytes, and each check consumes one unit of fuel.
+    /// When lowering, a reached epoch deadline or running out of fuel is
+    /// handled as configured for the [`Store`](crate::Store), for example by
+    /// trapping or by invoking the deadline callback. Lifted values may borrow
+    /// linear memory, so lifting can't invoke callbacks or yield and instead
+    /// always traps when the epoch deadline is reached or fuel runs out.
+    ///
+    /// Copies no larger than this interval aren't checked at all, and when
+    /// neither epochs nor fuel are enabled no checks are made.
+    ///
+    /// This is 1 MiB by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is zero.
+    #[cfg(feature = "component-model")]
+    pub fn component_model_copy_check_interval(&mut self, bytes: usize) -> &mut Self {
+        assert!(bytes > 0, "copy check interval must be non-zero");
+        self.component_model_copy_check_interval = bytes;
+        self
+    }
+
     /// Configures which compilation strategy will be used for wasm modules.
     ///
     /// This method can be used to configure which compiler is used for wasm
@@ -1227,6 +1227,9 @@ pub(crate) fn invoke_wasm_and_catch_traps<T>(
     closure: impl FnMut(*mut VMContext),
 ) -> Result<(), Trap> {
     unsafe {
+        // Fuel consumed by the host while it only had a shared borrow of the
+        // store has to be accounted for before WebAssembly consumes more.
+        store.0.flush_shared_fuel_consumed();
         let exit = enter_wasm(store);
 
         if let Err(trap) = store.0.call_hook(CallHook::CallingWasm) {
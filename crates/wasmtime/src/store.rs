@@ -90,7 +90,7 @@ use std::mem::{self, ManuallyDrop};
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
 use std::ptr;
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use wasmtime_runtime::{
@@ -297,6 +297,11 @@ pub struct StoreOpaque {
     #[cfg(feature = "async")]
     async_state: AsyncState,
     out_of_gas_behavior: OutOfGas,
+    /// Fuel consumed by host code which only has a shared borrow of this
+    /// store, see `StoreOpaque::check_interrupt_shared`, which is added to the
+    /// fuel consumed in `runtime_limits` once the store is next borrowed
+    /// exclusively.
+    shared_fuel_consumed: AtomicU64,
     /// Indexed data within this `Store`, used to store information about
     /// globals, functions, memories, etc.
     ///
@@ -492,6 +497,7 @@ impl<T> Store<T> {
                     current_poll_cx: UnsafeCell::new(ptr::null_mut()),
                 },
                 out_of_gas_behavior: OutOfGas::Trap,
+                shared_fuel_consumed: AtomicU64::new(0),
                 store_data: ManuallyDrop::new(StoreData::new()),
                 default_callee,
                 hostcall_val_storage: Vec::new(),
@@ -741,6 +747,27 @@ impl<T> Store<T> {
         self.inner.gc()
     }
 
+    /// Runs all `post-return` functions of component functions which were
+    /// deferred with
+    /// [`Config::component_model_deferred_post_return`](crate::Config::component_model_deferred_post_return).
+    ///
+    /// This is not required, as deferred `post-return`s are run automatically
+    /// before their instance is next entered, but can be used to release the
+    /// resources held by the guest for returned values eagerly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any `post-return` traps, in which case the
+    /// `post-return`s which weren't run yet remain deferred.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called on a store with async support enabled.
+    #[cfg(feature = "component-model")]
+    pub fn flush_post_returns(&mut self) -> Result<()> {
+        crate::component::Func::flush_post_returns(crate::AsContextMut::as_context_mut(self))
+    }
+
     /// Returns the amount of fuel consumed by this store's execution so far.
     ///
     /// If fuel consumption is not enabled via
@@ -1331,7 +1358,19 @@ impl StoreOpaque {
             return None;
         }
         let consumed = unsafe { *self.runtime_limits.fuel_consumed.get() };
-        Some(u64::try_from(self.fuel_adj + consumed).unwrap())
+        let shared = self.shared_fuel_consumed.load(Relaxed) as i64;
+        Some(u64::try_from(self.fuel_adj + consumed + shared).unwrap())
+    }
+
+    /// Adds the fuel consumed by host code with only a shared borrow of this
+    /// store, see `StoreOpaque::check_interrupt_shared`, to the fuel consumed
+    /// in `runtime_limits` which WebAssembly itself updates.
+    pub(crate) fn flush_shared_fuel_consumed(&mut self) {
+        let shared = mem::take(self.shared_fuel_consumed.get_mut());
+        if shared > 0 {
+            let consumed = self.runtime_limits.fuel_consumed.get_mut();
+            *consumed = consumed.saturating_add(shared as i64);
+        }
     }
 
     fn out_of_fuel_trap(&mut self) {
@@ -1399,6 +1438,7 @@ impl StoreOpaque {
             self.engine().config().tunables.consume_fuel,
             "fuel is not configured in this store"
         );
+        self.flush_shared_fuel_consumed();
 
         // Fuel is stored as an i64, so we need to cast it. If the provided fuel
         // value overflows that just assume that i64::max will suffice. Wasm
@@ -1429,6 +1469,7 @@ impl StoreOpaque {
     }
 
     fn consume_fuel(&mut self, fuel: u64) -> Result<u64> {
+        self.flush_shared_fuel_consumed();
         let consumed_ptr = unsafe { &mut *self.runtime_limits.fuel_consumed.get() };
         match i64::try_from(fuel)
             .ok()
@@ -1448,6 +1489,41 @@ impl StoreOpaque {
         Some(&**handler as *const _)
     }
 
+    /// Checks for interruption during a long-running host operation which
+    /// only has a shared borrow of the store, such as lifting a large value
+    /// out of a component.
+    ///
+    /// This is the counterpart of `StoreInner::check_interrupt`, but without
+    /// an exclusive borrow the store can't invoke a deadline callback, yield,
+    /// or inject more fuel. Reaching the epoch deadline therefore always
+    /// traps here, regardless of how the store is configured to handle it, as
+    /// does running out of fuel. Fuel consumed here is recorded separately and
+    /// added to the fuel consumed by WebAssembly before it next runs.
+    #[cfg(feature = "component-model")]
+    pub(crate) fn check_interrupt_shared(&self) -> Result<()> {
+        let tunables = &self.engine.config().tunables;
+        if tunables.epoch_interruption {
+            // Safety: the deadline is only modified with an exclusive borrow
+            // of the store, so reading it with a shared borrow doesn't race.
+            let deadline = unsafe { *self.runtime_limits.epoch_deadline.get() };
+            if self.engine.current_epoch() >= deadline {
+                let trap = Trap::new_wasm(wasmtime_environ::TrapCode::Interrupt, None);
+                return Err(trap.into());
+            }
+        }
+        if tunables.consume_fuel {
+            // As above the fuel consumed by WebAssembly is only modified with
+            // an exclusive borrow, and the fuel consumed here is atomic since
+            // other threads may share this borrow.
+            let consumed = unsafe { *self.runtime_limits.fuel_consumed.get() };
+            let shared = self.shared_fuel_consumed.fetch_add(1, Relaxed) + 1;
+            if consumed.saturating_add(shared as i64) >= 0 {
+                return Err(anyhow::Error::new(OutOfGasError));
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn vmruntime_limits(&self) -> *mut VMRuntimeLimits {
         &self.runtime_limits as *const VMRuntimeLimits as *mut VMRuntimeLimits
@@ -1895,7 +1971,7 @@ unsafe impl<T> wasmtime_runtime::Store for StoreInner<T> {
     }
 
     fn out_of_gas(&mut self) -> Result<(), anyhow::Error> {
-        return match &mut self.out_of_gas_behavior {
+        match &mut self.out_of_gas_behavior {
             OutOfGas::Trap => Err(anyhow::Error::new(OutOfGasError)),
             #[cfg(feature = "async")]
             OutOfGas::InjectFuel {
@@ -1915,18 +1991,7 @@ unsafe impl<T> wasmtime_runtime::Store for StoreInner<T> {
             }
             #[cfg(not(feature = "async"))]
             OutOfGas::InjectFuel { .. } => unreachable!(),
-        };
-
-        #[derive(Debug)]
-        struct OutOfGasError;
-
-        impl fmt::Display for OutOfGasError {
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                f.write_str("all fuel consumed by WebAssembly")
-            }
         }
-
-        impl std::error::Error for OutOfGasError {}
     }
 
     fn new_epoch(&mut self) -> Result<u64, anyhow::Error> {
@@ -1997,6 +2062,37 @@ impl<T> StoreInner<T> {
         drop(delta); // suppress warning in non-async build
     }
 
+    /// Checks for interruption during a long-running host operation, such as
+    /// lowering a large list into a component, in the same way that
+    /// WebAssembly does.
+    ///
+    /// If epoch interruption is enabled and the deadline has been reached then
+    /// it's handled as configured for this store, for example by trapping or
+    /// by invoking the deadline callback. If fuel is enabled then one unit of
+    /// fuel is consumed, with the configured behavior when it runs out.
+    #[cfg(feature = "component-model")]
+    pub(crate) fn check_interrupt(&mut self) -> Result<()> {
+        let tunables = &self.engine().config().tunables;
+        let (epoch_interruption, consume_fuel) =
+            (tunables.epoch_interruption, tunables.consume_fuel);
+        if epoch_interruption && self.engine().current_epoch() >= self.get_epoch_deadline() {
+            wasmtime_runtime::Store::new_epoch(self)?;
+        }
+        if consume_fuel {
+            self.flush_shared_fuel_consumed();
+            // Safety: as in `get_epoch_deadline` we have an exclusive borrow
+            // of the store here.
+            let consumed = unsafe { &mut *self.runtime_limits.fuel_consumed.get() };
+            *consumed += 1;
+            // Like WebAssembly this runs out of fuel once the consumed value,
+            // which is otherwise negative, reaches zero.
+            if *consumed >= 0 {
+                wasmtime_runtime::Store::out_of_gas(self)?;
+            }
+        }
+        Ok(())
+    }
+
     fn get_epoch_deadline(&self) -> u64 {
         // Safety: this is safe because, as above, it is only invoked
         // from within `new_epoch` which is called from guest Wasm
@@ -2006,6 +2102,18 @@ impl<T> StoreInner<T> {
     }
 }
 
+/// The error returned when a store runs out of fuel.
+#[derive(Debug)]
+struct OutOfGasError;
+
+impl fmt::Display for OutOfGasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("all fuel consumed by WebAssembly")
+    }
+}
+
+impl std::error::Error for OutOfGasError {}
+
 impl<T: Default> Default for Store<T> {
     fn default() -> Store<T> {
         Store::new(&Engine::default(), T::default())
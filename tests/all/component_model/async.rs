@@ -0,0 +1,90 @@
+use anyhow::Result;
+use wasmtime::component::*;
+use wasmtime::{Config, Engine, Store, StoreContextMut};
+
+fn async_engine() -> Engine {
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    config.async_support(true);
+    Engine::new(&config).unwrap()
+}
+
+#[tokio::test]
+async fn call_async_with_async_import() -> Result<()> {
+    let component = r#"
+        (component
+            (import "yield" (func $yield (param u32) (result u32)))
+
+            (core func $yield_lower (canon lower (func $yield)))
+            (core module $m
+                (import "host" "yield" (func $yield (param i32) (result i32)))
+
+                (func (export "run") (param i32) (result i32)
+                    local.get 0
+                    call $yield
+                    i32.const 1
+                    i32.add)
+            )
+            (core instance $i (instantiate $m
+                (with "host" (instance (export "yield" (func $yield_lower))))
+            ))
+            (func (export "run") (param u32) (result u32)
+                (canon lift (core func $i "run"))
+            )
+        )
+    "#;
+
+    let engine = async_engine();
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap_async(
+        "yield",
+        |mut store: StoreContextMut<'_, u32>, (arg,): (u32,)| {
+            Box::new(async move {
+                tokio::task::yield_now().await;
+                *store.data_mut() += 1;
+                Ok(arg * 2)
+            })
+        },
+    )?;
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, 0);
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let run = instance.get_typed_func::<(u32,), u32, _>(&mut store, "run")?;
+
+    for i in 0..3 {
+        let result = run.call_async(&mut store, (i,)).await?;
+        run.post_return_async(&mut store).await?;
+        assert_eq!(result, i * 2 + 1);
+    }
+    assert_eq!(*store.data(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[should_panic(expected = "must use `call_async` when async support is enabled")]
+async fn sync_call_with_async_store_panics() {
+    let component = r#"
+        (component
+            (core module $m
+                (func (export "run"))
+            )
+            (core instance $i (instantiate $m))
+            (func (export "run")
+                (canon lift (core func $i "run"))
+            )
+        )
+    "#;
+
+    let engine = async_engine();
+    let component = Component::new(&engine, component).unwrap();
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine)
+        .instantiate_async(&mut store, &component)
+        .await
+        .unwrap();
+    let run = instance
+        .get_typed_func::<(), (), _>(&mut store, "run")
+        .unwrap();
+    let _ = run.call(&mut store, ());
+}
@@ -509,3 +509,70 @@ fn everything() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn signature_reflection() -> Result<()> {
+    let engine = super::engine();
+    let mut store = Store::new(&engine, ());
+
+    let component = Component::new(
+        &engine,
+        r#"
+        (component
+            (core module $m
+                (func (export "f") (param i32 i32 i32 i32) (result i32)
+                    unreachable)
+
+                (memory (export "memory") 1)
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    unreachable)
+            )
+
+            (core instance $i (instantiate $m))
+
+            (type $person (record (field "name" string) (field "age" u32)))
+            (type $people (list $person))
+            (type $perms (flags "read" "write"))
+            (type $result (option (tuple u32 string)))
+
+            (func (export "f")
+                (param "people" $people)
+                (param "perms" $perms)
+                (param u8)
+                (result $result)
+                (canon lift
+                    (core func $i "f")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#,
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "f").unwrap();
+
+    let params = func
+        .named_params(&store)
+        .iter()
+        .map(|(name, ty)| match name {
+            Some(name) => format!("{}: {}", name, ty),
+            None => ty.to_string(),
+        })
+        .collect::<Vec<_>>();
+    let signature = format!("({}) -> {}", params.join(", "), func.result(&store));
+
+    assert_eq!(
+        signature,
+        "(people: list<record { name: string, age: u32 }>, perms: flags { read, write }, u8) \
+         -> option<tuple<u32, string>>"
+    );
+    assert_eq!(
+        func.params(&store).to_vec(),
+        func.named_params(&store)
+            .iter()
+            .map(|(_, ty)| ty.clone())
+            .collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
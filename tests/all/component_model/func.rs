@@ -1,9 +1,9 @@
-use super::{TypedFuncExt, REALLOC_AND_FREE};
+use super::REALLOC_AND_FREE;
 use anyhow::Result;
 use std::rc::Rc;
 use std::sync::Arc;
 use wasmtime::component::*;
-use wasmtime::{Store, StoreContextMut, Trap, TrapCode};
+use wasmtime::{AsContextMut, Store, StoreContextMut, Trap, TrapCode};
 
 const CANON_32BIT_NAN: u32 = 0b01111111110000000000000000000000;
 const CANON_64BIT_NAN: u64 = 0b0111111111111000000000000000000000000000000000000000000000000000;
@@ -615,19 +615,40 @@ fn chars() -> Result<()> {
     let err = u32_to_char(&mut store)?
         .call(&mut store, (0xd800,))
         .unwrap_err();
-    assert!(err.to_string().contains("integer out of range"), "{}", err);
+    assert!(
+        err.to_string()
+            .contains("invalid char value 0xd800 lifted from the stack: surrogate code points are not valid chars"),
+        "{}",
+        err
+    );
     let err = u32_to_char(&mut store)?
         .call(&mut store, (0xdfff,))
         .unwrap_err();
-    assert!(err.to_string().contains("integer out of range"), "{}", err);
+    assert!(
+        err.to_string()
+            .contains("invalid char value 0xdfff lifted from the stack: surrogate"),
+        "{}",
+        err
+    );
     let err = u32_to_char(&mut store)?
         .call(&mut store, (0x110000,))
         .unwrap_err();
-    assert!(err.to_string().contains("integer out of range"), "{}", err);
+    assert!(
+        err.to_string().contains(
+            "invalid char value 0x110000 lifted from the stack: value is larger than 0x10ffff"
+        ),
+        "{}",
+        err
+    );
     let err = u32_to_char(&mut store)?
         .call(&mut store, (u32::MAX,))
         .unwrap_err();
-    assert!(err.to_string().contains("integer out of range"), "{}", err);
+    assert!(
+        err.to_string()
+            .contains("invalid char value 0xffffffff lifted from the stack"),
+        "{}",
+        err
+    );
 
     Ok(())
 }
@@ -680,7 +701,10 @@ fn tuple_result() -> Result<()> {
         instance.get_typed_func::<(), (i8, u16, f32, f64), _>(&mut store, "invalid")?;
     let err = invalid_func.call(&mut store, ()).err().unwrap();
     assert!(
-        err.to_string().contains("pointer out of bounds of memory"),
+        err.to_string().contains(
+            "return pointer/length out of bounds of memory \
+             (ptr 0xfffffff8, len 1, memory size 65536)"
+        ),
         "{}",
         err
     );
@@ -688,6 +712,249 @@ fn tuple_result() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn tuple_result_flat() -> Result<()> {
+    let component = r#"
+        (component
+            (core module $m
+                (func (export "foo") (param i32) (result i32)
+                    (i32.add (local.get 0) (i32.const 1))
+                )
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "tuple") (param u32) (result (tuple u32 unit))
+                (canon lift (core func $i "foo"))
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    // A tuple which flattens to a single core wasm value is returned directly
+    // rather than through a return pointer, so no memory is needed.
+    let func = instance.get_typed_func::<(u32,), (u32, ()), _>(&mut store, "tuple")?;
+    assert_eq!(func.call_and_post_return(&mut store, (41,))?, (42, ()));
+
+    let err = instance
+        .get_typed_func::<(u32,), (u32, u32), _>(&mut store, "tuple")
+        .err()
+        .unwrap();
+    assert!(
+        format!("{:?}", err).contains("expected `u32` found `unit`"),
+        "{:?}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn large_tuples() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "make") (param i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 80)))
+                    (i32.store offset=0 (local.get $base) (i32.add (local.get 0) (i32.const 0)))
+                    (i32.store offset=4 (local.get $base) (i32.add (local.get 0) (i32.const 1)))
+                    (i32.store offset=8 (local.get $base) (i32.add (local.get 0) (i32.const 2)))
+                    (i32.store offset=12 (local.get $base) (i32.add (local.get 0) (i32.const 3)))
+                    (i32.store offset=16 (local.get $base) (i32.add (local.get 0) (i32.const 4)))
+                    (i32.store offset=20 (local.get $base) (i32.add (local.get 0) (i32.const 5)))
+                    (i32.store offset=24 (local.get $base) (i32.add (local.get 0) (i32.const 6)))
+                    (i32.store offset=28 (local.get $base) (i32.add (local.get 0) (i32.const 7)))
+                    (i32.store offset=32 (local.get $base) (i32.add (local.get 0) (i32.const 8)))
+                    (i32.store offset=36 (local.get $base) (i32.add (local.get 0) (i32.const 9)))
+                    (i32.store offset=40 (local.get $base) (i32.add (local.get 0) (i32.const 10)))
+                    (i32.store offset=44 (local.get $base) (i32.add (local.get 0) (i32.const 11)))
+                    (i32.store offset=48 (local.get $base) (i32.add (local.get 0) (i32.const 12)))
+                    (i32.store offset=52 (local.get $base) (i32.add (local.get 0) (i32.const 13)))
+                    (i32.store offset=56 (local.get $base) (i32.add (local.get 0) (i32.const 14)))
+                    (i32.store offset=60 (local.get $base) (i32.add (local.get 0) (i32.const 15)))
+                    (i32.store offset=64 (local.get $base) (i32.add (local.get 0) (i32.const 16)))
+                    (i32.store offset=68 (local.get $base) (i32.add (local.get 0) (i32.const 17)))
+                    (i32.store offset=72 (local.get $base) (i32.add (local.get 0) (i32.const 18)))
+                    (i32.store offset=76 (local.get $base) (i32.add (local.get 0) (i32.const 19)))
+                    (local.get $base)
+                )
+                (func (export "echo") (param i32) (result i32)
+                    (local.get 0)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (type $t20 (tuple u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32 u32))
+            (func (export "make") (param u32) (result $t20)
+                (canon lift (core func $i "make") (memory $i "memory"))
+            )
+            (func (export "echo") (param $t20) (result $t20)
+                (canon lift (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    type T20 = (
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+    );
+
+    // Tuples this large don't implement `PartialEq` or `Debug`, so compare
+    // them as arrays instead.
+    fn to_array(t: T20) -> [u32; 20] {
+        [
+            t.0, t.1, t.2, t.3, t.4, t.5, t.6, t.7, t.8, t.9, t.10, t.11, t.12, t.13, t.14, t.15,
+            t.16, t.17, t.18, t.19,
+        ]
+    }
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let make = instance.get_typed_func::<(u32,), T20, _>(&mut store, "make")?;
+    let echo = instance.get_typed_func::<(T20,), T20, _>(&mut store, "echo")?;
+
+    let mut expected = [0; 20];
+    for (i, slot) in expected.iter_mut().enumerate() {
+        *slot = 100 + i as u32;
+    }
+    let tuple = make.call_and_post_return(&mut store, (100,))?;
+    assert_eq!(to_array(tuple), expected);
+
+    // Lowering a large tuple as a parameter goes through linear memory, and
+    // lifting it back out should produce the same value.
+    let tuple = echo.call_and_post_return(&mut store, (tuple,))?;
+    assert_eq!(to_array(tuple), expected);
+
+    // Large tuples can be used as the parameters of a function too.
+    let err = instance
+        .get_typed_func::<T20, T20, _>(&mut store, "make")
+        .err()
+        .unwrap();
+    assert!(
+        format!("{:?}", err).contains("expected 20 types, found 1"),
+        "{:?}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn typecheck_signature_diagnostics() -> Result<()> {
+    let component = r#"
+        (component
+            (core module $m
+                (func (export "f") (param i32 i32 i32 i32 i32) (result i64)
+                    unreachable)
+                (func (export "g") (param i32)
+                    unreachable)
+                (memory (export "memory") 1)
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "named")
+                (param "a" u32) (param "b" string) (param "c" (list u8)) (result u64)
+                (canon lift (core func $i "f") (memory $i "memory"))
+            )
+            (func (export "unnamed") (param (flags "x" "y"))
+                (canon lift (core func $i "g"))
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let named = instance.get_func(&mut store, "named").unwrap();
+    let unnamed = instance.get_func(&mut store, "unnamed").unwrap();
+
+    let err = named
+        .typed::<(u32, &str, u32), u64, _>(&store)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch at parameter 3 (`c`): \
+         have `(u32, string, list<u8>) -> u64`, \
+         requested `(u32, string, u32) -> u64`"
+    );
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[2]: expected `u32` found `list<u8>`"
+    );
+
+    let err = named
+        .typed::<(u32, String, Vec<u8>), (), _>(&store)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch in result: \
+         have `(u32, string, list<u8>) -> u64`, \
+         requested `(u32, string, list<u8>)`"
+    );
+
+    let err = named
+        .typed::<(u32, String), Option<u64>, _>(&store)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch in number of parameters: \
+         have `(u32, string, list<u8>) -> u64`, \
+         requested `(u32, string) -> option<u64>`"
+    );
+
+    let err = unnamed
+        .typed::<(Result<u8, f32>,), (), _>(&store)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch at parameter 1: \
+         have `(flags { x, y })`, \
+         requested `(expected<u8, f32>)`"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn strings() -> Result<()> {
     let component = format!(
@@ -800,7 +1067,12 @@ fn strings() -> Result<()> {
 
     let ret = list16_to_str.call(&mut store, (&[0xd800],))?;
     let err = ret.to_str(&store).unwrap_err();
-    assert!(err.to_string().contains("unpaired surrogate"), "{}", err);
+    assert!(
+        err.to_string()
+            .contains("unpaired surrogate 0xd800 at code unit 0 of 1"),
+        "{}",
+        err
+    );
     list16_to_str.post_return(&mut store)?;
 
     let ret = list16_to_str.call(&mut store, (&[0xdfff],))?;
@@ -813,6 +1085,16 @@ fn strings() -> Result<()> {
     assert!(err.to_string().contains("unpaired surrogate"), "{}", err);
     list16_to_str.post_return(&mut store)?;
 
+    let ret = list16_to_str.call(&mut store, (&[0x61, 0xd83d, 0xdc9d, 0xdc9d, 0x62],))?;
+    let err = ret.to_str(&store).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unpaired surrogate 0xdc9d at code unit 3 of 5"),
+        "{}",
+        err
+    );
+    list16_to_str.post_return(&mut store)?;
+
     Ok(())
 }
 
@@ -930,7 +1212,7 @@ fn many_parameters() -> Result<()> {
         .as_slice(),
     );
     let (memory, pointer) = func.call(&mut store, input)?;
-    let memory = memory.as_le_slice(&store);
+    let memory = memory.as_le_slice(&store)?;
 
     let mut actual = &memory[pointer as usize..][..72];
     assert_eq!(i8::from_le_bytes(*actual.take_n::<1>()), input.0);
@@ -1789,7 +2071,7 @@ fn fancy_list() -> Result<()> {
         (Some(200), Err("general kenobi")),
     ];
     let (ptr, len, list) = func.call(&mut store, (&input,))?;
-    let memory = list.as_le_slice(&store);
+    let memory = list.as_le_slice(&store)?;
     let ptr = usize::try_from(ptr).unwrap();
     let len = usize::try_from(len).unwrap();
     let mut array = &memory[ptr..][..len * 16];
@@ -1822,6 +2104,67 @@ fn fancy_list() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn lower_list_from_iterator() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "echo") (param (list u32)) (result (list u32))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    let baseline = (0..1000u32).map(|i| i * 3 + 1).collect::<Vec<_>>();
+    let func = instance.get_typed_func::<(&[u32],), WasmList<u32>, _>(&mut store, "echo")?;
+    let list = func.call(&mut store, (&baseline,))?;
+    assert_eq!(list.as_le_slice(&store)?, baseline.as_slice());
+    func.post_return(&mut store)?;
+
+    let make_list = |n: u32| LowerList((0..n).map(|i| i * 3 + 1));
+    let func = instance.get_typed_func::<(LowerList<_>,), WasmList<u32>, _>(&mut store, "echo")?;
+    let list = func.call(&mut store, (make_list(1000),))?;
+    assert_eq!(list.as_le_slice(&store)?, baseline.as_slice());
+    func.post_return(&mut store)?;
+
+    let list = func.call(&mut store, (make_list(0),))?;
+    assert_eq!(list.len(), 0);
+    func.post_return(&mut store)?;
+
+    Ok(())
+}
+
 trait SliceExt<'a> {
     fn take_n<const N: usize>(&mut self) -> &'a [u8; N];
 
@@ -1926,7 +2269,7 @@ fn invalid_alignment() -> Result<()> {
         .err()
         .unwrap();
     assert!(
-        err.to_string().contains("return pointer not aligned"),
+        err.to_string().contains("return pointer is not aligned"),
         "{}",
         err
     );
@@ -2068,7 +2411,7 @@ fn raw_slice_of_various_types() -> Result<()> {
         .get_typed_func::<(), WasmList<u8>, _>(&mut store, "list-u8")?
         .call_and_post_return(&mut store, ())?;
     assert_eq!(
-        list.as_le_slice(&store),
+        list.as_le_slice(&store)?,
         [
             0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
             0x0e, 0x0f,
@@ -2078,7 +2421,7 @@ fn raw_slice_of_various_types() -> Result<()> {
         .get_typed_func::<(), WasmList<i8>, _>(&mut store, "list-i8")?
         .call_and_post_return(&mut store, ())?;
     assert_eq!(
-        list.as_le_slice(&store),
+        list.as_le_slice(&store)?,
         [
             0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
             0x0e, 0x0f,
@@ -2089,7 +2432,7 @@ fn raw_slice_of_various_types() -> Result<()> {
         .get_typed_func::<(), WasmList<u16>, _>(&mut store, "list-u16")?
         .call_and_post_return(&mut store, ())?;
     assert_eq!(
-        list.as_le_slice(&store),
+        list.as_le_slice(&store)?,
         [
             u16::to_le(0x01_00),
             u16::to_le(0x03_02),
@@ -2105,7 +2448,7 @@ fn raw_slice_of_various_types() -> Result<()> {
         .get_typed_func::<(), WasmList<i16>, _>(&mut store, "list-i16")?
         .call_and_post_return(&mut store, ())?;
     assert_eq!(
-        list.as_le_slice(&store),
+        list.as_le_slice(&store)?,
         [
             i16::to_le(0x01_00),
             i16::to_le(0x03_02),
@@ -2121,7 +2464,7 @@ fn raw_slice_of_various_types() -> Result<()> {
         .get_typed_func::<(), WasmList<u32>, _>(&mut store, "list-u32")?
         .call_and_post_return(&mut store, ())?;
     assert_eq!(
-        list.as_le_slice(&store),
+        list.as_le_slice(&store)?,
         [
             u32::to_le(0x03_02_01_00),
             u32::to_le(0x07_06_05_04),
@@ -2133,7 +2476,7 @@ fn raw_slice_of_various_types() -> Result<()> {
         .get_typed_func::<(), WasmList<i32>, _>(&mut store, "list-i32")?
         .call_and_post_return(&mut store, ())?;
     assert_eq!(
-        list.as_le_slice(&store),
+        list.as_le_slice(&store)?,
         [
             i32::to_le(0x03_02_01_00),
             i32::to_le(0x07_06_05_04),
@@ -2145,7 +2488,7 @@ fn raw_slice_of_various_types() -> Result<()> {
         .get_typed_func::<(), WasmList<u64>, _>(&mut store, "list-u64")?
         .call_and_post_return(&mut store, ())?;
     assert_eq!(
-        list.as_le_slice(&store),
+        list.as_le_slice(&store)?,
         [
             u64::to_le(0x07_06_05_04_03_02_01_00),
             u64::to_le(0x0f_0e_0d_0c_0b_0a_09_08),
@@ -2155,7 +2498,7 @@ fn raw_slice_of_various_types() -> Result<()> {
         .get_typed_func::<(), WasmList<i64>, _>(&mut store, "list-i64")?
         .call_and_post_return(&mut store, ())?;
     assert_eq!(
-        list.as_le_slice(&store),
+        list.as_le_slice(&store)?,
         [
             i64::to_le(0x07_06_05_04_03_02_01_00),
             i64::to_le(0x0f_0e_0d_0c_0b_0a_09_08),
@@ -2271,7 +2614,7 @@ fn lower_then_lift() -> Result<()> {
     let f = instance.get_typed_func::<(&str,), WasmStr, _>(&mut store, "f")?;
     let err = f.call(&mut store, ("hello",)).err().unwrap();
     assert!(
-        err.to_string().contains("return pointer not aligned"),
+        err.to_string().contains("return pointer is not aligned"),
         "{}",
         err
     );
@@ -2410,3 +2753,2903 @@ fn run_export_with_internal_adapter() -> Result<()> {
     assert_eq!(run.call(&mut store, ())?, 5);
     Ok(())
 }
+
+#[test]
+fn typed_caches_typechecks() -> Result<()> {
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+    use wasmtime::component::__internal::{ComponentTypes, InterfaceType, MemoryMut, Options};
+
+    static TYPECHECKS: AtomicUsize = AtomicUsize::new(0);
+
+    // A `u32` which counts how many times it's been typechecked.
+    #[derive(Copy, Clone)]
+    struct Counted(u32);
+
+    unsafe impl ComponentType for Counted {
+        type Lower = <u32 as ComponentType>::Lower;
+
+        const SIZE32: usize = u32::SIZE32;
+        const ALIGN32: u32 = u32::ALIGN32;
+
+        fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
+            TYPECHECKS.fetch_add(1, SeqCst);
+            u32::typecheck(ty, types)
+        }
+    }
+
+    unsafe impl Lower for Counted {
+        fn lower<T>(
+            &self,
+            store: &mut StoreContextMut<T>,
+            options: &Options,
+            dst: &mut MaybeUninit<Self::Lower>,
+        ) -> Result<()> {
+            self.0.lower(store, options, dst)
+        }
+
+        fn store<T>(&self, memory: &mut MemoryMut<'_, T>, offset: usize) -> Result<()> {
+            self.0.store(memory, offset)
+        }
+    }
+
+    let component = r#"
+        (component
+            (core module $m
+                (func (export "a") (param i32))
+                (func (export "b") (param i32))
+            )
+            (core instance $i (instantiate $m))
+            (func (export "a") (param u32)
+                (canon lift (core func $i "a"))
+            )
+            (func (export "b") (param u32)
+                (canon lift (core func $i "b"))
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    // The first lookup walks the types, but subsequent lookups of the same
+    // function with the same signature hit the cache, even through a fresh
+    // `Func` handle.
+    instance.get_typed_func::<(Counted,), (), _>(&mut store, "a")?;
+    assert_eq!(TYPECHECKS.load(SeqCst), 1);
+    instance.get_typed_func::<(Counted,), (), _>(&mut store, "a")?;
+    let a = instance.get_func(&mut store, "a").unwrap();
+    a.typed::<(Counted,), (), _>(&store)?;
+    assert_eq!(TYPECHECKS.load(SeqCst), 1);
+
+    // A different function is typechecked separately.
+    instance.get_typed_func::<(Counted,), (), _>(&mut store, "b")?;
+    assert_eq!(TYPECHECKS.load(SeqCst), 2);
+    instance.get_typed_func::<(Counted,), (), _>(&mut store, "b")?;
+    assert_eq!(TYPECHECKS.load(SeqCst), 2);
+
+    // Other signatures are still checked, and mismatches are never cached.
+    for _ in 0..2 {
+        assert!(instance
+            .get_typed_func::<(u64,), (), _>(&mut store, "a")
+            .is_err());
+        assert!(instance
+            .get_typed_func::<(Counted,), u32, _>(&mut store, "a")
+            .is_err());
+    }
+    assert_eq!(TYPECHECKS.load(SeqCst), 4);
+    instance
+        .get_typed_func::<(u32,), (), _>(&mut store, "a")?
+        .call_and_post_return(&mut store, (1,))?;
+
+    // A new instance has its own cache.
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    instance.get_typed_func::<(Counted,), (), _>(&mut store, "a")?;
+    assert_eq!(TYPECHECKS.load(SeqCst), 5);
+
+    Ok(())
+}
+
+#[test]
+fn strict_variant_padding() -> Result<()> {
+    #[derive(ComponentType, Lift, Lower, PartialEq, Debug)]
+    #[component(variant)]
+    enum Small {
+        A(u8),
+        B(u32),
+    }
+
+    let component = r#"
+        (component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "dirty-some") (result i32) i32.const 0)
+                (func (export "dirty-none") (result i32) i32.const 8)
+                (func (export "clean-none") (result i32) i32.const 16)
+                (func (export "dirty-ok") (result i32) i32.const 24)
+                (func (export "dirty-variant") (result i32) i32.const 32)
+
+                (data (i32.const 0) "\01\ff\00\00\2a\00\00\00")
+                (data (i32.const 8) "\00\00\00\00\07\00\00\00")
+                (data (i32.const 16) "\00\00\00\00\00\00\00\00")
+                (data (i32.const 24) "\00\00\00\00\05\00\01\00")
+                (data (i32.const 32) "\00\00\00\00\05\00\00\09")
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "dirty-some") (result (option u32))
+                (canon lift (core func $i "dirty-some") (memory $i "memory"))
+            )
+            (func (export "dirty-none") (result (option u32))
+                (canon lift (core func $i "dirty-none") (memory $i "memory"))
+            )
+            (func (export "clean-none") (result (option u32))
+                (canon lift (core func $i "clean-none") (memory $i "memory"))
+            )
+            (func (export "dirty-ok") (result (expected u8 u32))
+                (canon lift (core func $i "dirty-ok") (memory $i "memory"))
+            )
+            (type $small (variant (case "A" u8) (case "B" u32)))
+            (func (export "dirty-variant") (result $small)
+                (canon lift (core func $i "dirty-variant") (memory $i "memory"))
+            )
+        )
+    "#;
+
+    for strict in [false, true] {
+        let mut config = wasmtime::Config::new();
+        config.wasm_component_model(true);
+        config.component_model_strict_validation(strict);
+        let engine = wasmtime::Engine::new(&config)?;
+        let component = Component::new(&engine, component)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+        let dirty_some = instance.get_typed_func::<(), Option<u32>, _>(&mut store, "dirty-some")?;
+        let dirty_none = instance.get_typed_func::<(), Option<u32>, _>(&mut store, "dirty-none")?;
+        let clean_none = instance.get_typed_func::<(), Option<u32>, _>(&mut store, "clean-none")?;
+        let dirty_ok = instance.get_typed_func::<(), Result<u8, u32>, _>(&mut store, "dirty-ok")?;
+        let dirty_variant = instance.get_typed_func::<(), Small, _>(&mut store, "dirty-variant")?;
+
+        assert_eq!(clean_none.call_and_post_return(&mut store, ())?, None);
+
+        if !strict {
+            assert_eq!(dirty_some.call_and_post_return(&mut store, ())?, Some(42));
+            assert_eq!(dirty_none.call_and_post_return(&mut store, ())?, None);
+            assert_eq!(dirty_ok.call_and_post_return(&mut store, ())?, Ok(5));
+            assert_eq!(
+                dirty_variant.call_and_post_return(&mut store, ())?,
+                Small::A(5)
+            );
+            continue;
+        }
+
+        // Note that these errors poison the instance, so a fresh instance is
+        // used for each one.
+        let mut expect_err = |name: &str, msg: &str| -> Result<()> {
+            let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+            let func = instance.get_func(&mut store, name).unwrap();
+            let err = match name {
+                "dirty-ok" => func
+                    .typed::<(), Result<u8, u32>, _>(&store)?
+                    .call(&mut store, ())
+                    .unwrap_err(),
+                "dirty-variant" => func
+                    .typed::<(), Small, _>(&store)?
+                    .call(&mut store, ())
+                    .unwrap_err(),
+                _ => func
+                    .typed::<(), Option<u32>, _>(&store)?
+                    .call(&mut store, ())
+                    .unwrap_err(),
+            };
+            assert!(err.to_string().contains(msg), "{}", err);
+            Ok(())
+        };
+        expect_err(
+            "dirty-some",
+            "nonzero padding after discriminant byte 0xff at offset 1 of a 8-byte value",
+        )?;
+        expect_err(
+            "dirty-none",
+            "nonzero unused payload byte 0x07 at offset 4 of a 8-byte value",
+        )?;
+        expect_err(
+            "dirty-ok",
+            "nonzero unused payload byte 0x01 at offset 6 of a 8-byte value",
+        )?;
+        expect_err(
+            "dirty-variant",
+            "nonzero unused payload byte 0x09 at offset 7 of a 8-byte value",
+        )?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn strict_bool() -> Result<()> {
+    let component = r#"
+        (component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "stack") (result i32) i32.const 2)
+                (func (export "memory") (result i32) i32.const 0)
+
+                (data (i32.const 0) "\02\00\00\00\00\00\00\00")
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "stack") (result bool)
+                (canon lift (core func $i "stack"))
+            )
+            (func (export "memory") (result (tuple bool u32))
+                (canon lift (core func $i "memory") (memory $i "memory"))
+            )
+        )
+    "#;
+
+    for strict in [false, true] {
+        let mut config = wasmtime::Config::new();
+        config.wasm_component_model(true);
+        config.component_model_strict_validation(strict);
+        let engine = wasmtime::Engine::new(&config)?;
+        let component = Component::new(&engine, component)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let stack = instance.get_typed_func::<(), bool, _>(&mut store, "stack")?;
+        let memory = instance.get_typed_func::<(), (bool, u32), _>(&mut store, "memory")?;
+
+        if strict {
+            let err = stack.call(&mut store, ()).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("invalid bool value 2 lifted from the stack"),
+                "{}",
+                err
+            );
+
+            let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+            let memory = instance.get_typed_func::<(), (bool, u32), _>(&mut store, "memory")?;
+            let err = memory.call(&mut store, ()).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("invalid bool value 2 loaded from linear memory"),
+                "{}",
+                err
+            );
+        } else {
+            assert!(stack.call_and_post_return(&mut store, ())?);
+            assert_eq!(memory.call_and_post_return(&mut store, ())?, (true, 0));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn list_get_range_and_chunks() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "echo") (param (list u32)) (result (list char))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(&[u32],), WasmList<char>, _>(&mut store, "echo")?;
+
+    const LEN: usize = 10_000;
+    const BAD: usize = 5_000;
+    let mut input = (0..LEN as u32)
+        .map(|i| 'a' as u32 + i % 26)
+        .collect::<Vec<_>>();
+    input[BAD] = 0xd800;
+    let expected = |input: &[u32], range: std::ops::Range<usize>| {
+        range
+            .map(|i| char::from_u32(input[i]).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let list = func.call(&mut store, (&input,))?;
+    assert_eq!(list.len(), LEN);
+
+    // Ranges which don't include the invalid element decode successfully.
+    assert_eq!(list.get_range(&store, 0..BAD)?, expected(&input, 0..BAD));
+    assert_eq!(
+        list.get_range(&store, BAD + 1..LEN)?,
+        expected(&input, BAD + 1..LEN)
+    );
+    assert_eq!(list.get_range(&store, 10..10)?, Vec::new());
+
+    // Any range including the invalid element fails as a whole.
+    let err = list.get_range(&store, BAD - 10..BAD + 10).unwrap_err();
+    assert!(
+        err.to_string().contains("invalid char value 0xd800"),
+        "{}",
+        err
+    );
+    let err = list.get_range(&store, 0..LEN).unwrap_err();
+    assert!(
+        err.to_string().contains("invalid char value 0xd800"),
+        "{}",
+        err
+    );
+
+    // Out-of-bounds ranges are rejected.
+    let err = list.get_range(&store, 0..LEN + 1).unwrap_err();
+    assert!(err.to_string().contains("out of bounds"), "{}", err);
+    let err = list.get_range(&store, LEN + 1..LEN + 1).unwrap_err();
+    assert!(err.to_string().contains("out of bounds"), "{}", err);
+
+    // Chunked iteration visits every chunk up to the one containing the
+    // invalid element, and then stops.
+    let mut seen = Vec::new();
+    let err = list
+        .for_each_chunk(&store, 1024, |start, chunk| {
+            assert_eq!(chunk, expected(&input, start..start + chunk.len()));
+            seen.push((start, chunk.len()));
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("invalid char value 0xd800"),
+        "{}",
+        err
+    );
+    assert_eq!(
+        seen,
+        (0..BAD / 1024)
+            .map(|i| (i * 1024, 1024))
+            .collect::<Vec<_>>()
+    );
+    func.post_return(&mut store)?;
+
+    // Without an invalid element every chunk is visited, with a short final
+    // chunk.
+    input[BAD] = 'z' as u32;
+    let list = func.call(&mut store, (&input,))?;
+    let mut total = Vec::new();
+    list.for_each_chunk(&store, 1024, |start, chunk| {
+        assert_eq!(start, total.len());
+        assert!(chunk.len() == 1024 || start + chunk.len() == LEN);
+        total.extend(chunk);
+        Ok(())
+    })?;
+    assert_eq!(total, expected(&input, 0..LEN));
+
+    // Errors from the callback abort iteration.
+    let mut calls = 0;
+    let err = list
+        .for_each_chunk(&store, 1024, |_, _| {
+            calls += 1;
+            anyhow::bail!("stop")
+        })
+        .unwrap_err();
+    assert_eq!(err.to_string(), "stop");
+    assert_eq!(calls, 1);
+    func.post_return(&mut store)?;
+
+    Ok(())
+}
+
+#[test]
+fn list_iter_both_ends() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "echo") (param (list string)) (result (list string))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(&[&str],), WasmList<String>, _>(&mut store, "echo")?;
+
+    let input = ["a", "bb", "ccc", "dddd", "eeeee", "ffffff"];
+    let list = func.call(&mut store, (&input,))?;
+
+    // Forward iteration
+    let forward = list.iter(&store).collect::<Result<Vec<_>>>()?;
+    assert_eq!(forward, input);
+
+    // Reverse iteration
+    let reverse = list.iter(&store).rev().collect::<Result<Vec<_>>>()?;
+    assert_eq!(reverse, input.iter().rev().copied().collect::<Vec<_>>());
+
+    // Random access and exact sizes while consuming from both ends
+    let mut iter = list.iter(&store);
+    assert_eq!(iter.len(), 6);
+    assert_eq!(iter.nth(1).unwrap()?, "bb");
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.next_back().unwrap()?, "ffffff");
+    assert_eq!(iter.nth_back(1).unwrap()?, "dddd");
+    assert_eq!(iter.len(), 1);
+    assert_eq!(iter.next().unwrap()?, "ccc");
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
+    assert_eq!(iter.len(), 0);
+
+    assert_eq!(list.iter(&store).nth(5).unwrap()?, "ffffff");
+    assert!(list.iter(&store).nth(6).is_none());
+    assert_eq!(list.iter(&store).last().unwrap()?, "ffffff");
+    assert_eq!(list.iter(&store).skip(2).count(), 4);
+    func.post_return(&mut store)?;
+
+    let list = func.call(&mut store, (&[],))?;
+    assert!(list.iter(&store).next().is_none());
+    assert!(list.iter(&store).last().is_none());
+    func.post_return(&mut store)?;
+
+    Ok(())
+}
+
+#[test]
+fn lazy_values_with_wrong_store() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "echo-string") (param string) (result string)
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "echo-list") (param (list u8)) (result (list u8))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let mut other = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    Linker::new(&engine).instantiate(&mut other, &component)?;
+
+    let expect_err = |result: Result<()>| {
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "value belongs to a different store");
+    };
+
+    let echo_string = instance.get_typed_func::<(&str,), WasmStr, _>(&mut store, "echo-string")?;
+    let s = echo_string.call(&mut store, ("hello",))?;
+    expect_err(s.to_str(&other).map(drop));
+    assert_eq!(s.to_str(&store)?, "hello");
+    echo_string.post_return(&mut store)?;
+
+    let echo_list =
+        instance.get_typed_func::<(&[u8],), WasmList<u8>, _>(&mut store, "echo-list")?;
+    let list = echo_list.call(&mut store, (b"abc",))?;
+    expect_err(list.as_le_slice(&other).map(drop));
+    expect_err(list.get(&other, 0).unwrap().map(drop));
+    expect_err(list.get_range(&other, 0..3).map(drop));
+    expect_err(list.for_each_chunk(&other, 2, |_, _| Ok(())));
+    assert_eq!(list.iter(&other).len(), 3);
+    for item in list.iter(&other) {
+        expect_err(item.map(drop));
+    }
+    assert!(list.get(&other, 3).is_none());
+    assert_eq!(list.as_le_slice(&store)?, b"abc");
+    echo_list.post_return(&mut store)?;
+
+    Ok(())
+}
+
+#[test]
+fn string_list_oob_near_max() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "ret-pair") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "utf8") (param u32 u32) (result string)
+                (canon lift (core func $i "ret-pair")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "utf16") (param u32 u32) (result string)
+                (canon lift (core func $i "ret-pair")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                    string-encoding=utf16
+                )
+            )
+            (func (export "list-u64") (param u32 u32) (result (list u64))
+                (canon lift (core func $i "ret-pair")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    // Note that these errors poison the instance, so a fresh instance is used
+    // for each one.
+    let mut expect_err = |name: &str, params: (u32, u32), msg: &str| -> Result<()> {
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let err = if name == "list-u64" {
+            instance
+                .get_typed_func::<(u32, u32), WasmList<u64>, _>(&mut store, name)?
+                .call(&mut store, params)
+                .err()
+                .unwrap()
+        } else {
+            instance
+                .get_typed_func::<(u32, u32), WasmStr, _>(&mut store, name)?
+                .call(&mut store, params)
+                .err()
+                .unwrap()
+        };
+        assert_eq!(err.to_string(), msg);
+        Ok(())
+    };
+    expect_err(
+        "utf8",
+        (0xffff_fff0, 0x20),
+        "string pointer/length out of bounds of memory \
+         (ptr 0xfffffff0, len 32, memory size 65536)",
+    )?;
+    expect_err(
+        "utf8",
+        (0x10, u32::MAX),
+        "string pointer/length out of bounds of memory \
+         (ptr 0x10, len 4294967295, memory size 65536)",
+    )?;
+    expect_err(
+        "utf16",
+        (0, 0x8000_0000),
+        "string pointer/length out of bounds of memory \
+         (ptr 0x0, len 2147483648, memory size 65536)",
+    )?;
+    expect_err(
+        "list-u64",
+        (0xffff_fff8, 0x2000_0001),
+        "list pointer/length out of bounds of memory \
+         (ptr 0xfffffff8, len 536870913, memory size 65536)",
+    )?;
+
+    // Ranges ending exactly at the end of memory are in bounds.
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let utf8 = instance.get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf8")?;
+    let list_u64 =
+        instance.get_typed_func::<(u32, u32), WasmList<u64>, _>(&mut store, "list-u64")?;
+    let s = utf8.call(&mut store, (65536 - 4, 4))?;
+    assert_eq!(s.to_str(&store)?.len(), 4);
+    utf8.post_return(&mut store)?;
+    let s = utf8.call(&mut store, (65536, 0))?;
+    assert_eq!(s.to_str(&store)?, "");
+    utf8.post_return(&mut store)?;
+    let list = list_u64.call(&mut store, (65536 - 8, 1))?;
+    assert_eq!(list.len(), 1);
+    list_u64.post_return(&mut store)?;
+
+    Ok(())
+}
+
+#[test]
+fn wasm_str_as_str() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (data (i32.const 100) "hello")
+                (data (i32.const 200) "h\00i\00")
+                (data (i32.const 300) "\ff")
+                (func (export "ret-pair") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "utf8") (param u32 u32) (result string)
+                (canon lift (core func $i "ret-pair")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "utf16") (param u32 u32) (result string)
+                (canon lift (core func $i "ret-pair")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                    string-encoding=utf16
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let utf8 = instance.get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf8")?;
+    let utf16 = instance.get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf16")?;
+
+    // utf-8 strings are borrowed directly from linear memory.
+    let s = utf8.call_and_post_return(&mut store, (100, 5))?;
+    assert_eq!(s.encoding(), StringEncoding::Utf8);
+    let borrowed: &str = s.as_str(&store)?;
+    assert_eq!(borrowed, "hello");
+    assert_eq!(s.to_str(&store)?, "hello");
+
+    // Invalid utf-8 is still rejected.
+    let s = utf8.call_and_post_return(&mut store, (300, 1))?;
+    assert!(s.as_str(&store).is_err());
+
+    // utf-16 strings can't be borrowed, but can still be decoded.
+    let s = utf16.call_and_post_return(&mut store, (200, 2))?;
+    assert_eq!(s.encoding(), StringEncoding::Utf16);
+    let err = s.as_str(&store).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "cannot borrow a string encoded as Utf16 as `&str`, use `WasmStr::to_str` instead"
+    );
+    assert_eq!(s.to_str(&store)?, "hi");
+
+    Ok(())
+}
+
+// Lists whose length doesn't fit in 32 bits are only possible on 64-bit hosts.
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn lower_list_exceeding_u32() -> Result<()> {
+    // An iterator which claims to have `len` items without producing any, so
+    // huge lists can be lowered without allocating them on the host.
+    #[derive(Clone)]
+    struct Claims(usize);
+
+    impl Iterator for Claims {
+        type Item = u8;
+        fn next(&mut self) -> Option<u8> {
+            None
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.0, Some(self.0))
+        }
+    }
+
+    impl ExactSizeIterator for Claims {}
+
+    // Note that `realloc` traps, so lowering must fail before it's called.
+    let component = r#"
+        (component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    unreachable)
+                (func (export "take") (param i32 i32))
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "take-u8") (param (list u8))
+                (canon lift (core func $i "take")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "take-u16") (param (list u16))
+                (canon lift (core func $i "take")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let take = instance.get_typed_func::<(LowerList<Claims>,), (), _>(&mut store, "take-u8")?;
+    let err = take
+        .call(&mut store, (LowerList(Claims(u32::MAX as usize + 1)),))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`list<u8>` of length 4294967296 is too large to lower into a 32-bit linear memory"
+    );
+
+    // For larger elements the number of elements fits in 32 bits but the
+    // size in bytes doesn't.
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let take = instance
+        .get_typed_func::<(LowerList<std::iter::Map<Claims, fn(u8) -> u16>>,), (), _>(
+            &mut store, "take-u16",
+        )?;
+    let items = Claims(1 << 31).map(u16::from as fn(u8) -> u16);
+    let err = take.call(&mut store, (LowerList(items),)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`list<u16>` of length 2147483648 is too large to lower into a 32-bit linear memory"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lower_without_realloc() -> Result<()> {
+    let component = r#"(component
+        (core module $m
+            (memory (export "memory") 1)
+            (func (export "len") (param i32 i32) (result i32)
+                local.get 1)
+        )
+        (core instance $i (instantiate $m))
+
+        (func (export "str-len") (param string) (result u32)
+            (canon lift (core func $i "len") (memory $i "memory"))
+        )
+        (func (export "list-len") (param (list u8)) (result u32)
+            (canon lift (core func $i "len") (memory $i "memory"))
+        )
+    )"#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let str_len = instance.get_typed_func::<(&str,), u32, _>(&mut store, "str-len")?;
+    let list_len = instance.get_typed_func::<(&[u8],), u32, _>(&mut store, "list-len")?;
+
+    // Empty strings and lists don't need to allocate, so they work.
+    assert_eq!(str_len.call_and_post_return(&mut store, ("",))?, 0);
+    assert_eq!(list_len.call_and_post_return(&mut store, (&[],))?, 0);
+
+    // Anything else requires `realloc`, which is reported without poisoning
+    // the instance.
+    let err = str_len.call(&mut store, ("hello",)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "function `str-len` has no `realloc` canonical option, but lowering its \
+         parameters `(string)` requires allocating 5 bytes"
+    );
+    let err = list_len.call(&mut store, (&[1, 2, 3],)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "function `list-len` has no `realloc` canonical option, but lowering its \
+         parameters `(list<u8>)` requires allocating 3 bytes"
+    );
+
+    let str_len = instance.get_func(&mut store, "str-len").unwrap();
+    let err = str_len
+        .call(&mut store, &[Val::String("dynamic".into())])
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("requires allocating 7 bytes"),
+        "{}",
+        err
+    );
+
+    assert_eq!(list_len.call_and_post_return(&mut store, (&[],))?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn call_with_space() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "add") (param i32 i32) (result i32)
+                    (i32.add (local.get 0) (local.get 1)))
+                (func (export "echo") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "add") (param u32) (param u32) (result u32)
+                (canon lift (core func $i "add"))
+            )
+            (func (export "echo") (param string) (result string)
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let add = instance.get_typed_func::<(u32, u32), u32, _>(&mut store, "add")?;
+    let echo = instance.get_typed_func::<(&str,), WasmStr, _>(&mut store, "echo")?;
+
+    // One buffer can be shared by functions with differently sized parameters
+    // and results.
+    let mut buffer = CallBuffer::new();
+    for i in 0..3 {
+        let expected = add.call(&mut store, (i, 10))?;
+        add.post_return(&mut store)?;
+        assert_eq!(
+            add.call_with_space(&mut store, (i, 10), &mut buffer)?,
+            expected
+        );
+        add.post_return(&mut store)?;
+
+        let input = "x".repeat(i as usize);
+        let s = echo.call_with_space(&mut store, (&input,), &mut buffer)?;
+        assert_eq!(s.to_str(&store)?, input);
+        echo.post_return(&mut store)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn lifted_size_limits() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "ret-pair") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "utf8") (param u32 u32) (result string)
+                (canon lift (core func $i "ret-pair")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "utf16") (param u32 u32) (result string)
+                (canon lift (core func $i "ret-pair")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                    string-encoding=utf16
+                )
+            )
+            (func (export "list-u32") (param u32 u32) (result (list u32))
+                (canon lift (core func $i "ret-pair")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.component_model_max_lifted_string_bytes(100);
+    config.component_model_max_lifted_list_elements(10);
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let string_err = |bytes: u32| {
+        format!(
+            "lifted string of {} bytes exceeds the limit of 100 bytes configured \
+             by `Config::component_model_max_lifted_string_bytes`",
+            bytes
+        )
+    };
+    let list_err = |len: u32| {
+        format!(
+            "lifted list of {} elements exceeds the limit of 10 elements configured \
+             by `Config::component_model_max_lifted_list_elements`",
+            len
+        )
+    };
+
+    // Values at the limit are fine.
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let utf8 = instance.get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf8")?;
+    utf8.call(&mut store, (0, 100))?;
+    utf8.post_return(&mut store)?;
+    // utf-16 strings are measured in bytes rather than code units.
+    let utf16 = instance.get_typed_func::<(u32, u32), String, _>(&mut store, "utf16")?;
+    assert_eq!(utf16.call(&mut store, (0, 50))?.len(), 50);
+    utf16.post_return(&mut store)?;
+    // Lists are measured in elements.
+    let list = instance.get_typed_func::<(u32, u32), WasmList<u32>, _>(&mut store, "list-u32")?;
+    assert_eq!(list.call(&mut store, (0, 10))?.len(), 10);
+    list.post_return(&mut store)?;
+
+    // Note that these errors poison the instance, so a fresh instance is used
+    // for each one.
+    let mut expect_err = |name: &str, ty: &str, params: (u32, u32), msg: String| -> Result<()> {
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let func = instance.get_func(&mut store, name).unwrap();
+        let err = match ty {
+            "lazy" if name == "list-u32" => func
+                .typed::<(u32, u32), WasmList<u32>, _>(&store)?
+                .call(&mut store, params)
+                .err()
+                .unwrap(),
+            "lazy" => func
+                .typed::<(u32, u32), WasmStr, _>(&store)?
+                .call(&mut store, params)
+                .err()
+                .unwrap(),
+            "eager" if name == "list-u32" => func
+                .typed::<(u32, u32), Vec<u32>, _>(&store)?
+                .call(&mut store, params)
+                .err()
+                .unwrap(),
+            "eager" => func
+                .typed::<(u32, u32), String, _>(&store)?
+                .call(&mut store, params)
+                .err()
+                .unwrap(),
+            _ => func
+                .call(&mut store, &[Val::U32(params.0), Val::U32(params.1)])
+                .err()
+                .unwrap(),
+        };
+        assert_eq!(err.to_string(), msg);
+        Ok(())
+    };
+
+    // One more than the limit is rejected whether it's lifted lazily, eagerly,
+    // or through the dynamic API.
+    expect_err("utf8", "lazy", (0, 101), string_err(101))?;
+    expect_err("utf8", "eager", (0, 101), string_err(101))?;
+    expect_err("utf16", "eager", (0, 51), string_err(102))?;
+    expect_err("list-u32", "lazy", (0, 11), list_err(11))?;
+    expect_err("list-u32", "dynamic", (0, 11), list_err(11))?;
+
+    // The limit is checked before the bounds of memory, so huge lengths are
+    // rejected without attempting to allocate anything.
+    expect_err("utf8", "eager", (0, u32::MAX), string_err(u32::MAX))?;
+    expect_err("list-u32", "eager", (0, u32::MAX), list_err(u32::MAX))?;
+
+    Ok(())
+}
+
+#[test]
+fn typed_errors_downcast() -> Result<()> {
+    let component = r#"(component
+        (core module $m
+            (memory (export "memory") 1)
+            (data (i32.const 100) "\ff")
+            (data (i32.const 104) "\00\d8")
+
+            (func (export "id") (param i32) (result i32)
+                local.get 0)
+            (func (export "ret-pair") (param i32 i32) (result i32)
+                (i32.store offset=0 (i32.const 8) (local.get 0))
+                (i32.store offset=4 (i32.const 8) (local.get 1))
+                i32.const 8)
+            (func (export "take") (param i32 i32))
+            (func (export "realloc-unaligned") (param i32 i32 i32 i32) (result i32)
+                i32.const 1)
+            (func (export "realloc-oob") (param i32 i32 i32 i32) (result i32)
+                i32.const 0xffff0000)
+        )
+        (core instance $i (instantiate $m))
+
+        (func (export "char") (param u32) (result char)
+            (canon lift (core func $i "id"))
+        )
+        (func (export "expected") (param u32) (result (expected unit unit))
+            (canon lift (core func $i "id"))
+        )
+        (func (export "pair") (param u32) (result (tuple u32 u32))
+            (canon lift (core func $i "id") (memory $i "memory"))
+        )
+        (func (export "utf8") (param u32 u32) (result string)
+            (canon lift (core func $i "ret-pair") (memory $i "memory"))
+        )
+        (func (export "utf16") (param u32 u32) (result string)
+            (canon lift (core func $i "ret-pair") (memory $i "memory") string-encoding=utf16)
+        )
+        (func (export "list-u32") (param u32 u32) (result (list u32))
+            (canon lift (core func $i "ret-pair") (memory $i "memory"))
+        )
+        (func (export "take-no-realloc") (param (list u32))
+            (canon lift (core func $i "take") (memory $i "memory"))
+        )
+        (func (export "take-realloc-unaligned") (param (list u32))
+            (canon lift
+                (core func $i "take")
+                (memory $i "memory")
+                (realloc (func $i "realloc-unaligned"))
+            )
+        )
+        (func (export "take-realloc-oob") (param (list u32))
+            (canon lift
+                (core func $i "take")
+                (memory $i "memory")
+                (realloc (func $i "realloc-oob"))
+            )
+        )
+    )"#;
+
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.component_model_max_lifted_list_elements(10);
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = |store: &mut Store<()>| Linker::new(&engine).instantiate(store, &component);
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(u32,), char, _>(&mut store, "char")?
+        .call(&mut store, (0xd800,))
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<LiftError>(),
+            Some(LiftError::InvalidChar { value: 0xd800, .. })
+        ),
+        "{:?}",
+        err
+    );
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(u32,), Result<(), ()>, _>(&mut store, "expected")?
+        .call(&mut store, (2,))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LiftError>(),
+        Some(&LiftError::InvalidDiscriminant {
+            ty: "expected",
+            value: 2
+        })
+    );
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(u32,), (u32, u32), _>(&mut store, "pair")?
+        .call(&mut store, (1,))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LiftError>(),
+        Some(&LiftError::Misaligned {
+            what: "return",
+            ptr: 1,
+            align: 4
+        })
+    );
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(u32, u32), WasmList<u32>, _>(&mut store, "list-u32")?
+        .call(&mut store, (0xffff_0000, 1))
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<LiftError>(),
+            Some(LiftError::OutOfBounds {
+                what: "list",
+                ptr: 0xffff_0000,
+                ..
+            })
+        ),
+        "{:?}",
+        err
+    );
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(u32, u32), WasmList<u32>, _>(&mut store, "list-u32")?
+        .call(&mut store, (0, 11))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LiftError>(),
+        Some(&LiftError::LimitExceeded {
+            what: "list",
+            len: 11,
+            limit: 10
+        })
+    );
+
+    // Strings are validated when they're read rather than when they're
+    // lifted.
+    let instance_utf8 = instance(&mut store)?;
+    let s = instance_utf8
+        .get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf8")?
+        .call(&mut store, (100, 1))?;
+    let err = s.to_str(&store).unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<LiftError>(),
+            Some(LiftError::InvalidUtf8(_))
+        ),
+        "{:?}",
+        err
+    );
+
+    let instance_utf16 = instance(&mut store)?;
+    let s = instance_utf16
+        .get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf16")?
+        .call(&mut store, (104, 1))?;
+    let err = s.to_str(&store).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LiftError>(),
+        Some(&LiftError::InvalidUtf16 {
+            unpaired_surrogate: 0xd800,
+            position: 0,
+            len: 1
+        })
+    );
+
+    // The messages added to lowering errors by `TypedFunc::call` don't hide
+    // the underlying error.
+    let err = instance(&mut store)?
+        .get_typed_func::<(&[u32],), (), _>(&mut store, "take-no-realloc")?
+        .call(&mut store, (&[1, 2],))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LowerError>(),
+        Some(&LowerError::MissingRealloc { size: 8 })
+    );
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(&[u32],), (), _>(&mut store, "take-realloc-unaligned")?
+        .call(&mut store, (&[1, 2],))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LowerError>(),
+        Some(&LowerError::ReallocMisaligned { ptr: 1, align: 4 })
+    );
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(&[u32],), (), _>(&mut store, "take-realloc-oob")?
+        .call(&mut store, (&[1, 2],))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LowerError>(),
+        Some(&LowerError::ReallocOutOfBounds {
+            ptr: 0xffff_0000,
+            size: 8
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn wasm_list_bools() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "reinterpret") (param (list u8)) (result (list bool))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let bytes = (0..10_000u32).map(|i| (i % 3) as u8).collect::<Vec<_>>();
+    let valid = (0..10_000u32).map(|i| (i % 2) as u8).collect::<Vec<_>>();
+
+    for strict in [false, true] {
+        let mut config = wasmtime::Config::new();
+        config.wasm_component_model(true);
+        config.component_model_strict_validation(strict);
+        let engine = wasmtime::Engine::new(&config)?;
+        let component = Component::new(&engine, &component)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let reinterpret =
+            instance.get_typed_func::<(&[u8],), WasmList<bool>, _>(&mut store, "reinterpret")?;
+
+        // Lists of only 0 and 1 are valid regardless of strictness and match
+        // decoding element-by-element.
+        let list = reinterpret.call(&mut store, (&valid[..],))?;
+        assert_eq!(list.as_raw_bytes(&store)?, &valid[..]);
+        let bools = list.to_bools(&store)?;
+        assert_eq!(bools.len(), valid.len());
+        assert_eq!(bools, list.iter(&store).collect::<Result<Vec<_>>>()?,);
+        assert!(bools.iter().zip(&valid).all(|(b, v)| *b == (*v == 1)));
+        reinterpret.post_return(&mut store)?;
+
+        // Other nonzero bytes are `true` unless validation is strict.
+        let list = reinterpret.call(&mut store, (&bytes[..],))?;
+        assert_eq!(list.as_raw_bytes(&store)?, &bytes[..]);
+        if strict {
+            let err = list.to_bools(&store).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<LiftError>(),
+                Some(&LiftError::InvalidBool {
+                    value: 2,
+                    source: "loaded from linear memory"
+                })
+            );
+        } else {
+            let bools = list.to_bools(&store)?;
+            assert!(bools.iter().zip(&bytes).all(|(b, v)| *b == (*v != 0)));
+            assert_eq!(bools, list.iter(&store).collect::<Result<Vec<_>>>()?,);
+        }
+        reinterpret.post_return(&mut store)?;
+
+        // Lists are tied to the store they came from.
+        let other = Store::new(&engine, ());
+        assert!(list.to_bools(&other).is_err());
+        assert!(list.as_raw_bytes(&other).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn nested_lazy_lists() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (data (i32.const 60000) "hello")
+
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+
+                ;; Reinterprets a list of `u32` pairs as a list of 8-byte
+                ;; records.
+                (func (export "pairs") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0)
+                        (i32.shr_u (local.get 1) (i32.const 1)))
+                    i32.const 0)
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "strings") (param (list string)) (result (list string))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "lists") (param (list (list u32))) (result (list (list u32)))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "pairs-as-strings") (param (list u32)) (result (list string))
+                (canon lift
+                    (core func $i "pairs")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    // `list<string>` as `WasmList<WasmStr>`
+    let strings =
+        instance.get_typed_func::<(&[&str],), WasmList<WasmStr>, _>(&mut store, "strings")?;
+    let input = ["a", "", "hello", "\u{1f600}"];
+    let list = strings.call(&mut store, (&input[..],))?;
+    assert_eq!(list.len(), input.len());
+    assert_eq!(list.get(&store, 2).unwrap()?.to_str(&store)?, "hello");
+    assert!(list.get(&store, 4).is_none());
+    let all = list
+        .iter(&store)
+        .map(|s| -> Result<String> { Ok(s?.to_str(&store)?.into_owned()) })
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(all, input);
+    let range = list.get_range(&store, 1..3)?;
+    assert_eq!(range[0].to_str(&store)?, "");
+    assert_eq!(range[1].to_str(&store)?, "hello");
+    strings.post_return(&mut store)?;
+
+    // `list<list<u32>>` as `WasmList<WasmList<u32>>`
+    let lists =
+        instance.get_typed_func::<(&[&[u32]],), WasmList<WasmList<u32>>, _>(&mut store, "lists")?;
+    let input: [&[u32]; 3] = [&[1, 2, 3], &[], &[u32::MAX]];
+    let list = lists.call(&mut store, (&input[..],))?;
+    assert_eq!(list.len(), 3);
+    let last = list.iter(&store).next_back().unwrap()?;
+    assert_eq!(last.as_le_slice(&store)?, [u32::MAX.to_le()]);
+    for (inner, expected) in list.iter(&store).zip(input) {
+        let inner = inner?;
+        assert_eq!(inner.len(), expected.len());
+        let values = inner.iter(&store).collect::<Result<Vec<_>>>()?;
+        assert_eq!(values, expected);
+    }
+    lists.post_return(&mut store)?;
+
+    // Only the elements which are loaded are validated, so a list containing
+    // an invalid string can still be lifted and its valid elements read.
+    let pairs = instance
+        .get_typed_func::<(&[u32],), WasmList<WasmStr>, _>(&mut store, "pairs-as-strings")?;
+    let list = pairs.call(&mut store, (&[60000, 5, 0xffff_fff0, 16, 60000, 3][..],))?;
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.get(&store, 0).unwrap()?.to_str(&store)?, "hello");
+    assert_eq!(list.get(&store, 2).unwrap()?.to_str(&store)?, "hel");
+    let err = list.get(&store, 1).unwrap().unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<LiftError>(),
+            Some(LiftError::OutOfBounds {
+                what: "string",
+                ptr: 0xffff_fff0,
+                len: 16,
+                ..
+            })
+        ),
+        "{:?}",
+        err
+    );
+    assert!(list.get_range(&store, 0..3).is_err());
+    pairs.post_return(&mut store)?;
+
+    Ok(())
+}
+
+#[test]
+fn views_survive_memory_growth() -> Result<()> {
+    let component = r#"(component
+        (core module $m
+            (memory (export "memory") 1)
+            (data (i32.const 100) "hello")
+            (data (i32.const 200) "\01\00\00\00\02\00\00\00")
+
+            (func (export "ret-str") (result i32)
+                (i32.store offset=0 (i32.const 8) (i32.const 100))
+                (i32.store offset=4 (i32.const 8) (i32.const 5))
+                i32.const 8)
+            (func (export "ret-list") (result i32)
+                (i32.store offset=0 (i32.const 8) (i32.const 200))
+                (i32.store offset=4 (i32.const 8) (i32.const 2))
+                i32.const 8)
+            (func (export "grow") (param i32) (result i32)
+                (memory.grow (local.get 0)))
+        )
+        (core instance $i (instantiate $m))
+
+        (func (export "ret-str") (result string)
+            (canon lift (core func $i "ret-str") (memory $i "memory"))
+        )
+        (func (export "ret-list") (result (list u32))
+            (canon lift (core func $i "ret-list") (memory $i "memory"))
+        )
+        (func (export "grow") (param u32) (result s32)
+            (canon lift (core func $i "grow"))
+        )
+    )"#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let ret_str = instance.get_typed_func::<(), WasmStr, _>(&mut store, "ret-str")?;
+    let ret_list = instance.get_typed_func::<(), WasmList<u32>, _>(&mut store, "ret-list")?;
+    let grow = instance.get_typed_func::<(u32,), i32, _>(&mut store, "grow")?;
+
+    let s = ret_str.call(&mut store, ())?;
+    ret_str.post_return(&mut store)?;
+    let list = ret_list.call(&mut store, ())?;
+    ret_list.post_return(&mut store)?;
+
+    // Views held across other calls into the instance remain usable when
+    // memory grows, even if it's moved in the process.
+    assert_eq!(grow.call_and_post_return(&mut store, (10,))?, 1);
+    assert_eq!(grow.call_and_post_return(&mut store, (100,))?, 11);
+    assert_eq!(s.to_str(&store)?, "hello");
+    assert_eq!(s.as_str(&store)?, "hello");
+    assert_eq!(list.as_le_slice(&store)?, [1u32.to_le(), 2u32.to_le()]);
+    assert_eq!(list.get(&store, 1).unwrap()?, 2);
+
+    Ok(())
+}
+
+#[test]
+fn lossy_strings_and_chars() -> Result<()> {
+    let utf8: &[u8] = b"hello \xf0\x90\x80 w\xc3(orld\xff!\xed\xa0\x80";
+    let utf16: &[u16] = &[0x68, 0xd800, 0x69, 0xdc00, 0xd83d, 0xde00, 0xd800];
+    let chars: &[u32] = &[0x41, 0xd800, 0x11_0000, 0xdfff];
+    let data = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .map(|b| format!("\\{:02x}", b))
+            .collect::<String>()
+    };
+    let utf16_bytes = utf16
+        .iter()
+        .flat_map(|u| u.to_le_bytes())
+        .collect::<Vec<_>>();
+    let char_bytes = chars
+        .iter()
+        .flat_map(|c| c.to_le_bytes())
+        .collect::<Vec<_>>();
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (data (i32.const 100) "{}")
+                (data (i32.const 200) "{}")
+                (data (i32.const 300) "{}")
+
+                (func (export "id") (param i32) (result i32)
+                    local.get 0)
+                (func (export "ret-pair") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 8) (local.get 0))
+                    (i32.store offset=4 (i32.const 8) (local.get 1))
+                    i32.const 8)
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "utf8") (param u32 u32) (result string)
+                (canon lift (core func $i "ret-pair") (memory $i "memory"))
+            )
+            (func (export "utf16") (param u32 u32) (result string)
+                (canon lift
+                    (core func $i "ret-pair")
+                    (memory $i "memory")
+                    string-encoding=utf16
+                )
+            )
+            (func (export "char") (param u32) (result char)
+                (canon lift (core func $i "id"))
+            )
+            (func (export "chars") (param u32 u32) (result (list char))
+                (canon lift (core func $i "ret-pair") (memory $i "memory"))
+            )
+        )"#,
+        data(utf8),
+        data(&utf16_bytes),
+        data(&char_bytes),
+    );
+
+    for lossy in [false, true] {
+        let mut config = wasmtime::Config::new();
+        config.wasm_component_model(true);
+        config.component_model_lossy_chars(lossy);
+        let engine = wasmtime::Engine::new(&config)?;
+        let component = Component::new(&engine, &component)?;
+        let mut store = Store::new(&engine, ());
+        let instance = |store: &mut Store<()>| Linker::new(&engine).instantiate(store, &component);
+
+        // Lossy string decoding is independent of the configuration and
+        // matches the standard library.
+        let i = instance(&mut store)?;
+        let s = i
+            .get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf8")?
+            .call(&mut store, (100, utf8.len() as u32))?;
+        assert!(s.to_str(&store).is_err());
+        assert_eq!(s.to_string_lossy(&store)?, String::from_utf8_lossy(utf8));
+
+        let i = instance(&mut store)?;
+        let s = i
+            .get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf16")?
+            .call(&mut store, (200, utf16.len() as u32))?;
+        assert!(s.to_str(&store).is_err());
+        assert_eq!(s.to_string_lossy(&store)?, String::from_utf16_lossy(utf16));
+        assert_eq!(
+            s.to_string_lossy(&store)?,
+            "h\u{fffd}i\u{fffd}\u{1f600}\u{fffd}"
+        );
+
+        // Valid strings are unaffected.
+        let i = instance(&mut store)?;
+        let s = i
+            .get_typed_func::<(u32, u32), WasmStr, _>(&mut store, "utf8")?
+            .call(&mut store, (100, 5))?;
+        assert_eq!(s.to_string_lossy(&store)?, "hello");
+
+        // Invalid chars are only replaced if configured to do so.
+        let i = instance(&mut store)?;
+        let f = i.get_typed_func::<(u32,), char, _>(&mut store, "char")?;
+        assert_eq!(f.call_and_post_return(&mut store, ('x' as u32,))?, 'x');
+        for invalid in [0xd800, 0xdfff, 0x11_0000, u32::MAX] {
+            if lossy {
+                let i = instance(&mut store)?;
+                let f = i.get_typed_func::<(u32,), char, _>(&mut store, "char")?;
+                assert_eq!(f.call(&mut store, (invalid,))?, '\u{fffd}');
+            } else {
+                let i = instance(&mut store)?;
+                let f = i.get_typed_func::<(u32,), char, _>(&mut store, "char")?;
+                let err = f.call(&mut store, (invalid,)).unwrap_err();
+                assert!(
+                    matches!(
+                        err.downcast_ref::<LiftError>(),
+                        Some(LiftError::InvalidChar { value, .. }) if *value == invalid
+                    ),
+                    "{:?}",
+                    err
+                );
+            }
+        }
+
+        // The same applies to chars loaded from linear memory.
+        let i = instance(&mut store)?;
+        let list = i
+            .get_typed_func::<(u32, u32), WasmList<char>, _>(&mut store, "chars")?
+            .call(&mut store, (300, chars.len() as u32))?;
+        let loaded = list.iter(&store).collect::<Vec<_>>();
+        assert_eq!(*loaded[0].as_ref().unwrap(), 'A');
+        for c in &loaded[1..] {
+            match c {
+                Ok(c) => {
+                    assert!(lossy);
+                    assert_eq!(*c, '\u{fffd}');
+                }
+                Err(_) => assert!(!lossy),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn wasm_str_debug_and_display() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "utf8") (param string) (result string)
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "utf16") (param string) (result string)
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                    string-encoding=utf16
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let other_store = Store::new(&engine, ());
+
+    let input = format!("tab\there \"quoted\" é{}", "x".repeat(100));
+    let preview = format!("\"tab\\there \\\"quoted\\\" é{}\"…", "x".repeat(45));
+
+    for (name, encoding, len) in [
+        ("utf8", "Utf8", input.len()),
+        ("utf16", "Utf16", input.encode_utf16().count()),
+    ] {
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let echo = instance.get_typed_func::<(&str,), WasmStr, _>(&mut store, name)?;
+        let s = echo.call_and_post_return(&mut store, (input.as_str(),))?;
+
+        // Without a store only the location and encoding are known.
+        assert_eq!(
+            format!("{:?}", s),
+            format!("WasmStr {{ ptr: 8, len: {}, encoding: {} }}", len, encoding)
+        );
+
+        // The preview is escaped and truncated.
+        assert_eq!(s.display(&store).to_string(), preview);
+        assert_eq!(format!("{:?}", s.display(&store)), preview);
+        assert_eq!(s.display(&store).max_chars(5).to_string(), "\"tab\\th\"…");
+        assert_eq!(s.display(&store).max_chars(0).to_string(), "\"\"…");
+        assert_eq!(
+            s.display(&store).max_chars(1000).to_string(),
+            format!("{:?}", input)
+        );
+
+        // Strings which can't be read show the error instead.
+        assert_eq!(
+            s.display(&other_store).to_string(),
+            "<invalid: value belongs to a different store>"
+        );
+
+        // Strings that fit aren't marked as truncated.
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let echo = instance.get_typed_func::<(&str,), WasmStr, _>(&mut store, name)?;
+        let s = echo.call_and_post_return(&mut store, ("hello",))?;
+        assert_eq!(s.display(&store).max_chars(5).to_string(), "\"hello\"");
+        assert_eq!(s.display(&store).max_chars(4).to_string(), "\"hell\"…");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn wasm_list_debug() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "echo") (param (list u32)) (result (list u32))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "as-chars") (param (list u32)) (result (list char))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let other_store = Store::new(&engine, ());
+
+    let echo = |store: &mut Store<()>, list: &[u32]| -> Result<WasmList<u32>> {
+        let instance = Linker::new(&engine).instantiate(&mut *store, &component)?;
+        let echo = instance.get_typed_func::<(&[u32],), WasmList<u32>, _>(&mut *store, "echo")?;
+        echo.call_and_post_return(store, (list,))
+    };
+
+    // Without a store only the location and length are known.
+    let empty = echo(&mut store, &[])?;
+    let debug = format!("{:?}", empty);
+    assert!(debug.starts_with("WasmList<u32> { ptr: "), "{}", debug);
+    assert!(debug.ends_with(", len: 0 }"), "{}", debug);
+    assert_eq!(format!("{:?}", empty.debug_with(&store)), "[]");
+    assert_eq!(
+        format!("{:?}", empty.debug_with(&store).max_elements(0)),
+        "[]"
+    );
+
+    let short = echo(&mut store, &[1, 2, 3])?;
+    assert!(format!("{:?}", short).ends_with(", len: 3 }"));
+    assert_eq!(format!("{:?}", short.debug_with(&store)), "[1, 2, 3]");
+    assert_eq!(
+        format!("{:?}", short.debug_with(&store).max_elements(3)),
+        "[1, 2, 3]"
+    );
+    assert_eq!(
+        format!("{:?}", short.debug_with(&store).max_elements(2)),
+        "[1, 2, …]"
+    );
+    assert_eq!(
+        format!("{:#?}", short.debug_with(&store).max_elements(1)),
+        "[\n    1,\n    …,\n]"
+    );
+
+    // Long lists are truncated to the default number of elements.
+    let long = echo(&mut store, &(0..100).collect::<Vec<_>>())?;
+    assert_eq!(
+        format!("{:?}", long.debug_with(&store)),
+        "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, …]"
+    );
+    assert_eq!(
+        format!("{:?}", long.debug_with(&store).max_elements(0)),
+        "[…]"
+    );
+
+    // Lists which can't be read show the error instead.
+    assert_eq!(
+        format!("{:?}", short.debug_with(&other_store)),
+        "<invalid: value belongs to a different store>"
+    );
+
+    // Elements which fail to decode show their error in place.
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let as_chars =
+        instance.get_typed_func::<(&[u32],), WasmList<char>, _>(&mut store, "as-chars")?;
+    let chars = as_chars.call_and_post_return(&mut store, (&[0x61, 0xd800, 0x62][..],))?;
+    assert!(format!("{:?}", chars).starts_with("WasmList<char> { ptr: "));
+    assert_eq!(
+        format!("{:?}", chars.debug_with(&store)),
+        "['a', <invalid: invalid char value 0xd800 loaded from linear memory: \
+         surrogate code points are not valid chars>, 'b']"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn wasm_str_copy_to() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "utf8") (param string) (result string)
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "utf16") (param string) (result string)
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                    string-encoding=utf16
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let other_store = Store::new(&engine, ());
+
+    // "é" is 2 bytes of utf-8 and "😀" is 4, so this has character boundaries
+    // at 0, 1, 3, 4, 8 and 9.
+    let input = "aéb😀c";
+
+    for name in ["utf8", "utf16"] {
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let echo = instance.get_typed_func::<(&str,), WasmStr, _>(&mut store, name)?;
+        let s = echo.call_and_post_return(&mut store, (input,))?;
+        assert_eq!(s.required_capacity(&store)?, input.len());
+
+        // An exact fit copies the whole string.
+        let mut buf = [0xff; 9];
+        assert_eq!(s.copy_to(&store, &mut buf)?, WasmStrCopy::Complete(9));
+        assert_eq!(&buf, input.as_bytes());
+
+        // Extra space is left untouched.
+        let mut buf = [0xff; 12];
+        let copied = s.copy_to(&store, &mut buf)?;
+        assert!(copied.is_complete());
+        assert_eq!(copied.written(), 9);
+        assert_eq!(&buf[..9], input.as_bytes());
+        assert_eq!(buf[9..], [0xff; 3]);
+
+        // Truncation never splits a character.
+        for (size, written) in [
+            (0, 0),
+            (1, 1),
+            (2, 1),
+            (3, 3),
+            (4, 4),
+            (5, 4),
+            (6, 4),
+            (7, 4),
+            (8, 8),
+        ] {
+            let mut buf = vec![0xff; size];
+            let copied = s.copy_to(&store, &mut buf)?;
+            assert_eq!(copied, WasmStrCopy::Partial(written), "{} {}", name, size);
+            assert!(!copied.is_complete());
+            assert_eq!(&buf[..written], input[..written].as_bytes());
+            assert!(buf[written..].iter().all(|b| *b == 0xff));
+        }
+
+        let err = s.copy_to(&other_store, &mut [0; 9]).unwrap_err();
+        assert_eq!(err.to_string(), "value belongs to a different store");
+        let err = s.required_capacity(&other_store).unwrap_err();
+        assert_eq!(err.to_string(), "value belongs to a different store");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn wasm_list_copy_to_slice() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "echo-u32") (param (list u32)) (result (list u32))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "echo-f64") (param (list float64)) (result (list float64))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let other_store = Store::new(&engine, ());
+
+    for len in [0, 1, 7, 1000] {
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let echo =
+            instance.get_typed_func::<(&[u32],), WasmList<u32>, _>(&mut store, "echo-u32")?;
+        let input = (0..len).map(|i| i * 0x10001 + 5).collect::<Vec<u32>>();
+        let list = echo.call_and_post_return(&mut store, (&input,))?;
+        let baseline = list.iter(&store).collect::<Result<Vec<_>>>()?;
+        let mut dst = vec![0; len as usize];
+        list.copy_to_slice(&store, &mut dst)?;
+        assert_eq!(dst, baseline);
+        assert_eq!(dst, input);
+
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let echo =
+            instance.get_typed_func::<(&[f64],), WasmList<f64>, _>(&mut store, "echo-f64")?;
+        let input = (0..len)
+            .map(|i| match i % 4 {
+                0 => f64::from(i) * -1.5,
+                1 => f64::INFINITY,
+                2 => f64::NAN,
+                _ => f64::MIN_POSITIVE,
+            })
+            .collect::<Vec<f64>>();
+        let list = echo.call_and_post_return(&mut store, (&input,))?;
+        let baseline = list.iter(&store).collect::<Result<Vec<_>>>()?;
+        let mut dst = vec![0.0; len as usize];
+        list.copy_to_slice(&store, &mut dst)?;
+        let bits = |floats: &[f64]| floats.iter().map(|f| f.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&dst), bits(&baseline));
+    }
+
+    // The destination must be exactly the length of the list.
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let echo = instance.get_typed_func::<(&[u32],), WasmList<u32>, _>(&mut store, "echo-u32")?;
+    let list = echo.call_and_post_return(&mut store, (&[1, 2, 3][..],))?;
+    for len in [2, 4] {
+        let err = list.copy_to_slice(&store, &mut vec![0; len]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "destination slice has length {} but the list has length 3",
+                len
+            )
+        );
+    }
+    let err = list.copy_to_slice(&other_store, &mut [0; 3]).unwrap_err();
+    assert_eq!(err.to_string(), "value belongs to a different store");
+
+    Ok(())
+}
+
+#[test]
+fn func_options() -> Result<()> {
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+                (func (export "ret") (result i32) i32.const 0)
+                (func (export "nop"))
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "utf16") (param string) (result string)
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                    string-encoding=utf16
+                )
+            )
+            (func (export "utf8-no-realloc") (result string)
+                (canon lift
+                    (core func $i "ret")
+                    (memory $i "memory")
+                )
+            )
+            (func (export "nop")
+                (canon lift (core func $i "nop"))
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    let options = instance
+        .get_func(&mut store, "utf16")
+        .unwrap()
+        .options(&store);
+    assert_eq!(options.string_encoding(), StringEncoding::Utf16);
+    assert!(options.has_memory());
+    assert!(options.has_realloc());
+    assert!(!options.memory64());
+    assert_eq!(
+        format!("{:?}", options),
+        "Options { string_encoding: Utf16, memory: true, realloc: true, memory64: false }"
+    );
+
+    let func = instance.get_typed_func::<(), WasmStr, _>(&mut store, "utf8-no-realloc")?;
+    let options = func.func().options(&store);
+    assert_eq!(options.string_encoding(), StringEncoding::Utf8);
+    assert!(options.has_memory());
+    assert!(!options.has_realloc());
+
+    let options = instance
+        .get_func(&mut store, "nop")
+        .unwrap()
+        .options(&store);
+    assert_eq!(options.string_encoding(), StringEncoding::Utf8);
+    assert!(!options.has_memory());
+    assert!(!options.has_realloc());
+
+    Ok(())
+}
+
+#[test]
+fn memory_from_func_options() -> Result<()> {
+    let component = r#"(component
+        (core module $m
+            (memory (export "memory") 1)
+            (func (export "load") (param i32) (result i32)
+                (i32.load (local.get 0)))
+        )
+        (core instance $i (instantiate $m))
+
+        (func (export "load") (param u32) (result u32)
+            (canon lift (core func $i "load") (memory $i "memory"))
+        )
+        (func (export "no-memory") (param u32) (result u32)
+            (canon lift (core func $i "load"))
+        )
+    )"#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let load = instance.get_typed_func::<(u32,), u32, _>(&mut store, "load")?;
+    let options = load.func().options(&store);
+
+    // Writes are visible to the guest, and out-of-bounds writes are rejected
+    // without modifying memory.
+    let mut memory = MemoryMut::from_options(store.as_context_mut(), &options)?;
+    memory.write(100, b"abcd")?;
+    memory.write_u32(65532, 0x1234_5678)?;
+    let err = memory.write(65533, &[0; 4]).unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<LowerError>(),
+            Some(LowerError::OutOfBounds {
+                what: "write",
+                ptr: 65533,
+                len: 4,
+                memory_size: 65536,
+            })
+        ),
+        "{:?}",
+        err
+    );
+    assert!(memory.write_u32(usize::MAX, 0).is_err());
+    assert_eq!(
+        load.call_and_post_return(&mut store, (100,))?,
+        u32::from_le_bytes(*b"abcd")
+    );
+    assert_eq!(
+        load.call_and_post_return(&mut store, (65532,))?,
+        0x1234_5678
+    );
+
+    let memory = Memory::from_options(&store, &options)?;
+    assert_eq!(memory.read(100, 4)?, b"abcd");
+    assert_eq!(memory.read_u32(65532)?, 0x1234_5678);
+    assert_eq!(memory.read(65536, 0)?, b"");
+    let err = memory.read(65533, 4).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "read pointer/length out of bounds of memory (ptr 0xfffd, len 4, memory size 65536)"
+    );
+    assert!(memory.read_u32(usize::MAX).is_err());
+
+    // Views can only be created for options with a memory in the same store.
+    let options = instance
+        .get_func(&mut store, "no-memory")
+        .unwrap()
+        .options(&store);
+    let err = Memory::from_options(&store, &options).err().unwrap();
+    assert_eq!(err.to_string(), "options don't specify a linear memory");
+
+    let options = load.func().options(&store);
+    let mut other_store = Store::new(&engine, ());
+    let err = Memory::from_options(&other_store, &options).err().unwrap();
+    assert_eq!(err.to_string(), "options belong to a different store");
+    let err = MemoryMut::from_options(other_store.as_context_mut(), &options)
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "options belong to a different store");
+
+    Ok(())
+}
+
+#[test]
+fn lowering_errors_free_allocations() -> Result<()> {
+    // An allocator which tracks the number of live bytes. Freeing returns a
+    // misaligned and out-of-bounds pointer to check that the return value of
+    // a realloc to zero isn't validated.
+    let component = r#"
+        (component
+            (core module $m
+                (memory (export "memory") 1)
+                (global $live (mut i32) (i32.const 0))
+                (global $last (mut i32) (i32.const 8))
+                (func (export "realloc")
+                    (param $old_ptr i32)
+                    (param $old_size i32)
+                    (param $align i32)
+                    (param $new_size i32)
+                    (result i32)
+                    (local $ret i32)
+
+                    (if (local.get $old_ptr)
+                        (then
+                            (if (i32.eqz (local.get $new_size))
+                                (then
+                                    (global.set $live
+                                        (i32.sub (global.get $live) (local.get $old_size)))
+                                    (return (i32.const -1))))
+                            unreachable))
+
+                    ;; align up `$last` and bump it past the new allocation
+                    (local.set $ret
+                        (i32.and
+                            (i32.add (global.get $last) (i32.sub (local.get $align) (i32.const 1)))
+                            (i32.sub (i32.const 0) (local.get $align))))
+                    (global.set $last (i32.add (local.get $ret) (local.get $new_size)))
+                    (global.set $live (i32.add (global.get $live) (local.get $new_size)))
+                    local.get $ret)
+                (func (export "take") (param i32 i32))
+                (func (export "live") (result i32) global.get $live)
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "take") (param (list u32))
+                (canon lift (core func $i "take")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "take-nested") (param (list (list u32)))
+                (canon lift (core func $i "take")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "live") (result u32)
+                (canon lift (core func $i "live"))
+            )
+        )
+    "#;
+
+    // An iterator which yields `items` while reporting a length of `len`.
+    #[derive(Clone)]
+    struct Misreported {
+        items: std::ops::Range<u32>,
+        len: usize,
+    }
+
+    impl Iterator for Misreported {
+        type Item = u32;
+        fn next(&mut self) -> Option<u32> {
+            self.items.next()
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.len, Some(self.len))
+        }
+    }
+
+    impl ExactSizeIterator for Misreported {}
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let take = instance.get_typed_func::<(LowerList<Misreported>,), (), _>(&mut store, "take")?;
+    let take_nested = instance
+        .get_typed_func::<(LowerList<std::iter::Once<LowerList<Misreported>>>,), (), _>(
+            &mut store,
+            "take-nested",
+        )?;
+    let get_live = instance.get_typed_func::<(), u32, _>(&mut store, "live")?;
+    let live = |store: &mut Store<()>| -> Result<u32> {
+        let ret = get_live.call(&mut *store, ())?;
+        get_live.post_return(&mut *store)?;
+        Ok(ret)
+    };
+
+    // A successful call transfers ownership of the list to the guest.
+    take.call(
+        &mut store,
+        (LowerList(Misreported {
+            items: 0..4,
+            len: 4,
+        }),),
+    )?;
+    take.post_return(&mut store)?;
+    assert_eq!(live(&mut store)?, 16);
+
+    let err = take
+        .call(
+            &mut store,
+            (LowerList(Misreported {
+                items: 0..2,
+                len: 4,
+            }),),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "iterator yielded 2 items but reported a length of 4"
+    );
+    assert_eq!(live(&mut store)?, 16);
+
+    let err = take
+        .call(
+            &mut store,
+            (LowerList(Misreported {
+                items: 0..5,
+                len: 3,
+            }),),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "iterator yielded more items than its reported length of 3"
+    );
+    assert_eq!(live(&mut store)?, 16);
+
+    // Both the inner list which failed and the outer list are freed.
+    let inner = LowerList(Misreported {
+        items: 0..1,
+        len: 2,
+    });
+    let err = take_nested
+        .call(&mut store, (LowerList(std::iter::once(inner)),))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "iterator yielded 1 items but reported a length of 2"
+    );
+    assert_eq!(live(&mut store)?, 16);
+
+    Ok(())
+}
+
+#[test]
+fn shared_memory_rejected() -> Result<()> {
+    let component = r#"
+        (component
+            (import "host" (func $host (param string)))
+
+            (core module $mem
+                (memory (export "memory") 1 1 shared)
+            )
+            (core instance $mem (instantiate $mem))
+            (core func $host_lower
+                (canon lower (func $host) (memory $mem "memory"))
+            )
+
+            (core module $m
+                (import "" "memory" (memory 1 1 shared))
+                (import "" "host" (func $host (param i32 i32)))
+                (func (export "echo") (param i32) (result i32)
+                    local.get 0)
+                (func (export "call-host")
+                    (call $host (i32.const 0) (i32.const 0)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "memory" (memory $mem "memory"))
+                    (export "host" (func $host_lower))
+                ))
+            ))
+
+            (func (export "echo") (param u32) (result u32)
+                (canon lift (core func $i "echo") (memory $mem "memory"))
+            )
+            (func (export "call-host")
+                (canon lift (core func $i "call-host"))
+            )
+        )
+    "#;
+
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.wasm_threads(true);
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap(
+        "host",
+        |_: StoreContextMut<'_, ()>, _: WasmStr| -> Result<()> {
+            panic!("host function should not be called")
+        },
+    )?;
+    let instance = linker.instantiate(&mut store, &component)?;
+
+    let expected = "shared memories are not supported by the component canonical ABI \
+                    host implementation";
+
+    // Calls are rejected even though only scalars are passed, and the failure
+    // doesn't leave the instance unusable.
+    let echo = instance.get_typed_func::<(u32,), u32, _>(&mut store, "echo")?;
+    for _ in 0..2 {
+        let err = echo.call(&mut store, (1,)).unwrap_err();
+        assert_eq!(err.to_string(), expected);
+    }
+    let err = instance
+        .get_func(&mut store, "echo")
+        .unwrap()
+        .call(&mut store, &[Val::U32(1)])
+        .unwrap_err();
+    assert_eq!(err.to_string(), expected);
+
+    // Host functions lowered with a shared memory fail before they're invoked.
+    let call_host = instance.get_typed_func::<(), (), _>(&mut store, "call-host")?;
+    let err = call_host.call(&mut store, ()).unwrap_err();
+    assert!(err.to_string().contains(expected), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn large_copies_are_interruptible() -> Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    let component = format!(
+        r#"
+        (component
+            (core module $m
+                (memory (export "memory") 200)
+                (func (export "take") (param i32 i32))
+                (func (export "list") (param i32) (result i32)
+                    (i32.store (i32.const 0) (i32.const 1024))
+                    (i32.store (i32.const 4) (local.get 0))
+                    i32.const 0)
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "take") (param (list u32))
+                (canon lift (core func $i "take")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "list") (param u32) (result (list u32))
+                (canon lift (core func $i "list") (memory $i "memory"))
+            )
+        )
+    "#
+    );
+
+    // An iterator which increments the engine's epoch, as another thread
+    // would, once it yields `at`, and which counts the items pulled from it.
+    #[derive(Clone)]
+    struct Ticking {
+        engine: wasmtime::Engine,
+        items: std::ops::Range<u32>,
+        at: u32,
+        pulled: Arc<AtomicUsize>,
+    }
+
+    impl Iterator for Ticking {
+        type Item = u32;
+        fn next(&mut self) -> Option<u32> {
+            let item = self.items.next()?;
+            if item == self.at {
+                self.engine.increment_epoch();
+            }
+            self.pulled.fetch_add(1, SeqCst);
+            Some(item)
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.items.size_hint()
+        }
+    }
+
+    impl ExactSizeIterator for Ticking {}
+
+    const LEN: u32 = 1 << 20;
+    const INTERVAL: usize = 1 << 16;
+
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.epoch_interruption(true);
+    config.component_model_copy_check_interval(INTERVAL);
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let take = instance.get_typed_func::<(LowerList<Ticking>,), (), _>(&mut store, "take")?;
+    let list = instance.get_typed_func::<(u32,), WasmList<u32>, _>(&mut store, "list")?;
+    let ticking = |at| Ticking {
+        engine: engine.clone(),
+        items: 0..LEN,
+        at,
+        pulled: Arc::new(AtomicUsize::new(0)),
+    };
+
+    // Without reaching the deadline the whole list is lowered.
+    let items = ticking(LEN);
+    take.call(&mut store, (LowerList(items.clone()),))?;
+    take.post_return(&mut store)?;
+    assert_eq!(items.pulled.load(SeqCst), LEN as usize);
+
+    // Reaching the deadline partway through lowering traps at the next check,
+    // long before the rest of the list is copied.
+    let items = ticking(100_000);
+    let err = take
+        .call(&mut store, (LowerList(items.clone()),))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<Trap>()?.trap_code(),
+        Some(TrapCode::Interrupt)
+    );
+    let pulled = items.pulled.load(SeqCst);
+    assert!(
+        pulled > 100_000 && pulled <= 100_000 + INTERVAL / 4,
+        "{pulled}"
+    );
+
+    // Lifting a list checks the deadline in the same way, while ranges no
+    // larger than the interval aren't checked.
+    store.set_epoch_deadline(1);
+    let ret = list.call(&mut store, (LEN,))?;
+    engine.increment_epoch();
+    assert_eq!(ret.get_range(&store, 0..INTERVAL / 4)?.len(), INTERVAL / 4);
+    let err = ret.to_vec(&store).unwrap_err();
+    assert_eq!(
+        err.downcast::<Trap>()?.trap_code(),
+        Some(TrapCode::Interrupt)
+    );
+    list.post_return(&mut store)?;
+
+    Ok(())
+}
+
+#[test]
+fn large_lifts_of_results_are_interruptible() -> Result<()> {
+    // `$tick` reaches the epoch deadline after the core function has been
+    // entered, so it only traps once the result is lifted.
+    let component = r#"
+        (component
+            (import "tick" (func $tick))
+            (core func $tick_lower
+                (canon lower (func $tick))
+            )
+            (core module $m
+                (import "" "tick" (func $tick))
+                (memory (export "memory") 200)
+                (func (export "get") (param i32) (result i32)
+                    call $tick
+                    (i32.store (i32.const 0) (i32.const 1024))
+                    (i32.store (i32.const 4) (local.get 0))
+                    i32.const 0)
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance (export "tick" (func $tick_lower))))
+            ))
+
+            (type $pair (record (field "a" u32) (field "b" u32)))
+            (func (export "list") (param u32) (result (list u32))
+                (canon lift (core func $i "get") (memory $i "memory"))
+            )
+            (func (export "pairs") (param u32) (result (list $pair))
+                (canon lift (core func $i "get") (memory $i "memory"))
+            )
+            (func (export "string") (param u32) (result string)
+                (canon lift (core func $i "get") (memory $i "memory"))
+            )
+        )
+    "#;
+
+    #[derive(ComponentType, Lift, Debug)]
+    #[component(record)]
+    #[component(pod)]
+    #[repr(C)]
+    struct Pair {
+        a: u32,
+        b: u32,
+    }
+
+    const LEN: u32 = 1 << 20;
+
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.epoch_interruption(true);
+    config.component_model_copy_check_interval(1 << 16);
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let mut linker = Linker::new(&engine);
+    let ticker = engine.clone();
+    linker
+        .root()
+        .func_wrap("tick", move |_: StoreContextMut<'_, ()>| -> Result<()> {
+            ticker.increment_epoch();
+            Ok(())
+        })?;
+    let instance = |store: &mut Store<()>| linker.instantiate(store, &component);
+
+    fn assert_interrupted<T: std::fmt::Debug>(result: Result<T>) {
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.downcast::<Trap>().unwrap().trap_code(),
+            Some(TrapCode::Interrupt)
+        );
+    }
+
+    // Small results are lifted without checking the deadline.
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let list = instance(&mut store)?.get_typed_func::<(u32,), Vec<u32>, _>(&mut store, "list")?;
+    assert_eq!(list.call(&mut store, (1000,))?, vec![0; 1000]);
+
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let pairs =
+        instance(&mut store)?.get_typed_func::<(u32,), Vec<Pair>, _>(&mut store, "pairs")?;
+    let ret = pairs.call(&mut store, (1000,))?;
+    assert!(ret.len() == 1000 && ret.iter().all(|p| p.a == 0 && p.b == 0));
+
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let list = instance(&mut store)?.get_typed_func::<(u32,), Vec<u32>, _>(&mut store, "list")?;
+    assert_interrupted(list.call(&mut store, (LEN,)));
+
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let pairs =
+        instance(&mut store)?.get_typed_func::<(u32,), Vec<Pair>, _>(&mut store, "pairs")?;
+    assert_interrupted(pairs.call(&mut store, (LEN,)));
+
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let string = instance(&mut store)?.get_typed_func::<(u32,), String, _>(&mut store, "string")?;
+    assert_interrupted(string.call(&mut store, (LEN,)));
+
+    Ok(())
+}
+
+#[test]
+fn large_copies_consume_fuel() -> Result<()> {
+    let component = format!(
+        r#"
+        (component
+            (core module $m
+                (memory (export "memory") 200)
+                (func (export "take") (param i32 i32))
+                (func (export "list") (param i32) (result i32)
+                    (i32.store (i32.const 0) (i32.const 1024))
+                    (i32.store (i32.const 4) (local.get 0))
+                    i32.const 0)
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "take") (param (list u32))
+                (canon lift (core func $i "take")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "list") (param u32) (result (list u32))
+                (canon lift (core func $i "list") (memory $i "memory"))
+            )
+        )
+    "#
+    );
+
+    // Every 16 elements copied consume one unit of fuel, so copying `LEN`
+    // elements needs far more fuel than the store is given.
+    const LEN: u32 = 1 << 20;
+    const INTERVAL: usize = 64;
+    const FUEL: u64 = 10_000;
+
+    fn assert_out_of_fuel<T: std::fmt::Debug>(result: Result<T>) {
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("all fuel consumed"), "bad error: {err}");
+    }
+
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.consume_fuel(true);
+    config.component_model_copy_check_interval(INTERVAL);
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let linker = Linker::new(&engine);
+
+    // Lowering a list consumes fuel for each check and traps once it runs
+    // out.
+    let mut store = Store::new(&engine, ());
+    store.add_fuel(FUEL)?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    let take = instance.get_typed_func::<(&[u32],), (), _>(&mut store, "take")?;
+    let before = store.fuel_consumed().unwrap();
+    take.call(&mut store, (&[0; 1000],))?;
+    take.post_return(&mut store)?;
+    let consumed = store.fuel_consumed().unwrap() - before;
+    assert!(consumed >= 1000 / 16 - 1, "{consumed}");
+    assert_out_of_fuel(take.call(&mut store, (&vec![0u32; LEN as usize],)));
+
+    // Lifting a list only has a shared borrow of the store, but fuel is
+    // consumed all the same and is reflected in the store once the lift is
+    // done.
+    let mut store = Store::new(&engine, ());
+    store.add_fuel(FUEL)?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    let list = instance.get_typed_func::<(u32,), WasmList<u32>, _>(&mut store, "list")?;
+    let ret = list.call(&mut store, (LEN,))?;
+    let before = store.fuel_consumed().unwrap();
+    assert_eq!(ret.get_range(&store, 0..1000)?.len(), 1000);
+    let consumed = store.fuel_consumed().unwrap() - before;
+    assert!(consumed >= 1000 / 16 - 1, "{consumed}");
+    assert_out_of_fuel(ret.to_vec(&store));
+    list.post_return(&mut store)?;
+
+    // Fuel consumed while lifting is charged before WebAssembly runs again,
+    // so the store remains out of fuel.
+    assert_out_of_fuel(list.call(&mut store, (1,)));
+
+    Ok(())
+}
+
+#[test]
+fn maps() -> Result<()> {
+    use std::collections::{BTreeMap, HashMap};
+
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "roundtrip") (param i32 i32) (result i32)
+                    (local $base i32)
+                    (local.set $base
+                        (call $realloc
+                            (i32.const 0)
+                            (i32.const 0)
+                            (i32.const 4)
+                            (i32.const 8)))
+                    (i32.store offset=0
+                        (local.get $base)
+                        (local.get 0))
+                    (i32.store offset=4
+                        (local.get $base)
+                        (local.get 1))
+                    (local.get $base)
+                )
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+
+            (func (export "string-keys")
+                (param (list (tuple string u32)))
+                (result (list (tuple string u32)))
+                (canon lift
+                    (core func $i "roundtrip")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "u32-keys")
+                (param (list (tuple u32 u32)))
+                (result (list (tuple u32 u32)))
+                (canon lift
+                    (core func $i "roundtrip")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, &component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    // Maps round-trip through `list<tuple<K, V>>`.
+    let hash_map = instance.get_typed_func::<(&HashMap<String, u32>,), HashMap<String, u32>, _>(
+        &mut store,
+        "string-keys",
+    )?;
+    let map = HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+    assert_eq!(hash_map.call(&mut store, (&map,))?, map);
+    hash_map.post_return(&mut store)?;
+    let empty = HashMap::new();
+    assert_eq!(hash_map.call(&mut store, (&empty,))?, empty);
+    hash_map.post_return(&mut store)?;
+
+    let btree_map = instance
+        .get_typed_func::<(&BTreeMap<u32, u32>,), BTreeMap<u32, u32>, _>(&mut store, "u32-keys")?;
+    let map = BTreeMap::from([(3, 30), (1, 10), (2, 20)]);
+    assert_eq!(btree_map.call(&mut store, (&map,))?, map);
+    btree_map.post_return(&mut store)?;
+
+    // Entries are lowered in the map's iteration order.
+    let to_list = instance
+        .get_typed_func::<(&BTreeMap<u32, u32>,), Vec<(u32, u32)>, _>(&mut store, "u32-keys")?;
+    assert_eq!(
+        to_list.call(&mut store, (&map,))?,
+        [(1, 10), (2, 20), (3, 30)]
+    );
+    to_list.post_return(&mut store)?;
+
+    // The last entry wins when the guest produces duplicate keys.
+    let duplicates = [(1, 10), (2, 20), (1, 11)];
+    let to_hash_map = instance
+        .get_typed_func::<(&[(u32, u32)],), HashMap<u32, u32>, _>(&mut store, "u32-keys")?;
+    assert_eq!(
+        to_hash_map.call(&mut store, (&duplicates[..],))?,
+        HashMap::from([(1, 11), (2, 20)])
+    );
+    to_hash_map.post_return(&mut store)?;
+    let to_btree_map = instance
+        .get_typed_func::<(&[(u32, u32)],), BTreeMap<u32, u32>, _>(&mut store, "u32-keys")?;
+    assert_eq!(
+        to_btree_map.call(&mut store, (&duplicates[..],))?,
+        BTreeMap::from([(1, 11), (2, 20)])
+    );
+    to_btree_map.post_return(&mut store)?;
+
+    // Maps are typechecked as lists of tuples.
+    let err = instance
+        .get_func(&mut store, "u32-keys")
+        .unwrap()
+        .typed::<(&HashMap<String, u32>,), (), _>(&store)
+        .err()
+        .unwrap();
+    assert!(
+        err.to_string()
+            .contains("requested `(list<tuple<string, u32>>)`"),
+        "{}",
+        err
+    );
+
+    // Duplicate keys are an error with strict validation, while maps without
+    // duplicates are still lifted.
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.component_model_strict_validation(true);
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(&engine, &component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = |store: &mut Store<()>| Linker::new(&engine).instantiate(store, &component);
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(&[(u32, u32)],), HashMap<u32, u32>, _>(&mut store, "u32-keys")?
+        .call(&mut store, (&duplicates[..],))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LiftError>(),
+        Some(&LiftError::DuplicateMapKey { index: 2, len: 3 })
+    );
+
+    let err = instance(&mut store)?
+        .get_typed_func::<(&[(u32, u32)],), BTreeMap<u32, u32>, _>(&mut store, "u32-keys")?
+        .call(&mut store, (&duplicates[..],))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "duplicate map key at entry 2 of a list of 3 entries"
+    );
+
+    let btree_map = instance(&mut store)?
+        .get_typed_func::<(&BTreeMap<u32, u32>,), BTreeMap<u32, u32>, _>(&mut store, "u32-keys")?;
+    let map = BTreeMap::from([(1, 10), (2, 20)]);
+    assert_eq!(btree_map.call(&mut store, (&map,))?, map);
+    btree_map.post_return(&mut store)?;
+
+    Ok(())
+}
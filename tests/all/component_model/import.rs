@@ -663,3 +663,221 @@ fn no_actual_wasm_code() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn linker_func_wrap() -> Result<()> {
+    let component = format!(
+        r#"
+(component
+  (type $greeting (record (field "message" string) (field "length" u32)))
+  (import "host" (instance $host
+    (export "log" (func (param u32) (param string)))
+    (export "greet" (func (param string) (result $greeting)))
+  ))
+
+  (core module $libc
+    {REALLOC_AND_FREE}
+    (memory (export "memory") 1)
+  )
+  (core instance $libc (instantiate (module $libc)))
+
+  (core func $log_lower
+    (canon lower (func $host "log") (memory $libc "memory") (realloc (func $libc "realloc")))
+  )
+  (core func $greet_lower
+    (canon lower (func $host "greet") (memory $libc "memory") (realloc (func $libc "realloc")))
+  )
+
+  (core module $m
+    (import "host" "log" (func $log (param i32 i32 i32)))
+    (import "host" "greet" (func $greet (param i32 i32 i32)))
+    (import "libc" "memory" (memory 1))
+
+    (func (export "run") (param $ptr i32) (param $len i32) (result i32)
+      (call $log (i32.const 2) (local.get $ptr) (local.get $len))
+      (call $greet (local.get $ptr) (local.get $len) (i32.const 32768))
+      i32.const 32768)
+  )
+  (core instance $m (instantiate $m
+    (with "libc" (instance $libc))
+    (with "host" (instance
+      (export "log" (func $log_lower))
+      (export "greet" (func $greet_lower))
+    ))
+  ))
+
+  (func (export "run") (param string) (result $greeting)
+    (canon lift (core func $m "run") (memory $libc "memory") (realloc (func $libc "realloc")))
+  )
+)
+        "#
+    );
+
+    #[derive(ComponentType, Lift, Lower, Debug, PartialEq)]
+    #[component(record)]
+    struct Greeting {
+        message: String,
+        length: u32,
+    }
+
+    let engine = super::engine();
+    let mut linker = Linker::<Vec<String>>::new(&engine);
+    linker.func_wrap(
+        "host",
+        "log",
+        |mut cx: StoreContextMut<'_, Vec<String>>, (level, msg): (u32, String)| -> Result<()> {
+            cx.data_mut().push(format!("{}: {}", level, msg));
+            Ok(())
+        },
+    )?;
+    // A second function is added to the same instance.
+    linker.func_wrap(
+        "host",
+        "greet",
+        |cx: StoreContextMut<'_, Vec<String>>, (name,): (WasmStr,)| -> Result<Greeting> {
+            let name = name.to_str(&cx)?;
+            Ok(Greeting {
+                message: format!("hello, {}!", name),
+                length: u32::try_from(name.len())?,
+            })
+        },
+    )?;
+    // Redefining a function is an error unless shadowing is allowed.
+    assert!(linker
+        .func_wrap(
+            "host",
+            "greet",
+            |_: StoreContextMut<'_, Vec<String>>, (): ()| -> Result<()> { Ok(()) },
+        )
+        .is_err());
+
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, Vec::new());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(&str,), Greeting, _>(&mut store, "run")?;
+    let greeting = run.call_and_post_return(&mut store, ("world",))?;
+    assert_eq!(
+        greeting,
+        Greeting {
+            message: "hello, world!".to_string(),
+            length: 5,
+        }
+    );
+    assert_eq!(store.data(), &["2: world"]);
+
+    // A function whose signature doesn't match the import is rejected at
+    // instantiation.
+    let mut linker = Linker::<Vec<String>>::new(&engine);
+    linker.func_wrap(
+        "host",
+        "log",
+        |_: StoreContextMut<'_, Vec<String>>, (_msg,): (String,)| -> Result<()> { Ok(()) },
+    )?;
+    linker.func_wrap(
+        "host",
+        "greet",
+        |_: StoreContextMut<'_, Vec<String>>, (_name,): (String,)| -> Result<Greeting> {
+            unreachable!()
+        },
+    )?;
+    assert!(linker.instantiate(&mut store, &component).is_err());
+
+    // Functions can't be defined within a name that isn't an instance.
+    let mut linker = Linker::<Vec<String>>::new(&engine);
+    linker
+        .root()
+        .func_wrap("host", || -> Result<()> { Ok(()) })?;
+    let err = linker
+        .func_wrap(
+            "host",
+            "log",
+            |_: StoreContextMut<'_, Vec<String>>, (): ()| -> Result<()> { Ok(()) },
+        )
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("import of `host` is already defined as something other than an instance"),
+        "{}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn host_result_errors_and_traps() -> Result<()> {
+    let component = format!(
+        r#"
+(component
+  (type $result (expected u32 string))
+  (import "f" (func $f (param u32) (result $result)))
+
+  (core module $libc
+    {REALLOC_AND_FREE}
+    (memory (export "memory") 1)
+  )
+  (core instance $libc (instantiate (module $libc)))
+
+  (core func $f_lower
+    (canon lower (func $f) (memory $libc "memory") (realloc (func $libc "realloc")))
+  )
+
+  (core module $m
+    (import "host" "f" (func $f (param i32 i32)))
+    (import "libc" "memory" (memory 1))
+
+    (func (export "run") (param i32) (result i32)
+      (call $f (local.get 0) (i32.const 32768))
+      i32.const 32768)
+  )
+  (core instance $m (instantiate $m
+    (with "libc" (instance $libc))
+    (with "host" (instance (export "f" (func $f_lower))))
+  ))
+
+  (func (export "run") (param u32) (result $result)
+    (canon lift (core func $m "run") (memory $libc "memory") (realloc (func $libc "realloc")))
+  )
+)
+        "#
+    );
+
+    let engine = super::engine();
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap("f", |x: u32| -> Result<Result<u32, String>> {
+            match x {
+                // A successful result is lowered into the `ok` case...
+                0 => Ok(Ok(42)),
+                // ... an error for the guest into the `err` case ...
+                1 => Ok(Err("not found".to_string())),
+                // ... and an error of the host function itself traps.
+                _ => Err(anyhow::anyhow!("host failure {}", x)),
+            }
+        })?;
+    let component = Component::new(&engine, component)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(u32,), Result<u32, String>, _>(&mut store, "run")?;
+
+    assert_eq!(run.call_and_post_return(&mut store, (0,))?, Ok(42));
+    assert_eq!(
+        run.call_and_post_return(&mut store, (1,))?,
+        Err("not found".to_string())
+    );
+
+    let trap = run.call(&mut store, (2,)).unwrap_err().downcast::<Trap>()?;
+    assert!(
+        trap.to_string().contains("host failure 2"),
+        "bad trap: {}",
+        trap
+    );
+
+    // The trap unwound through the guest, so the instance can't be entered
+    // again.
+    assert!(run.call(&mut store, (0,)).is_err());
+
+    Ok(())
+}
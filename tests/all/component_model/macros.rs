@@ -1,7 +1,7 @@
-use super::{make_echo_component, TypedFuncExt};
+use super::{make_echo_component, make_echo_component_with_params, Param, Type, REALLOC_AND_FREE};
 use anyhow::Result;
 use component_macro_test::{add_variants, flags_test};
-use wasmtime::component::{Component, ComponentType, Lift, Linker, Lower};
+use wasmtime::component::{Component, ComponentType, Lift, Linker, Lower, WasmList};
 use wasmtime::Store;
 
 #[test]
@@ -110,6 +110,39 @@ fn record_derive() -> Result<()> {
 
     assert_eq!(input, output);
 
+    // Happy path: `PhantomData` fields are skipped and their type parameters
+    // don't need to implement any component traits
+
+    #[derive(PartialEq, Eq, Debug, Copy, Clone)]
+    struct Marker;
+
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(record)]
+    struct Tagged<T, M> {
+        #[component(name = "foo-bar-baz")]
+        a: T,
+        _marker: std::marker::PhantomData<M>,
+        b: T,
+    }
+
+    let input = Tagged::<i32, Marker> {
+        a: -44,
+        _marker: std::marker::PhantomData,
+        b: 75,
+    };
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(r#"(record (field "foo-bar-baz" s32) (field "b" s32))"#, 8),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    let output = instance
+        .get_typed_func::<(Tagged<i32, Marker>,), Tagged<i32, Marker>, _>(&mut store, "echo")?
+        .call_and_post_return(&mut store, (input,))?;
+
+    assert_eq!(input, output);
+
     Ok(())
 }
 
@@ -143,9 +176,14 @@ fn union_derive() -> Result<()> {
     let component = Component::new(&engine, make_echo_component("(union s32 u32)", 8))?;
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
 
-    assert!(instance
+    let err = instance
         .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
-        .is_err());
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[0]: expected union of 3 types, found 2 types in `union { s32, u32 }`"
+    );
 
     // Sad path: case count mismatch (too many)
 
@@ -168,9 +206,31 @@ fn union_derive() -> Result<()> {
     let component = Component::new(&engine, make_echo_component("(union s32 s32 s32)", 8))?;
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
 
-    assert!(instance
+    let err = instance
         .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
-        .is_err());
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[0] (case 1): expected `u32` found `s32`"
+    );
+
+    // Sad path: case type mismatch, with a composite payload
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(r#"(union s32 (record (field "a" u32)) s32)"#, 8),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    let err = instance
+        .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[0] (case 1): expected `u32` found `record { a: u32 }`"
+    );
 
     // Happy path redux, with generics this time
 
@@ -241,9 +301,14 @@ fn variant_derive() -> Result<()> {
     )?;
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
 
-    assert!(instance
+    let err = instance
         .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
-        .is_err());
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[0]: expected variant of 3 cases, found 2 cases:\n  - C"
+    );
 
     // Sad path: case count mismatch (too many)
 
@@ -271,9 +336,14 @@ fn variant_derive() -> Result<()> {
     )?;
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
 
-    assert!(instance
+    let err = instance
         .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
-        .is_err());
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[0]: expected variant case 0 to be named `foo-bar-baz`, found `A`"
+    );
 
     // Sad path: case type mismatch
 
@@ -286,9 +356,14 @@ fn variant_derive() -> Result<()> {
     )?;
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
 
-    assert!(instance
+    let err = instance
         .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
-        .is_err());
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[0] (case B): expected `u32` found `s32`"
+    );
 
     // Happy path redux, with generics this time
 
@@ -321,6 +396,511 @@ fn variant_derive() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn generic_variant_payload_derive() -> Result<()> {
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(record)]
+    struct Pair<T> {
+        a: T,
+        b: T,
+    }
+
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(variant)]
+    enum Shape<T> {
+        #[component(name = "point")]
+        Point(Pair<T>),
+        #[component(name = "scalar")]
+        Scalar(T),
+        #[component(name = "empty")]
+        Empty,
+    }
+
+    let engine = super::engine();
+    let mut store = Store::new(&engine, ());
+    let shape = r#"(variant
+        (case "point" (record (field "a" s32) (field "b" s32)))
+        (case "scalar" s32)
+        (case "empty" unit)
+    )"#;
+    let inputs = [
+        Shape::Point(Pair { a: -1, b: 2 }),
+        Shape::Scalar(-3),
+        Shape::Empty,
+    ];
+
+    // Happy path: the payloads are lowered as parameters and lifted back out
+    // of linear memory
+
+    let component = Component::new(&engine, make_echo_component(shape, 12))?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(Shape<i32>,), Shape<i32>, _>(&mut store, "echo")?;
+
+    for &input in &inputs {
+        let output = func.call_and_post_return(&mut store, (input,))?;
+
+        assert_eq!(input, output);
+    }
+
+    // Happy path: the payloads are stored into and loaded from linear memory
+    // as list elements
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(&format!("(list {})", shape), 8),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func =
+        instance.get_typed_func::<(&[Shape<i32>],), WasmList<Shape<i32>>, _>(&mut store, "echo")?;
+    let output = func.call(&mut store, (&inputs[..],))?;
+    assert_eq!(output.to_vec(&store)?, inputs);
+    func.post_return(&mut store)?;
+
+    // Sad path: the payload's type parameter is instantiated with the wrong
+    // type
+
+    let component = Component::new(&engine, make_echo_component(shape, 12))?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    assert!(instance
+        .get_typed_func::<(Shape<u32>,), Shape<u32>, _>(&mut store, "echo")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn lift_lower_derive_with_handwritten_component_type() -> Result<()> {
+    use wasmtime::component::__internal::{typecheck_record, ComponentTypes, InterfaceType};
+
+    // Only `Lift` and `Lower` are derived here, so they can't rely on
+    // anything generated by `#[derive(ComponentType)]`.
+    #[derive(Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(record)]
+    struct Foo {
+        a: u8,
+        b: u32,
+    }
+
+    #[derive(Copy, Clone)]
+    #[repr(C)]
+    struct LowerFoo {
+        a: <u8 as ComponentType>::Lower,
+        b: <u32 as ComponentType>::Lower,
+    }
+
+    unsafe impl ComponentType for Foo {
+        type Lower = LowerFoo;
+
+        const SIZE32: usize = 8;
+        const ALIGN32: u32 = 4;
+
+        fn typecheck(ty: &InterfaceType, types: &ComponentTypes) -> Result<()> {
+            typecheck_record(ty, types, &[("a", u8::typecheck), ("b", u32::typecheck)])
+        }
+    }
+
+    let engine = super::engine();
+    let mut store = Store::new(&engine, ());
+    let record = r#"(record (field "a" u8) (field "b" u32))"#;
+    let inputs = [
+        Foo { a: 1, b: 2 },
+        Foo {
+            a: 0xff,
+            b: u32::MAX,
+        },
+    ];
+
+    let component = Component::new(&engine, make_echo_component(record, 8))?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")?;
+
+    for &input in &inputs {
+        let output = func.call_and_post_return(&mut store, (input,))?;
+
+        assert_eq!(input, output);
+    }
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(&format!("(list {})", record), 8),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(&[Foo],), WasmList<Foo>, _>(&mut store, "echo")?;
+    let output = func.call(&mut store, (&inputs[..],))?;
+    assert_eq!(output.to_vec(&store)?, inputs);
+    func.post_return(&mut store)?;
+
+    Ok(())
+}
+
+#[test]
+fn rename_all_derive() -> Result<()> {
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(record)]
+    #[component(rename_all = "kebab-case")]
+    struct Settings {
+        is_enabled: bool,
+        #[component(name = "limit")]
+        max_count: u32,
+    }
+
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(rename_all = "kebab-case")]
+    #[component(variant)]
+    enum Lookup {
+        Found(u32),
+        NotFound,
+        HTTPError(u32),
+    }
+
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(enum)]
+    #[component(rename_all = "kebab-case")]
+    enum Level {
+        VeryLow,
+        #[component(name = "medium")]
+        Mid,
+    }
+
+    let engine = super::engine();
+    let mut store = Store::new(&engine, ());
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(
+            r#"(record (field "is-enabled" bool) (field "limit" u32))"#,
+            8,
+        ),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let input = Settings {
+        is_enabled: true,
+        max_count: 42,
+    };
+    let output = instance
+        .get_typed_func::<(Settings,), Settings, _>(&mut store, "echo")?
+        .call_and_post_return(&mut store, (input,))?;
+    assert_eq!(input, output);
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(
+            r#"(variant (case "found" u32) (case "not-found" unit) (case "http-error" u32))"#,
+            8,
+        ),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(Lookup,), Lookup, _>(&mut store, "echo")?;
+    for &input in &[Lookup::Found(1), Lookup::NotFound, Lookup::HTTPError(404)] {
+        let output = func.call_and_post_return(&mut store, (input,))?;
+        assert_eq!(input, output);
+    }
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(r#"(enum "very-low" "medium")"#, 4),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(Level,), Level, _>(&mut store, "echo")?;
+    for &input in &[Level::VeryLow, Level::Mid] {
+        let output = func.call_and_post_return(&mut store, (input,))?;
+        assert_eq!(input, output);
+    }
+
+    // Sad path: names without the rename rule applied don't match
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(
+            r#"(record (field "is_enabled" bool) (field "limit" u32))"#,
+            8,
+        ),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    assert!(instance
+        .get_typed_func::<(Settings,), Settings, _>(&mut store, "echo")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn field_order_derive() -> Result<()> {
+    // Note that the Rust declaration order here is intentionally different
+    // from the order in the component.
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(record)]
+    #[component(field_order = "first, second")]
+    struct Foo {
+        second: u32,
+        #[component(name = "first")]
+        a: i32,
+    }
+
+    let engine = super::engine();
+    let mut store = Store::new(&engine, ());
+
+    // Happy path: fields are lifted and lowered in the component's order
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(r#"(record (field "first" s32) (field "second" u32))"#, 8),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    let input = Foo { second: 73, a: -42 };
+    let output = instance
+        .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")?
+        .call_and_post_return(&mut store, (input,))?;
+    assert_eq!(input, output);
+
+    let component = r#"
+        (component
+            (core module $m
+                (func (export "first") (param i32 i32) (result i32)
+                    local.get 0)
+            )
+            (core instance $i (instantiate $m))
+            (type $foo (record (field "first" s32) (field "second" u32)))
+            (func (export "first") (param $foo) (result s32)
+                (canon lift (core func $i "first"))
+            )
+        )
+    "#;
+    let component = Component::new(&engine, component)?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let first = instance
+        .get_typed_func::<(Foo,), i32, _>(&mut store, "first")?
+        .call_and_post_return(&mut store, (input,))?;
+    assert_eq!(first, -42);
+
+    // Records stored in linear memory follow the component's order as well
+
+    let component = format!(
+        r#"
+        (component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "first") (param i32 i32) (result i32)
+                    (i32.load (local.get 0)))
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+            (type $foo (record (field "first" s32) (field "second" u32)))
+            (func (export "first") (param (list $foo)) (result s32)
+                (canon lift
+                    (core func $i "first")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )
+    "#
+    );
+    let component = Component::new(&engine, component)?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let first = instance
+        .get_typed_func::<(&[Foo],), i32, _>(&mut store, "first")?
+        .call_and_post_return(&mut store, (&[input][..],))?;
+    assert_eq!(first, -42);
+
+    // Sad path: the component declares the fields in a different order than
+    // `field_order`, which would change the record's layout
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(r#"(record (field "second" u32) (field "first" s32))"#, 8),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "echo").unwrap();
+    let err = func.typed::<(Foo,), Foo, _>(&store).err().unwrap();
+    assert!(
+        format!("{:?}", err).contains("record field named first is declared at position 1"),
+        "{err:?}"
+    );
+
+    // Sad path: mismatched sets of names
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(r#"(record (field "first" s32) (field "third" u32))"#, 8),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "echo").unwrap();
+    let err = func.typed::<(Foo,), Foo, _>(&store).err().unwrap();
+    assert!(
+        format!("{:?}", err).contains("record has unexpected field named third"),
+        "{err:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transparent_derive() -> Result<()> {
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(transparent)]
+    struct UserId(u64);
+
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(transparent)]
+    struct Wrapper<T> {
+        inner: T,
+    }
+
+    #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
+    #[component(record)]
+    struct Point {
+        x: u32,
+        y: u32,
+    }
+
+    let engine = super::engine();
+    let mut store = Store::new(&engine, ());
+
+    // Happy path: the newtype is typechecked, lowered, and lifted as a `u64`
+
+    let component = Component::new(
+        &engine,
+        make_echo_component_with_params("u64", &[Param(Type::I64, Some(0))]),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(UserId,), UserId, _>(&mut store, "echo")?;
+    for input in [UserId(0), UserId(42), UserId(u64::MAX)] {
+        let output = func.call_and_post_return(&mut store, (input,))?;
+        assert_eq!(input, output);
+    }
+
+    // Transparent types can be mixed freely with the types they wrap
+    let func = instance.get_typed_func::<(u64,), UserId, _>(&mut store, "echo")?;
+    assert_eq!(func.call_and_post_return(&mut store, (7,))?, UserId(7));
+    let func = instance.get_typed_func::<(Wrapper<UserId>,), u64, _>(&mut store, "echo")?;
+    assert_eq!(
+        func.call_and_post_return(&mut store, (Wrapper { inner: UserId(8) },))?,
+        8
+    );
+
+    // Sad path: the newtype isn't a record with one field
+
+    let component = Component::new(
+        &engine,
+        make_echo_component_with_params(
+            r#"(record (field "id" u64))"#,
+            &[Param(Type::I64, Some(0))],
+        ),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    assert!(instance
+        .get_typed_func::<(UserId,), UserId, _>(&mut store, "echo")
+        .is_err());
+
+    // Happy path: transparent types forward to compound types too, including
+    // when generic
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(r#"(record (field "x" u32) (field "y" u32))"#, 8),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let input = Wrapper {
+        inner: Point { x: 1, y: 2 },
+    };
+    let output = instance
+        .get_typed_func::<(Wrapper<Point>,), Wrapper<Point>, _>(&mut store, "echo")?
+        .call_and_post_return(&mut store, (input,))?;
+    assert_eq!(input, output);
+
+    Ok(())
+}
+
+#[test]
+fn typecheck_error_path() -> Result<()> {
+    #[derive(ComponentType, Lift, Lower)]
+    #[component(record)]
+    struct Entry {
+        name: String,
+    }
+
+    #[derive(ComponentType, Lift, Lower)]
+    #[component(record)]
+    struct Outer {
+        entries: Vec<Entry>,
+    }
+
+    let component = r#"
+        (component
+            (core module $m
+                (func (export "take") (param i32 i32 i32))
+                (func (export "ret") (result i32) unreachable)
+                (memory (export "memory") 1)
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    unreachable)
+            )
+            (core instance $i (instantiate $m))
+
+            (type $entry (record (field "name" u32)))
+            (type $outer (record (field "entries" (list $entry))))
+            (func (export "take") (param u32) (param $outer)
+                (canon lift (core func $i "take") (memory $i "memory") (realloc (func $i "realloc")))
+            )
+            (func (export "ret") (result (option (tuple u8 $outer)))
+                (canon lift (core func $i "ret") (memory $i "memory") (realloc (func $i "realloc")))
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let mut store = Store::new(&engine, ());
+    let component = Component::new(&engine, component)?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    let take = instance.get_func(&mut store, "take").unwrap();
+    let err = take.typed::<(u32, Outer), (), _>(&store).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch at parameter 2: \
+         have `(u32, record { entries: list<record { name: u32 }> })`, \
+         requested `(u32, Outer)`"
+    );
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[1].entries (list element).name: expected `string` found `u32`"
+    );
+
+    let err = take.typed::<(u32,), (), _>(&store).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch in number of parameters: \
+         have `(u32, record { entries: list<record { name: u32 }> })`, \
+         requested `(u32)`"
+    );
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params: expected 1 types, found 2"
+    );
+
+    let ret = instance.get_func(&mut store, "ret").unwrap();
+    let err = ret
+        .typed::<(), Option<(u8, Outer)>, _>(&store)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch in result: \
+         have `() -> option<tuple<u8, record { entries: list<record { name: u32 }> }>>`, \
+         requested `() -> option<tuple<u8, Outer>>`"
+    );
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for result (option payload).1.entries (list element).name: \
+         expected `string` found `u32`"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn enum_derive() -> Result<()> {
     #[derive(ComponentType, Lift, Lower, PartialEq, Eq, Debug, Copy, Clone)]
@@ -370,18 +950,28 @@ fn enum_derive() -> Result<()> {
     )?;
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
 
-    assert!(instance
+    let err = instance
         .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
-        .is_err());
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[0]: expected enum of 3 names, found 4 names:\n  + D"
+    );
 
     // Sad path: case name mismatch
 
     let component = Component::new(&engine, make_echo_component(r#"(enum "A" "B" "C")"#, 4))?;
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
 
-    assert!(instance
+    let err = instance
         .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
-        .is_err());
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "type mismatch for params[0]: expected enum case 0 to be named `foo-bar-baz`, found `A`"
+    );
 
     // Happy path redux, with large enums (i.e. more than 2^8 cases)
 
@@ -504,6 +1094,24 @@ fn flags() -> Result<()> {
         .get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")
         .is_err());
 
+    // Happy path redux, with a single flag
+
+    flags_test!(Foo1, 1);
+
+    assert_eq!(Foo1::default(), Foo1::F0 & !Foo1::F0);
+    assert_eq!(format!("{:?}", Foo1::F0), "(F0)");
+    assert_eq!(format!("{:?}", Foo1::default()), "()");
+
+    let component = Component::new(&engine, make_echo_component(r#"(flags "F0")"#, 4))?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(Foo1,), Foo1, _>(&mut store, "echo")?;
+
+    for &input in &[Foo1::default(), Foo1::F0] {
+        let output = func.call_and_post_return(&mut store, (input,))?;
+
+        assert_eq!(input, output);
+    }
+
     // Happy path redux, with large flag count (exactly 8)
 
     flags_test!(Foo8Exact, 8);
@@ -745,6 +1353,11 @@ fn flags() -> Result<()> {
         assert_eq!(input, output);
     }
 
+    assert_eq!(
+        format!("{:?}", Foo32Exact::F0 | Foo32Exact::F31),
+        "(F0|F31)"
+    );
+
     // Happy path redux, with large flag count (more than 32)
 
     flags_test!(Foo64, 33);
@@ -772,7 +1385,63 @@ fn flags() -> Result<()> {
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
     let func = instance.get_typed_func::<(Foo64,), Foo64, _>(&mut store, "echo")?;
 
-    for &input in &[Foo64::F0, Foo64::F1, Foo64::F30, Foo64::F31, Foo64::F32] {
+    for &input in &[
+        Foo64::F0,
+        Foo64::F1,
+        Foo64::F30,
+        Foo64::F31,
+        Foo64::F32,
+        Foo64::F0 | Foo64::F32,
+    ] {
+        let output = func.call_and_post_return(&mut store, (input,))?;
+
+        assert_eq!(input, output);
+    }
+
+    assert_eq!(format!("{:?}", Foo64::F32), "(F32)");
+    assert_eq!(format!("{:?}", Foo64::F1 | Foo64::F32), "(F1|F32)");
+
+    // Happy path redux, with large flag count (exactly 64)
+
+    flags_test!(Foo64Exact, 64);
+
+    assert_eq!(
+        Foo64Exact::default(),
+        (Foo64Exact::F0 | Foo64Exact::F62) & Foo64Exact::F63
+    );
+    assert_eq!(
+        Foo64Exact::F0 | Foo64Exact::F63,
+        !((!Foo64Exact::F0) & (!Foo64Exact::F63))
+    );
+    assert_eq!(
+        format!("{:?}", Foo64Exact::F31 | Foo64Exact::F32 | Foo64Exact::F63),
+        "(F31|F32|F63)"
+    );
+
+    let component = Component::new(
+        &engine,
+        make_echo_component(
+            &format!(
+                "(flags {})",
+                (0..64)
+                    .map(|index| format!(r#""F{}""#, index))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            8,
+        ),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(Foo64Exact,), Foo64Exact, _>(&mut store, "echo")?;
+
+    for &input in &[
+        Foo64Exact::F0,
+        Foo64Exact::F31,
+        Foo64Exact::F32,
+        Foo64Exact::F33,
+        Foo64Exact::F63,
+        Foo64Exact::F1 | Foo64Exact::F40 | Foo64Exact::F63,
+    ] {
         let output = func.call_and_post_return(&mut store, (input,))?;
 
         assert_eq!(input, output);
@@ -813,3 +1482,267 @@ fn flags() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn flags_bits() -> Result<()> {
+    wasmtime::component::flags! {
+        Foo {
+            #[component(name = "foo-bar-baz")]
+            const A;
+            const B;
+            const C;
+        }
+    }
+
+    assert_eq!(Foo::A.to_bits(), 0b001);
+    assert_eq!((Foo::B | Foo::C).to_bits(), 0b110);
+    assert_eq!(Foo::all().to_bits(), 0b111);
+
+    // Bits beyond the declared flags are never reported...
+    assert_eq!((!Foo::A).to_bits(), 0b110);
+
+    // ...and are rejected by `from_bits` but masked by `from_bits_truncate`
+    for bits in 0..=0b111 {
+        let flags = Foo::from_bits(bits).unwrap();
+        assert_eq!(flags.to_bits(), bits);
+        assert_eq!(Foo::from_bits_truncate(bits), flags);
+    }
+    assert!(Foo::from_bits(0b1000).is_none());
+    assert!(Foo::from_bits(0xFF).is_none());
+    assert_eq!(Foo::from_bits_truncate(0b1010), Foo::B);
+
+    assert!(Foo::default().is_empty());
+    assert!(!Foo::A.is_empty());
+    assert!((Foo::A | Foo::C).contains(Foo::A));
+    assert!((Foo::A | Foo::C).contains(Foo::A | Foo::C));
+    assert!(!(Foo::A | Foo::C).contains(Foo::B));
+    assert!(Foo::A.contains(Foo::default()));
+
+    assert_eq!(
+        (Foo::A | Foo::C).iter_names().collect::<Vec<_>>(),
+        ["A", "C"]
+    );
+    assert_eq!(Foo::default().iter_names().count(), 0);
+    assert_eq!(Foo::all().iter_names().collect::<Vec<_>>(), ["A", "B", "C"]);
+
+    flags_test!(Foo16, 9);
+
+    assert_eq!(Foo16::F8.to_bits(), 0x100_u16);
+    assert_eq!(Foo16::all().to_bits(), 0x1FF);
+    assert!(Foo16::from_bits(0x200).is_none());
+    assert_eq!(Foo16::from_bits_truncate(0xFFFF), Foo16::all());
+
+    flags_test!(Foo32Exact, 32);
+
+    assert_eq!(Foo32Exact::F31.to_bits(), 0x8000_0000_u32);
+    assert_eq!(Foo32Exact::all().to_bits(), u32::MAX);
+    assert_eq!(Foo32Exact::from_bits(u32::MAX), Some(Foo32Exact::all()));
+
+    // More than 32 flags use an array of words, least significant first
+
+    flags_test!(Foo96, 65);
+
+    assert_eq!((Foo96::F0 | Foo96::F33).to_bits(), [1, 2, 0]);
+    assert_eq!(Foo96::F64.to_bits(), [0, 0, 1]);
+    assert_eq!(Foo96::all().to_bits(), [u32::MAX, u32::MAX, 1]);
+    assert_eq!((!Foo96::F64).to_bits(), [u32::MAX, u32::MAX, 0]);
+
+    for bits in [[0, 0, 0], [1, 0, 0], [0, 0x8000_0000, 1], [u32::MAX, 7, 1]] {
+        let flags = Foo96::from_bits(bits).unwrap();
+        assert_eq!(flags.to_bits(), bits);
+    }
+    assert!(Foo96::from_bits([0, 0, 2]).is_none());
+    assert_eq!(
+        Foo96::from_bits_truncate([u32::MAX, u32::MAX, u32::MAX]),
+        Foo96::all()
+    );
+    assert!((Foo96::F1 | Foo96::F64).contains(Foo96::F64));
+    assert!(!(Foo96::F1 | Foo96::F64).contains(Foo96::F2));
+    assert_eq!(
+        (Foo96::F1 | Foo96::F40 | Foo96::F64)
+            .iter_names()
+            .collect::<Vec<_>>(),
+        ["F1", "F40", "F64"]
+    );
+
+    // Lowering and lifting are unaffected by the bit-level view
+
+    let engine = super::engine();
+    let mut store = Store::new(&engine, ());
+    let component = Component::new(
+        &engine,
+        make_echo_component(r#"(flags "foo-bar-baz" "B" "C")"#, 4),
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_typed_func::<(Foo,), Foo, _>(&mut store, "echo")?;
+
+    for bits in 0..=0b111 {
+        let input = Foo::from_bits(bits).unwrap();
+        let output = func.call_and_post_return(&mut store, (input,))?;
+        assert_eq!(output.to_bits(), bits);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pod_derive() -> Result<()> {
+    macro_rules! records {
+        ($($(#[$attr:meta])* struct $name:ident;)*) => ($(
+            #[derive(ComponentType, Lift, Lower, PartialEq, Debug, Copy, Clone)]
+            #[component(record)]
+            $(#[$attr])*
+            #[repr(C)]
+            struct $name {
+                a: f64,
+                b: u32,
+                c: f32,
+                d: i16,
+                e: u16,
+                f: i8,
+                g: u8,
+                h: i16,
+            }
+
+            impl $name {
+                fn bits(&self) -> (u64, u32, u32, i16, u16, i8, u8, i16) {
+                    (
+                        self.a.to_bits(),
+                        self.b,
+                        self.c.to_bits(),
+                        self.d,
+                        self.e,
+                        self.f,
+                        self.g,
+                        self.h,
+                    )
+                }
+            }
+        )*)
+    }
+
+    records! {
+        #[component(pod)]
+        struct Pod;
+        struct Slow;
+    }
+
+    // Note that `from-bytes` reinterprets a `list<u8>` as a list of records,
+    // which relies on the first allocation being aligned to 8 bytes.
+    let component = format!(
+        r#"(component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "echo") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0) (local.get 1))
+                    i32.const 0)
+                (func (export "from-bytes") (param i32 i32) (result i32)
+                    (i32.store offset=0 (i32.const 0) (local.get 0))
+                    (i32.store offset=4 (i32.const 0)
+                        (i32.div_u (local.get 1) (i32.const 24)))
+                    i32.const 0)
+
+                {REALLOC_AND_FREE}
+            )
+            (core instance $i (instantiate $m))
+            (type $rec (record
+                (field "a" float64)
+                (field "b" u32)
+                (field "c" float32)
+                (field "d" s16)
+                (field "e" u16)
+                (field "f" s8)
+                (field "g" u8)
+                (field "h" s16)
+            ))
+            (func (export "echo") (param (list $rec)) (result (list $rec))
+                (canon lift
+                    (core func $i "echo")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+            (func (export "from-bytes") (param (list u8)) (result (list $rec))
+                (canon lift
+                    (core func $i "from-bytes")
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                )
+            )
+        )"#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let linker = Linker::new(&engine);
+
+    // Arbitrary bytes, including NaNs with payloads which need to be
+    // canonicalized, lift identically through both paths.
+    let mut bytes = (0..24 * 100u32)
+        .map(|i| (i.wrapping_mul(151) ^ (i >> 3)) as u8)
+        .collect::<Vec<_>>();
+    bytes[0..8].copy_from_slice(&0x7ff8_0000_0000_0001u64.to_le_bytes());
+    bytes[12..16].copy_from_slice(&0xffc0_0001u32.to_le_bytes());
+    bytes[24..32].copy_from_slice(&f64::NAN.to_bits().to_le_bytes());
+
+    let instance = linker.instantiate(&mut store, &component)?;
+    let from_bytes =
+        instance.get_typed_func::<(&[u8],), WasmList<Slow>, _>(&mut store, "from-bytes")?;
+    let slow = from_bytes.call(&mut store, (&bytes,))?;
+    let expected = slow
+        .to_vec(&store)?
+        .iter()
+        .map(Slow::bits)
+        .collect::<Vec<_>>();
+    from_bytes.post_return(&mut store)?;
+
+    let instance = linker.instantiate(&mut store, &component)?;
+    let from_bytes =
+        instance.get_typed_func::<(&[u8],), WasmList<Pod>, _>(&mut store, "from-bytes")?;
+    let pods = from_bytes.call(&mut store, (&bytes,))?;
+    assert_eq!(pods.len(), 100);
+    let actual = pods
+        .to_vec(&store)?
+        .iter()
+        .map(Pod::bits)
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+    assert_eq!(actual[0].0, f64::NAN.to_bits());
+    assert_eq!(actual[0].2, f32::NAN.to_bits());
+    assert_eq!(actual[1].0, f64::NAN.to_bits());
+    assert_eq!(actual[2].3, i16::from_le_bytes([bytes[64], bytes[65]]));
+
+    let range = pods.get_range(&store, 7..42)?;
+    assert_eq!(
+        range.iter().map(Pod::bits).collect::<Vec<_>>(),
+        expected[7..42]
+    );
+    pods.for_each_chunk(&store, 30, |start, chunk| {
+        let chunk = chunk.iter().map(Pod::bits).collect::<Vec<_>>();
+        assert_eq!(chunk, expected[start..][..chunk.len()]);
+        Ok(())
+    })?;
+    from_bytes.post_return(&mut store)?;
+
+    // Lowering is unaffected.
+    let input = (0..100)
+        .map(|i| Pod {
+            a: f64::from(i) * 1.5,
+            b: i,
+            c: -(i as f32),
+            d: -(i as i16),
+            e: i as u16,
+            f: i as i8,
+            g: i as u8,
+            h: i as i16 * 2,
+        })
+        .collect::<Vec<_>>();
+    let echo = instance.get_typed_func::<(&[Pod],), WasmList<Pod>, _>(&mut store, "echo")?;
+    let output = echo.call(&mut store, (&input,))?;
+    assert_eq!(output.to_vec(&store)?, input);
+    echo.post_return(&mut store)?;
+
+    Ok(())
+}
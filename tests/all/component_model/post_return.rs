@@ -28,9 +28,9 @@ fn invalid_api() -> Result<()> {
     let thunk2 = instance.get_typed_func::<(), (), _>(&mut store, "thunk2")?;
 
     // Ensure that we can't call `post_return` before doing anything
-    let msg = "post_return can only be called after a function has previously been called";
-    assert_panics(|| drop(thunk1.post_return(&mut store)), msg);
-    assert_panics(|| drop(thunk2.post_return(&mut store)), msg);
+    let msg = "post_return called without a preceding successful call";
+    assert_err(thunk1.post_return(&mut store), msg);
+    assert_err(thunk2.post_return(&mut store), msg);
 
     // Schedule a "needs post return"
     thunk1.call(&mut store, ())?;
@@ -52,22 +52,33 @@ fn invalid_api() -> Result<()> {
         err
     );
 
-    // Calling post-return on the wrong function should panic
-    assert_panics(
-        || drop(thunk2.post_return(&mut store)),
-        "calling post_return on wrong function",
+    // Calling post-return on the wrong function is an error
+    assert_err(
+        thunk2.post_return(&mut store),
+        "post_return called on the wrong function",
     );
 
     // Actually execute the post-return
     thunk1.post_return(&mut store)?;
 
     // And now post-return should be invalid again.
-    assert_panics(|| drop(thunk1.post_return(&mut store)), msg);
-    assert_panics(|| drop(thunk2.post_return(&mut store)), msg);
+    assert_err(
+        thunk1.post_return(&mut store),
+        "post_return already completed for the previous call",
+    );
+    assert_err(thunk2.post_return(&mut store), msg);
 
     Ok(())
 }
 
+#[track_caller]
+fn assert_err(result: Result<()>, msg: &str) {
+    match result {
+        Ok(()) => panic!("expected an error"),
+        Err(e) => assert!(e.to_string().contains(msg), "bad error: {:?}", e),
+    }
+}
+
 #[track_caller]
 fn assert_panics(f: impl FnOnce(), msg: &str) {
     match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
@@ -140,6 +151,104 @@ fn invoke_post_return() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn guard_invokes_post_return() -> Result<()> {
+    let component = r#"
+        (component
+            (import "f" (func $f))
+
+            (core func $f_lower
+                (canon lower (func $f))
+            )
+            (core module $m
+                (import "" "" (func $f))
+                (memory (export "memory") 1)
+
+                (func (export "get") (result i32)
+                    (i32.store offset=0 (i32.const 8) (i32.const 100))
+                    (i32.store offset=4 (i32.const 8) (i32.const 11))
+                    i32.const 8
+                )
+
+                (func $post_return (param i32)
+                    call $f)
+                (export "post-return" (func $post_return))
+
+                (data (i32.const 100) "hello world")
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "" (func $f_lower))
+                ))
+            ))
+            (func (export "get") (result string)
+                (canon lift
+                    (core func $i "get")
+                    (post-return (func $i "post-return"))
+                    (memory $i "memory")
+                )
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, 0);
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap("f", |mut store: StoreContextMut<'_, u32>| -> Result<()> {
+            *store.data_mut() += 1;
+            Ok(())
+        })?;
+
+    let instance = linker.instantiate(&mut store, &component)?;
+    let get = instance.get_typed_func::<(), WasmStr, _>(&mut store, "get")?;
+
+    for i in 0..2 {
+        let ret = get.call_guarded(&mut store, ())?;
+        assert_eq!(ret.to_str(&store)?, "hello world");
+        assert_eq!(*store.data(), i);
+        ret.finish(&mut store)?;
+        assert_eq!(*store.data(), i + 1);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(
+    not(debug_assertions),
+    ignore = "the unfinished guard check is a debug assertion"
+)]
+fn guard_dropped_without_finish() -> Result<()> {
+    let component = r#"
+        (component
+            (core module $m
+                (func (export "thunk"))
+            )
+            (core instance $i (instantiate $m))
+            (func (export "thunk")
+                (canon lift (core func $i "thunk"))
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let thunk = instance.get_typed_func::<(), (), _>(&mut store, "thunk")?;
+
+    let ret = thunk.call_guarded(&mut store, ())?;
+    assert_panics(
+        || drop(ret),
+        "`ReturnValue` dropped without calling `ReturnValue::finish`",
+    );
+
+    Ok(())
+}
+
 #[test]
 fn post_return_all_types() -> Result<()> {
     let component = r#"
@@ -273,6 +382,9 @@ fn trap_in_post_return_poisons_instance() -> Result<()> {
                     (post-return (func $i "post"))
                 )
             )
+            (func (export "g")
+                (canon lift (core func $i "f"))
+            )
         )
     "#;
 
@@ -281,20 +393,415 @@ fn trap_in_post_return_poisons_instance() -> Result<()> {
     let mut store = Store::new(&engine, ());
     let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
     let f = instance.get_typed_func::<(), (), _>(&mut store, "f")?;
+    let g = instance.get_typed_func::<(), (), _>(&mut store, "g")?;
     f.call(&mut store, ())?;
     let trap = f.post_return(&mut store).unwrap_err().downcast::<Trap>()?;
     assert_eq!(trap.trap_code(), Some(TrapCode::UnreachableCodeReached));
+
+    // Every function of the instance reports the trap from now on.
+    let expected = "instance poisoned by trap in post-return of `f`: \
+                    wasm trap: wasm `unreachable` instruction executed";
     let err = f.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.to_string(), expected);
+    let poisoned = err.downcast::<InstancePoisoned>()?;
+    assert_eq!(poisoned.func_name(), "f");
+    assert_eq!(
+        poisoned.trap().trap_code(),
+        Some(TrapCode::UnreachableCodeReached)
+    );
+    assert_eq!(
+        poisoned.trap().trace().map(|t| t.len()),
+        trap.trace().map(|t| t.len())
+    );
+    assert_err(g.call(&mut store, ()), expected);
+    assert_err(
+        f.post_return(&mut store),
+        "post_return already completed for the previous call",
+    );
+
+    // Other instances aren't affected.
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let g = instance.get_typed_func::<(), (), _>(&mut store, "g")?;
+    g.call(&mut store, ())?;
+    g.post_return(&mut store)?;
+
+    // With deferred post-return the trap happens when the instance is next
+    // entered, which is reported as poisoning too.
+    let mut config = wasmtime::Config::new();
+    config.wasm_component_model(true);
+    config.component_model_deferred_post_return(true);
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let f = instance.get_typed_func::<(), (), _>(&mut store, "f")?;
+    let g = instance.get_typed_func::<(), (), _>(&mut store, "g")?;
+    f.call(&mut store, ())?;
+    assert_err(g.call(&mut store, ()), expected);
+    assert_err(f.call(&mut store, ()), expected);
+
+    Ok(())
+}
+
+#[test]
+fn call_and_post_return() -> Result<()> {
+    let component = r#"
+        (component
+            (core module $m
+                (memory (export "memory") 1)
+                (global $post_returns (mut i32) (i32.const 0))
+
+                (func (export "get-string") (result i32)
+                    (i32.store offset=0 (i32.const 8) (i32.const 100))
+                    (i32.store offset=4 (i32.const 8) (i32.const 11))
+                    i32.const 8
+                )
+                (func (export "post-string") (param i32)
+                    ;; clobber the string to ensure it was copied out first
+                    (i32.store8 (i32.const 100) (i32.const 0))
+                    (global.set $post_returns
+                        (i32.add (global.get $post_returns) (i32.const 1)))
+                )
+
+                (func (export "get-count") (result i32)
+                    global.get $post_returns)
+                (func (export "post-count") (param i32)
+                    (global.set $post_returns
+                        (i32.add (global.get $post_returns) (i32.const 1)))
+                )
+
+                (func (export "trap"))
+                (func (export "post-trap") unreachable)
+
+                (data (i32.const 100) "hello world")
+            )
+            (core instance $i (instantiate $m))
+            (func (export "get-string") (result string)
+                (canon lift
+                    (core func $i "get-string")
+                    (post-return (func $i "post-string"))
+                    (memory $i "memory")
+                )
+            )
+            (func (export "get-count") (result u32)
+                (canon lift
+                    (core func $i "get-count")
+                    (post-return (func $i "post-count"))
+                )
+            )
+            (func (export "trap")
+                (canon lift
+                    (core func $i "trap")
+                    (post-return (func $i "post-trap"))
+                )
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    // Owned strings are copied out of linear memory before post-return runs
+    let get_string = instance.get_typed_func::<(), String, _>(&mut store, "get-string")?;
+    assert_eq!(
+        get_string.call_and_post_return(&mut store, ())?,
+        "hello world"
+    );
+
+    // Each call runs post-return, so the instance can be reentered
+    let get_count = instance.get_typed_func::<(), u32, _>(&mut store, "get-count")?;
+    assert_eq!(get_count.call_and_post_return(&mut store, ())?, 1);
+    assert_eq!(get_count.call_and_post_return(&mut store, ())?, 2);
+
+    // A trap in post-return is reported with context distinguishing it from
+    // a trap in the call itself
+    let trap = instance.get_typed_func::<(), (), _>(&mut store, "trap")?;
+    let err = trap.call_and_post_return(&mut store, ()).unwrap_err();
+    assert!(
+        err.to_string().contains("failed to invoke post-return"),
+        "{}",
+        err
+    );
+    let trap = err.downcast::<Trap>()?;
+    assert_eq!(trap.trap_code(), Some(TrapCode::UnreachableCodeReached));
+
+    Ok(())
+}
+
+#[test]
+fn unit_functions_still_require_post_return() -> Result<()> {
+    let component = r#"
+        (component
+            (import "f" (func $f))
+
+            (core func $f_lower
+                (canon lower (func $f))
+            )
+            (core module $m
+                (import "" "" (func $f))
+
+                (func (export "thunk"))
+
+                (func $post_return
+                    call $f)
+                (export "post-return" (func $post_return))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "" (func $f_lower))
+                ))
+            ))
+            (func (export "unit")
+                (canon lift
+                    (core func $i "thunk")
+                    (post-return (func $i "post-return"))
+                )
+            )
+            (func (export "empty-tuple") (result (tuple))
+                (canon lift
+                    (core func $i "thunk")
+                    (post-return (func $i "post-return"))
+                )
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, 0);
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap("f", |mut store: StoreContextMut<'_, u32>| -> Result<()> {
+            *store.data_mut() += 1;
+            Ok(())
+        })?;
+    let instance = linker.instantiate(&mut store, &component)?;
+
+    // `()` typechecks against both a `unit` result and an empty tuple.
+    let unit = instance.get_typed_func::<(), (), _>(&mut store, "unit")?;
+    let empty_tuple = instance.get_typed_func::<(), (), _>(&mut store, "empty-tuple")?;
+
+    for (i, func) in [unit, empty_tuple].iter().enumerate() {
+        let expected = u32::try_from(i).unwrap();
+        func.call(&mut store, ())?;
+        assert_eq!(*store.data(), expected);
+
+        // The instance can't be entered again until post-return runs.
+        let err = func.call(&mut store, ()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("cannot reenter component instance"),
+            "{}",
+            err
+        );
+
+        func.post_return(&mut store)?;
+        assert_eq!(*store.data(), expected + 1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn deferred_post_return() -> Result<()> {
+    let component = r#"
+        (component
+            (import "log" (func $log (param u32)))
+
+            (core func $log_lower
+                (canon lower (func $log))
+            )
+            (core module $m
+                (import "" "log" (func $log (param i32)))
+
+                (func (export "a") (param i32) (result i32)
+                    (call $log (i32.const 1))
+                    local.get 0)
+                (func (export "post-return-a") (param i32)
+                    (call $log (i32.const 10)))
+                (func (export "b") (param i32) (result i32)
+                    (call $log (i32.const 2))
+                    local.get 0)
+                (func (export "post-return-b") (param i32)
+                    (call $log (i32.const 20)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "log" (func $log_lower))
+                ))
+            ))
+            (func (export "a") (param u32) (result u32)
+                (canon lift
+                    (core func $i "a")
+                    (post-return (func $i "post-return-a"))
+                )
+            )
+            (func (export "b") (param u32) (result u32)
+                (canon lift
+                    (core func $i "b")
+                    (post-return (func $i "post-return-b"))
+                )
+            )
+        )
+    "#;
+
+    let run = |deferred: bool| -> Result<_> {
+        let mut config = wasmtime::Config::new();
+        config.wasm_component_model(true);
+        config.component_model_deferred_post_return(deferred);
+        let engine = wasmtime::Engine::new(&config)?;
+        let component = Component::new(&engine, component)?;
+        let mut store = Store::new(&engine, Vec::new());
+        let mut linker = Linker::new(&engine);
+        linker.root().func_wrap(
+            "log",
+            |mut store: StoreContextMut<'_, Vec<u32>>, n: u32| -> Result<()> {
+                store.data_mut().push(n);
+                Ok(())
+            },
+        )?;
+        let instance = linker.instantiate(&mut store, &component)?;
+        let a = instance.get_typed_func::<(u32,), u32, _>(&mut store, "a")?;
+        let b = instance.get_typed_func::<(u32,), u32, _>(&mut store, "b")?;
+        Ok((store, a, b))
+    };
+
+    // By default calling into the instance again without `post_return` is an
+    // error.
+    let (mut store, a, b) = run(false)?;
+    assert_eq!(a.call(&mut store, (100,))?, 100);
+    let err = b.call(&mut store, (200,)).unwrap_err();
     assert!(
         err.to_string()
             .contains("cannot reenter component instance"),
         "{}",
         err
     );
-    assert_panics(
-        || drop(f.post_return(&mut store)),
-        "can only be called after",
+    assert_eq!(*store.data(), [1]);
+
+    // In deferred mode the post-return of the previous call runs right before
+    // the instance is entered again.
+    let (mut store, a, b) = run(true)?;
+    assert_eq!(a.call(&mut store, (100,))?, 100);
+    assert_eq!(*store.data(), [1]);
+    assert_eq!(b.call(&mut store, (200,))?, 200);
+    assert_eq!(*store.data(), [1, 10, 2]);
+    assert_eq!(a.call(&mut store, (300,))?, 300);
+    assert_eq!(b.call(&mut store, (400,))?, 400);
+    assert_eq!(*store.data(), [1, 10, 2, 20, 1, 10, 2]);
+
+    // Flushing runs the pending post-return, and afterwards there's nothing
+    // left to flush.
+    store.flush_post_returns()?;
+    assert_eq!(*store.data(), [1, 10, 2, 20, 1, 10, 2, 20]);
+    store.flush_post_returns()?;
+    assert_eq!(store.data().len(), 8);
+
+    // Explicit post-returns still work and aren't run a second time.
+    store.data_mut().clear();
+    assert_eq!(a.call(&mut store, (500,))?, 500);
+    a.post_return(&mut store)?;
+    assert_eq!(b.call_and_post_return(&mut store, (600,))?, 600);
+    store.flush_post_returns()?;
+    assert_eq!(*store.data(), [1, 10, 2, 20]);
+
+    // Once a deferred post-return has run it can't be run again explicitly.
+    assert_eq!(a.call(&mut store, (700,))?, 700);
+    store.flush_post_returns()?;
+    assert_err(
+        a.post_return(&mut store),
+        "post_return already completed for the previous call",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn post_return_misuse() -> Result<()> {
+    let component = r#"
+        (component
+            (import "f" (func $f))
+
+            (core func $f_lower
+                (canon lower (func $f))
+            )
+            (core module $m
+                (import "" "" (func $f))
+
+                (func (export "thunk"))
+                (func (export "ret-char") (result i32)
+                    i32.const 0xd800)
+
+                (func $post_return
+                    call $f)
+                (export "post-return" (func $post_return))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "" (func $f_lower))
+                ))
+            ))
+            (func (export "thunk")
+                (canon lift
+                    (core func $i "thunk")
+                    (post-return (func $i "post-return"))
+                )
+            )
+            (func (export "ret-char") (result char)
+                (canon lift
+                    (core func $i "ret-char")
+                    (post-return (func $i "post-return"))
+                )
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, 0);
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap("f", |mut store: StoreContextMut<'_, u32>| -> Result<()> {
+            *store.data_mut() += 1;
+            Ok(())
+        })?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    let thunk = instance.get_typed_func::<(), (), _>(&mut store, "thunk")?;
+
+    // `post_return` without any call is an error, and doesn't prevent calling
+    // the function afterwards.
+    assert_err(
+        thunk.post_return(&mut store),
+        "post_return called without a preceding successful call",
+    );
+    thunk.call(&mut store, ())?;
+    assert_eq!(*store.data(), 0);
+
+    // Calling `post_return` twice runs it only once, and the second call is an
+    // error which doesn't affect subsequent calls.
+    thunk.post_return(&mut store)?;
+    assert_eq!(*store.data(), 1);
+    assert_err(
+        thunk.post_return(&mut store),
+        "post_return already completed for the previous call",
+    );
+    assert_eq!(*store.data(), 1);
+    thunk.call_and_post_return(&mut store, ())?;
+    assert_eq!(*store.data(), 2);
+
+    // After a failed call there's no post-return to run, so `post_return` is an
+    // error as well.
+    let ret_char = instance.get_typed_func::<(), char, _>(&mut store, "ret-char")?;
+    ret_char.call(&mut store, ()).unwrap_err();
+    assert_err(
+        ret_char.post_return(&mut store),
+        "post_return called without a preceding successful call: the previous call failed",
     );
+    assert_eq!(*store.data(), 2);
 
     Ok(())
 }